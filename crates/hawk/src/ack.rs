@@ -0,0 +1,379 @@
+// ▛▞// hawk acks :: hawk.ack
+// @ctx ⫸ [ack.silence.persist.broadcast]
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use hawk_core::{HawkFrame, Level, ACK_KIND};
+use time::format_description::well_known::Rfc3339;
+use time::{Duration, OffsetDateTime};
+
+// :: ∎
+
+// ▛▞// ack model :: hawk.ack.model
+// @ctx ⫸ [ack.frame.kind]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AckOp {
+    /// One entity, exact scope and id.
+    Ack,
+    /// Scope/id glob patterns (`*`, `?`).
+    Silence,
+    /// Remove acks and silences with the same scope/id pattern.
+    Unack,
+}
+
+impl AckOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AckOp::Ack => "ack",
+            AckOp::Silence => "silence",
+            AckOp::Unack => "unack",
+        }
+    }
+
+    fn parse(s: &str) -> Option<AckOp> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ack" => Some(AckOp::Ack),
+            "silence" => Some(AckOp::Silence),
+            "unack" => Some(AckOp::Unack),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Ack {
+    pub op: AckOp,
+    pub scope: String,
+    pub id: String,
+    pub note: String,
+    pub by: String,
+    pub at: OffsetDateTime,
+    pub until: Option<OffsetDateTime>,
+}
+
+impl Ack {
+    pub fn new(
+        op: AckOp,
+        scope: &str,
+        id: &str,
+        note: &str,
+        ttl: Option<Duration>,
+        now: OffsetDateTime,
+    ) -> Self {
+        Self {
+            op,
+            scope: scope.to_string(),
+            id: id.to_string(),
+            note: note.replace(['\t', '\n', '\r'], " "),
+            by: std::env::var("USER").unwrap_or_default(),
+            at: now,
+            // Past the calendar's end it never expires anyway.
+            until: ttl.and_then(|d| now.checked_add(d)),
+        }
+    }
+
+    pub fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.until.is_some_and(|u| u <= now)
+    }
+
+    /// Only silences glob; an ack names one entity, even if its id has `*`.
+    pub fn matches(&self, scope: &str, id: &str) -> bool {
+        match self.op {
+            AckOp::Silence => glob_match(&self.scope, scope) && glob_match(&self.id, id),
+            AckOp::Ack | AckOp::Unack => self.scope == scope && self.id == id,
+        }
+    }
+
+    pub fn to_frame(&self) -> HawkFrame {
+        let mut kv = BTreeMap::new();
+        kv.insert("op".to_string(), self.op.as_str().to_string());
+        if !self.by.is_empty() {
+            kv.insert("by".to_string(), self.by.clone());
+        }
+        if let Some(u) = self.until {
+            kv.insert("until".to_string(), u.format(&Rfc3339).unwrap_or_default());
+        }
+
+        HawkFrame {
+            ts: Some(self.at),
+            kind: ACK_KIND.to_string(),
+            scope: self.scope.clone(),
+            id: self.id.clone(),
+            level: Level::Info,
+            level_raw: None,
+            msg: self.note.clone(),
+            kv,
+        }
+    }
+
+    /// Returns None for frames that are not well-formed ACK frames.
+    pub fn from_frame(f: &HawkFrame, now: OffsetDateTime) -> Option<Ack> {
+        if f.kind != ACK_KIND {
+            return None;
+        }
+        let op = AckOp::parse(f.kv.get("op").map(String::as_str).unwrap_or("ack"))?;
+        let until = match f.kv.get("until") {
+            Some(raw) => Some(OffsetDateTime::parse(raw, &Rfc3339).ok()?),
+            None => None,
+        };
+
+        Some(Ack {
+            op,
+            scope: f.scope.clone(),
+            id: f.id.clone(),
+            note: f.msg.clone(),
+            by: f.kv.get("by").cloned().unwrap_or_default(),
+            at: f.ts.unwrap_or(now),
+            until,
+        })
+    }
+}
+// :: ∎
+
+// ▛▞// ack store :: hawk.ack.store
+// ⫸ [apply.match.save.publish]
+#[derive(Debug, Default)]
+pub struct AckStore {
+    pub entries: Vec<Ack>,
+    /// Acks file (ACK frames as TSV). None disables persistence.
+    pub path: Option<PathBuf>,
+    /// Write changes back to `path`. Only the live TUI sets this; status
+    /// and replay read the file but never rewrite it.
+    pub persist: bool,
+    /// hawkd ingest socket to publish acks to. None keeps acks local.
+    pub publish: Option<PathBuf>,
+}
+
+impl AckStore {
+    pub fn load(path: Option<PathBuf>, publish: Option<PathBuf>) -> anyhow::Result<Self> {
+        let mut store = AckStore {
+            entries: Vec::new(),
+            path,
+            persist: false,
+            publish,
+        };
+
+        let Some(p) = store.path.clone() else {
+            return Ok(store);
+        };
+        if !p.exists() {
+            return Ok(store);
+        }
+
+        let raw = fs::read_to_string(&p).with_context(|| format!("read acks {:?}", p))?;
+        let now = OffsetDateTime::now_utc();
+        for line in raw.lines() {
+            if let Ok(Some(f)) = HawkFrame::parse_tsv_line(line) {
+                if let Some(a) = Ack::from_frame(&f, now) {
+                    store.apply(a);
+                }
+            }
+        }
+        store.prune(now);
+        Ok(store)
+    }
+
+    /// Insert or remove by scope/id pattern. Returns true when the set changed.
+    pub fn apply(&mut self, ack: Ack) -> bool {
+        let before = self.entries.len();
+        self.entries
+            .retain(|a| !(a.scope == ack.scope && a.id == ack.id));
+        if ack.op == AckOp::Unack {
+            return self.entries.len() != before;
+        }
+        self.entries.push(ack);
+        true
+    }
+
+    pub fn prune(&mut self, now: OffsetDateTime) {
+        self.entries.retain(|a| !a.is_expired(now));
+    }
+
+    pub fn find(&self, scope: &str, id: &str, now: OffsetDateTime) -> Option<&Ack> {
+        self.entries
+            .iter()
+            .find(|a| !a.is_expired(now) && a.matches(scope, id))
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(p) = self.path.as_ref().filter(|_| self.persist) else {
+            return Ok(());
+        };
+        if let Some(dir) = p.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("create {:?}", dir))?;
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let mut out = String::new();
+        for a in &self.entries {
+            out.push_str(&a.to_frame().to_tsv_line(now));
+            out.push('\n');
+        }
+        fs::write(p, out).with_context(|| format!("write acks {:?}", p))
+    }
+
+    /// Fire-and-forget publish so other mirrors on the same hawkd see the ack.
+    pub fn publish(&self, ack: &Ack) {
+        #[cfg(unix)]
+        if let Some(path) = self.publish.clone() {
+            let line = ack.to_frame().to_tsv_line(OffsetDateTime::now_utc()) + "\n";
+            std::thread::spawn(move || {
+                if let Ok(mut s) = std::os::unix::net::UnixStream::connect(&path) {
+                    let _ = s.write_all(line.as_bytes());
+                    let _ = s.flush();
+                }
+            });
+        }
+        #[cfg(not(unix))]
+        let _ = ack;
+    }
+}
+
+/// Default acks file: $XDG_STATE_HOME/hawk/acks.tsv or ~/.local/state/hawk/acks.tsv.
+pub fn default_acks_path() -> Option<PathBuf> {
+    match std::env::var_os("XDG_STATE_HOME") {
+        Some(v) if !v.is_empty() => Some(PathBuf::from(v).join("hawk").join("acks.tsv")),
+        _ => std::env::var_os("HOME").map(|h| {
+            PathBuf::from(h)
+                .join(".local")
+                .join("state")
+                .join("hawk")
+                .join("acks.tsv")
+        }),
+    }
+}
+// :: ∎
+
+// ▛▞// helpers :: hawk.ack.helpers
+// ⫸ [glob.duration.prompt]
+/// Minimal glob: `*` matches any run, `?` matches one char.
+pub fn glob_match(pattern: &str, s: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = s.chars().collect();
+
+    let (mut pi, mut ti) = (0usize, 0usize);
+    let mut star: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+
+    while pi < p.len() && p[pi] == '*' {
+        pi += 1;
+    }
+    pi == p.len()
+}
+
+/// Durations like `90s`, `30m`, `2h`, `1d`. A bare number is seconds.
+/// `None` when malformed or too large to represent.
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let t = s.trim();
+    if t.is_empty() {
+        return None;
+    }
+    let (num, unit) = match t.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => t.split_at(i),
+        None => (t, "s"),
+    };
+    let n: i64 = num.parse().ok()?;
+    let per = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return None,
+    };
+    n.checked_mul(per).map(Duration::seconds)
+}
+
+/// Split prompt input into an optional leading duration and the note.
+/// `2h planned maintenance` -> (Some(2h), "planned maintenance")
+pub fn split_ttl_note(input: &str) -> (Option<Duration>, String) {
+    let t = input.trim();
+    let (first, rest) = t.split_once(' ').unwrap_or((t, ""));
+    match parse_duration(first) {
+        Some(d) => (Some(d), rest.trim().to_string()),
+        None => (None, t.to_string()),
+    }
+}
+// :: ∎
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matches_wildcards() {
+        assert!(glob_match("db-*", "db-primary"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("unit-?.service", "unit-a.service"));
+        assert!(!glob_match("db-*", "cache-1"));
+    }
+
+    #[test]
+    fn ack_frame_round_trips() {
+        let now = OffsetDateTime::now_utc();
+        let a = Ack::new(
+            AckOp::Silence,
+            "systemd",
+            "db-*",
+            "migration",
+            parse_duration("2h"),
+            now,
+        );
+        let line = a.to_frame().to_tsv_line(now);
+        let f = HawkFrame::parse_tsv_line(&line).unwrap().unwrap();
+        let b = Ack::from_frame(&f, now).unwrap();
+        assert_eq!(b.op, AckOp::Silence);
+        assert_eq!(b.note, "migration");
+        assert!(b.matches("systemd", "db-replica"));
+        assert!(!b.is_expired(now));
+        assert!(b.is_expired(now + Duration::hours(3)));
+    }
+
+    #[test]
+    fn huge_durations_are_rejected_not_a_panic() {
+        assert_eq!(parse_duration("90"), Some(Duration::seconds(90)));
+        assert_eq!(parse_duration("1d"), Some(Duration::days(1)));
+        assert_eq!(parse_duration("9223372036854775807d"), None);
+        assert_eq!(parse_duration("9223372036854775808"), None);
+        assert_eq!(parse_duration("2x"), None);
+
+        // Parses, but lands past the calendar's end: never expires.
+        let now = OffsetDateTime::now_utc();
+        let ttl = parse_duration("9223372036854775807");
+        let a = Ack::new(AckOp::Ack, "grpc", "alpha", "", ttl, now);
+        assert!(a.until.is_none());
+    }
+
+    #[test]
+    fn unack_removes_pattern() {
+        let now = OffsetDateTime::now_utc();
+        let mut store = AckStore::default();
+        store.apply(Ack::new(AckOp::Ack, "grpc", "alpha", "", None, now));
+        assert!(store.find("grpc", "alpha", now).is_some());
+        assert!(store.apply(Ack::new(AckOp::Unack, "grpc", "alpha", "", None, now)));
+        assert!(store.find("grpc", "alpha", now).is_none());
+
+        // An exact ack on a literal `*` id covers that entity only.
+        store.apply(Ack::new(AckOp::Ack, "docker", "web-*", "", None, now));
+        assert!(store.find("docker", "web-*", now).is_some());
+        assert!(store.find("docker", "web-1", now).is_none());
+    }
+}
+// :: ∎
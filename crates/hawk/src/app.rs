@@ -1,11 +1,18 @@
 // ▛▞// hawk app state :: hawk.app
 // @ctx ⫸ [entity.liveness.sort]
 use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
 
-use hawk_core::{HawkFrame, Level, LevelRank};
+use hawk_core::{HawkFrame, Level, LevelRank, RejectedLine, RepairedLine, RETIRE_KIND};
 use time::OffsetDateTime;
 
-use crate::io::IngestMsg;
+use crate::ack::{self, Ack, AckOp, AckStore};
+use crate::awk::RunnerStats;
+use crate::capture::ReplayHandle;
+use crate::clock::{Clock, SystemClock};
+use crate::io::{IngestMsg, SourceStats};
+use crate::packs::{self, PackIndex, ThreadOutput, ThreadResolved};
+use crate::pipeline::{self, Stage, TransformSwitch};
 
 #[derive(Debug, Clone)]
 pub struct EntityState {
@@ -18,19 +25,154 @@ pub struct EntityState {
     pub kv: BTreeMap<String, String>,
 }
 
+impl EntityState {
+    /// Entity key and state for a frame; frames without ts are seen `now`.
+    fn from_frame(f: HawkFrame, now: OffsetDateTime) -> (String, EntityState) {
+        let HawkFrame {
+            ts,
+            kind,
+            scope,
+            id,
+            level,
+            msg,
+            kv,
+            ..
+        } = f;
+
+        let key = entity_key(&scope, &id);
+        let st = EntityState {
+            scope,
+            id,
+            last_level: level,
+            last_msg: msg,
+            last_seen: ts.unwrap_or(now),
+            kind,
+            kv,
+        };
+        (key, st)
+    }
+}
+
 #[derive(Debug)]
 pub struct App {
-    pub tail: VecDeque<String>,
+    /// Newest first. Holds up to `history_size` frames; the live view shows `tail_size`.
+    pub tail: VecDeque<HawkFrame>,
     pub tail_size: usize,
+    pub history_size: usize,
+    /// Frames between the newest and the top of the tail view.
+    pub tail_scroll: usize,
+    pub tail_paused: bool,
+
+    /// Selected entity key (scope:id), stable across re-sorts.
+    pub selected: Option<String>,
 
     pub frames_seen: u64,
     pub parse_errors: u64,
     pub io_errors: u64,
+    /// Lines accepted only after parser repairs.
+    pub repaired_lines: u64,
+    /// Recent rejected and repaired lines, newest first, for the error panel.
+    pub line_issues: VecDeque<LineIssue>,
+    pub show_errors: bool,
 
     pub entities: BTreeMap<String, EntityState>, // key = scope:id
+    /// Transform output kept apart from raw entities (`output = "panel"`).
+    pub derived: BTreeMap<String, EntityState>,
 
     pub stale_s: i64,
     pub dead_s: i64,
+    /// Drop entities unseen for this long. 0 keeps them forever.
+    pub forget_s: i64,
+    /// Per-scope forget TTL, wins over `forget_s`.
+    pub forget_by_scope: BTreeMap<String, i64>,
+    pub entities_forgotten: u64,
+
+    pub acks: AckStore,
+    /// Active input prompt (ack note, silence pattern).
+    pub prompt: Option<Prompt>,
+    /// Last ack/prompt outcome, shown in the footer.
+    pub status: Option<String>,
+
+    /// Set when the mirror is driven by `hawk replay`.
+    pub replay: Option<ReplayHandle>,
+
+    /// Source of "now" for liveness, ages, acks and forget TTLs.
+    pub clock: Arc<dyn Clock>,
+
+    /// Live readers feeding the mirror; empty for replays.
+    pub sources: Vec<Arc<SourceStats>>,
+
+    /// Supervised awk pipeline stages in order; empty without a transform.
+    pub transforms: Vec<Arc<RunnerStats>>,
+    pub show_pipeline: bool,
+    /// Swaps the live transform; None for replays.
+    pub switch: Option<SwitchHandle>,
+    /// Thread picker (`T`), open when set.
+    pub picker: Option<Picker>,
+}
+
+/// Line issues kept for the error panel.
+pub const LINE_ISSUES_KEEP: usize = 200;
+
+#[derive(Debug, Clone)]
+pub enum LineIssue {
+    Rejected(RejectedLine),
+    Repaired(RepairedLine),
+}
+
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum PromptKind {
+    /// Ack one entity: `[ttl] note`.
+    Ack { scope: String, id: String },
+    /// Silence a glob: `scope:id-glob [ttl] note`.
+    Silence,
+}
+
+#[derive(Debug, Clone)]
+pub struct SwitchHandle {
+    pub switch: TransformSwitch,
+    pub packs: PackIndex,
+    /// `--transform-output`; wins over the picked thread's own mode.
+    pub output: Option<ThreadOutput>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Picker {
+    /// Sorted by id. Row 0 of the list is `none`, thread i is row i + 1.
+    pub threads: Vec<ThreadResolved>,
+    pub selected: usize,
+    /// Set once a thread with args is chosen.
+    pub form: Option<ArgsForm>,
+    /// Last validation or spawn error, shown in the popup.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArgsForm {
+    pub thread: ThreadResolved,
+    /// One value per `thread.args`, prefilled from defaults.
+    pub values: Vec<String>,
+    pub focus: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateCounts {
+    pub total: u64,
+    pub ok: u64,
+    pub warn: u64,
+    pub fail: u64,
+    /// Fresh entities whose level is not one hawk recognises.
+    pub unknown: u64,
+    pub stale: u64,
+    pub dead: u64,
+    /// Acked or silenced; excluded from the buckets above.
+    pub acked: u64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -41,70 +183,499 @@ pub enum Liveness {
     Dead,
 }
 
+impl Liveness {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Liveness::Active => "active",
+            Liveness::Dream => "dream",
+            Liveness::Stale => "stale",
+            Liveness::Dead => "dead",
+        }
+    }
+}
+
 impl App {
-    pub fn new(tail_size: usize, stale_s: i64, dead_s: i64) -> Self {
+    pub fn new(tail_size: usize, history_size: usize, stale_s: i64, dead_s: i64) -> Self {
+        let history_size = history_size.max(tail_size);
         Self {
-            tail: VecDeque::with_capacity(tail_size),
+            tail: VecDeque::with_capacity(history_size),
             tail_size,
+            history_size,
+            tail_scroll: 0,
+            tail_paused: false,
+            selected: None,
             frames_seen: 0,
             parse_errors: 0,
             io_errors: 0,
+            repaired_lines: 0,
+            line_issues: VecDeque::new(),
+            show_errors: false,
             entities: BTreeMap::new(),
+            derived: BTreeMap::new(),
             stale_s,
             dead_s,
+            forget_s: 0,
+            forget_by_scope: BTreeMap::new(),
+            entities_forgotten: 0,
+            acks: AckStore::default(),
+            prompt: None,
+            status: None,
+            replay: None,
+            clock: Arc::new(SystemClock),
+            sources: Vec::new(),
+            transforms: Vec::new(),
+            show_pipeline: false,
+            switch: None,
+            picker: None,
         }
     }
 
+    pub fn now(&self) -> OffsetDateTime {
+        self.clock.now()
+    }
+
     pub fn ingest(&mut self, msg: IngestMsg) {
         match msg {
             IngestMsg::Frame(f) => self.ingest_frame(f),
-            IngestMsg::ParseError(_) => self.parse_errors += 1,
+            IngestMsg::ParseError(r) => {
+                self.parse_errors += 1;
+                self.push_issue(LineIssue::Rejected(r));
+            }
+            IngestMsg::Repaired(r) => {
+                self.repaired_lines += 1;
+                self.push_issue(LineIssue::Repaired(r));
+            }
             IngestMsg::IoError(_) => self.io_errors += 1,
+            IngestMsg::Derived(f) => self.ingest_derived(f),
+            IngestMsg::ReplayDone => {}
+            IngestMsg::Reset => self.reset(),
         }
     }
 
     fn ingest_frame(&mut self, f: HawkFrame) {
         self.frames_seen += 1;
 
-        let compact = f.to_compact_line();
+        self.push_tail(f.clone());
 
-        let HawkFrame {
-            ts,
-            kind,
-            scope,
-            id,
-            level,
-            msg,
-            kv,
-        } = f;
+        let now = self.now();
+        if let Some(a) = Ack::from_frame(&f, now) {
+            // Acks from other mirrors (via hawkd) or our own echo.
+            if self.acks.apply(a) {
+                self.save_acks();
+            }
+            return;
+        }
 
-        let now = OffsetDateTime::now_utc();
-        let seen = ts.unwrap_or(now);
+        if f.kind == RETIRE_KIND {
+            self.remove_entity(&entity_key(&f.scope, &f.id));
+            return;
+        }
 
-        let key = format!("{}:{}", scope, id);
-        let st = EntityState {
-            scope,
-            id,
-            last_level: level,
-            last_msg: msg,
-            last_seen: seen,
-            kind,
-            kv,
-        };
+        let (key, st) = EntityState::from_frame(f, now);
         self.entities.insert(key, st);
+    }
 
-        self.push_tail(compact);
+    fn ingest_derived(&mut self, f: HawkFrame) {
+        if f.kind == RETIRE_KIND {
+            self.derived.remove(&entity_key(&f.scope, &f.id));
+            return;
+        }
+        let (key, st) = EntityState::from_frame(f, self.now());
+        self.derived.insert(key, st);
     }
 
-    fn push_tail(&mut self, line: String) {
-        self.tail.push_front(line);
-        while self.tail.len() > self.tail_size {
+    /// Derived panel rows: worst level first, then scope and id.
+    pub fn sorted_derived(&self) -> Vec<&EntityState> {
+        let mut v: Vec<&EntityState> = self.derived.values().collect();
+        v.sort_by(|a, b| {
+            LevelRank::from(a.last_level)
+                .cmp(&LevelRank::from(b.last_level))
+                .then_with(|| a.scope.cmp(&b.scope))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        v
+    }
+
+    /// Forget everything learned from the stream. Acks are kept.
+    pub fn reset(&mut self) {
+        self.entities.clear();
+        self.derived.clear();
+        self.tail.clear();
+        self.tail_scroll = 0;
+        self.frames_seen = 0;
+        self.parse_errors = 0;
+        self.repaired_lines = 0;
+        self.line_issues.clear();
+        self.io_errors = 0;
+        self.entities_forgotten = 0;
+        self.selected = None;
+    }
+
+    fn push_tail(&mut self, frame: HawkFrame) {
+        self.tail.push_front(frame);
+        // Keep a paused view pinned to the same frames while ingest continues.
+        if self.tail_paused {
+            self.tail_scroll += 1;
+        }
+        while self.tail.len() > self.history_size {
             self.tail.pop_back();
         }
+        self.tail_scroll = self.tail_scroll.min(self.tail.len().saturating_sub(1));
+    }
+
+    fn push_issue(&mut self, issue: LineIssue) {
+        self.line_issues.push_front(issue);
+        self.line_issues.truncate(LINE_ISSUES_KEEP);
+    }
+
+    pub fn toggle_errors(&mut self) {
+        self.show_errors = !self.show_errors;
+    }
+
+    pub fn toggle_pipeline(&mut self) {
+        self.show_pipeline = !self.show_pipeline;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if self.tail_paused {
+            self.resume_tail();
+        } else {
+            self.tail_paused = true;
+        }
+    }
+
+    /// Back to live: unpause and jump to the newest frame.
+    pub fn resume_tail(&mut self) {
+        self.tail_paused = false;
+        self.tail_scroll = 0;
+    }
+
+    /// Positive delta scrolls back into history. Scrolling back pauses the view.
+    pub fn scroll_tail(&mut self, delta: isize) {
+        let max = self.tail.len().saturating_sub(1);
+        let next = if delta >= 0 {
+            self.tail_scroll.saturating_add(delta as usize).min(max)
+        } else {
+            self.tail_scroll.saturating_sub(delta.unsigned_abs())
+        };
+        self.tail_scroll = next;
+        if next > 0 {
+            self.tail_paused = true;
+        }
+    }
+
+    /// Frames for the tail view, newest first, honoring scroll.
+    pub fn tail_view(&self) -> impl Iterator<Item = &HawkFrame> {
+        self.tail.iter().skip(self.tail_scroll).take(self.tail_size)
+    }
+
+    pub fn select_next(&mut self) {
+        self.move_selection(1);
+    }
+
+    pub fn select_prev(&mut self) {
+        self.move_selection(-1);
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let keys: Vec<String> = self
+            .sorted_entities()
+            .iter()
+            .map(|st| entity_key(&st.scope, &st.id))
+            .collect();
+        if keys.is_empty() {
+            self.selected = None;
+            return;
+        }
+
+        let cur = self
+            .selected
+            .as_ref()
+            .and_then(|k| keys.iter().position(|x| x == k));
+        let next = match cur {
+            None => 0,
+            Some(i) => (i as isize + delta).clamp(0, keys.len() as isize - 1) as usize,
+        };
+        self.selected = Some(keys[next].clone());
+    }
+
+    pub fn selected_entity(&self) -> Option<&EntityState> {
+        self.selected.as_ref().and_then(|k| self.entities.get(k))
+    }
+
+    pub fn ack_for(&self, st: &EntityState, now: OffsetDateTime) -> Option<&Ack> {
+        self.acks.find(&st.scope, &st.id, now)
+    }
+
+    pub fn open_ack_prompt(&mut self) {
+        if let Some(st) = self.selected_entity() {
+            let kind = PromptKind::Ack {
+                scope: st.scope.clone(),
+                id: st.id.clone(),
+            };
+            self.prompt = Some(Prompt {
+                kind,
+                input: String::new(),
+            });
+        }
+    }
+
+    pub fn open_silence_prompt(&mut self) {
+        // Prefill with the selected entity's scope so a glob is one edit away.
+        let input = self
+            .selected_entity()
+            .map(|st| format!("{}:", st.scope))
+            .unwrap_or_default();
+        self.prompt = Some(Prompt {
+            kind: PromptKind::Silence,
+            input,
+        });
+    }
+
+    pub fn submit_prompt(&mut self) {
+        let Some(p) = self.prompt.take() else {
+            return;
+        };
+        let now = self.now();
+
+        let ack = match p.kind {
+            PromptKind::Ack { scope, id } => {
+                let (ttl, note) = ack::split_ttl_note(&p.input);
+                Ack::new(AckOp::Ack, &scope, &id, &note, ttl, now)
+            }
+            PromptKind::Silence => {
+                let input = p.input.trim();
+                let (pattern, rest) = input.split_once(' ').unwrap_or((input, ""));
+                let Some((scope, id)) = pattern.split_once(':') else {
+                    self.status = Some("silence expects scope:id-glob [ttl] note".to_string());
+                    return;
+                };
+                let (ttl, note) = ack::split_ttl_note(rest);
+                let id = if id.is_empty() { "*" } else { id };
+                Ack::new(AckOp::Silence, scope, id, &note, ttl, now)
+            }
+        };
+
+        self.status = Some(format!("{} {}:{}", ack.op.as_str(), ack.scope, ack.id));
+        self.acks.publish(&ack);
+        self.acks.apply(ack);
+        self.save_acks();
+    }
+
+    pub fn open_picker(&mut self) {
+        let Some(h) = &self.switch else {
+            self.status = Some("transform switching needs live sources".to_string());
+            return;
+        };
+        let mut threads: Vec<ThreadResolved> = h.packs.threads.values().cloned().collect();
+        threads.sort_by(|a, b| (&a.thread_id, &a.pack_id).cmp(&(&b.thread_id, &b.pack_id)));
+        let current = h.switch.label();
+        let selected = threads
+            .iter()
+            .position(|t| {
+                current == format!("thread:{}", t.qualified_id())
+                    || current == format!("thread:{}", t.thread_id)
+            })
+            .map_or(0, |i| i + 1);
+        self.picker = Some(Picker {
+            threads,
+            selected,
+            form: None,
+            error: None,
+        });
+    }
+
+    /// Moves the list selection, or the focused field in the args form.
+    pub fn picker_move(&mut self, delta: isize) {
+        let Some(p) = self.picker.as_mut() else {
+            return;
+        };
+        let (pos, len) = match p.form.as_mut() {
+            Some(form) => (&mut form.focus, form.values.len()),
+            None => (&mut p.selected, p.threads.len() + 1),
+        };
+        if len > 0 {
+            *pos = (*pos as isize + delta).rem_euclid(len as isize) as usize;
+        }
+    }
+
+    pub fn picker_input(&mut self, c: Option<char>) {
+        let Some(form) = self.picker.as_mut().and_then(|p| p.form.as_mut()) else {
+            return;
+        };
+        let Some(v) = form.values.get_mut(form.focus) else {
+            return;
+        };
+        match c {
+            Some(c) => v.push(c),
+            None => {
+                v.pop();
+            }
+        }
+    }
+
+    /// Esc: leave the args form, or close the picker.
+    pub fn picker_back(&mut self) {
+        match self.picker.as_mut() {
+            Some(p) if p.form.is_some() => {
+                p.form = None;
+                p.error = None;
+            }
+            _ => self.picker = None,
+        }
+    }
+
+    /// Enter: pick the selected row, or submit the args form.
+    pub fn picker_submit(&mut self) {
+        let Some(p) = self.picker.as_mut() else {
+            return;
+        };
+        let (thread, values) = match p.form.take() {
+            Some(form) => (Some(form.thread), form.values),
+            None => match p.selected.checked_sub(1).and_then(|i| p.threads.get(i)) {
+                None => (None, Vec::new()),
+                Some(th) if th.args.is_empty() => (Some(th.clone()), Vec::new()),
+                Some(th) => {
+                    p.form = Some(ArgsForm {
+                        thread: th.clone(),
+                        values: th.args.iter().map(|a| a.default.clone()).collect(),
+                        focus: 0,
+                    });
+                    p.error = None;
+                    return;
+                }
+            },
+        };
+
+        if let Err(e) = self.switch_transform(thread.as_ref(), &values) {
+            if let Some(p) = self.picker.as_mut() {
+                if let Some(th) = thread {
+                    p.form = Some(ArgsForm {
+                        thread: th,
+                        values,
+                        focus: 0,
+                    });
+                }
+                p.error = Some(e);
+            }
+            return;
+        }
+        self.picker = None;
+    }
+
+    /// Validate tvars and hot-swap the transform. Sources, entities and the
+    /// tail are kept; the derived panel belonged to the old transform.
+    fn switch_transform(
+        &mut self,
+        thread: Option<&ThreadResolved>,
+        values: &[String],
+    ) -> Result<(), String> {
+        let Some(h) = &self.switch else {
+            return Err("no live sources".to_string());
+        };
+
+        let stages = match thread {
+            None => Vec::new(),
+            Some(th) => {
+                let tvars: BTreeMap<String, String> = th
+                    .args
+                    .iter()
+                    .zip(values)
+                    .map(|(a, v)| (a.name.clone(), v.clone()))
+                    .collect();
+                packs::validate_tvars(&th.args, &tvars)?;
+                let tvars = packs::awk_tvars(&th.args, tvars);
+                vec![Stage {
+                    name: format!("thread:{}", th.qualified_id()),
+                    script_path: th.script_path.clone(),
+                    tvars,
+                    output: th.output,
+                    tick: th.tick,
+                    sandbox: th.sandbox.clone(),
+                    sha256: th.sha256.clone(),
+                }]
+            }
+        };
+        let output = h.output.unwrap_or_else(|| pipeline::output_mode(&stages));
+
+        self.transforms = h.switch.swap(stages, output).map_err(|e| e.to_string())?;
+        self.derived.clear();
+        self.status = Some(format!("transform {}", h.switch.label()));
+        Ok(())
+    }
+
+    /// Remove the ack or silence covering the selected entity.
+    pub fn unack_selected(&mut self) {
+        let now = self.now();
+        let Some(st) = self.selected_entity() else {
+            return;
+        };
+        let Some(found) = self.acks.find(&st.scope, &st.id, now) else {
+            return;
+        };
+        let unack = Ack::new(AckOp::Unack, &found.scope, &found.id, "", None, now);
+
+        self.status = Some(format!("unack {}:{}", unack.scope, unack.id));
+        self.acks.publish(&unack);
+        self.acks.apply(unack);
+        self.save_acks();
+    }
+
+    fn save_acks(&mut self) {
+        self.acks.prune(self.now());
+        if let Err(e) = self.acks.save() {
+            self.status = Some(format!("ack save failed: {}", e));
+        }
     }
 
-    pub fn compute_liveness(&self, last_seen: OffsetDateTime, now: OffsetDateTime) -> Liveness {
-        self.compute_liveness_with_ttl(last_seen, now, self.stale_s, self.dead_s)
+    pub fn remove_entity(&mut self, key: &str) -> bool {
+        if self.selected.as_deref() == Some(key) {
+            // Keep the cursor on the table: step to a neighbour first.
+            self.select_next();
+            if self.selected.as_deref() == Some(key) {
+                self.select_prev();
+            }
+            if self.selected.as_deref() == Some(key) {
+                self.selected = None;
+            }
+        }
+        self.entities.remove(key).is_some()
+    }
+
+    pub fn delete_selected(&mut self) {
+        if let Some(key) = self.selected.clone() {
+            if self.remove_entity(&key) {
+                self.status = Some(format!("deleted {}", key));
+            }
+        }
+    }
+
+    /// Forget TTL for an entity: kv `ttl_forget_s`, then scope, then global.
+    pub fn forget_ttl(&self, st: &EntityState) -> Option<i64> {
+        parse_ttl_from_kv(&st.kv, "ttl_forget_s")
+            .or_else(|| self.forget_by_scope.get(&st.scope).copied())
+            .or(Some(self.forget_s))
+            .filter(|n| *n > 0)
+    }
+
+    /// Drop entities past their forget TTL. Returns how many were removed.
+    pub fn expire_entities(&mut self) -> usize {
+        let now = self.now();
+        let expired: Vec<String> = self
+            .entities
+            .iter()
+            .filter(|(_, st)| {
+                self.forget_ttl(st)
+                    .is_some_and(|ttl| (now - st.last_seen).whole_seconds() >= ttl)
+            })
+            .map(|(k, _)| k.clone())
+            .collect();
+
+        for k in &expired {
+            self.remove_entity(k);
+        }
+        self.entities_forgotten += expired.len() as u64;
+        expired.len()
     }
 
     pub fn compute_liveness_with_ttl(
@@ -140,33 +711,35 @@ impl App {
         self.compute_liveness_with_ttl(st.last_seen, now, stale_s, dead_s)
     }
 
-    pub fn counts_by_state(&self) -> (u64, u64, u64, u64, u64, u64) {
-        let now = OffsetDateTime::now_utc();
-        let mut ok = 0u64;
-        let mut warn = 0u64;
-        let mut fail = 0u64;
-        let mut stale = 0u64;
-        let mut dead = 0u64;
+    pub fn counts_by_state(&self) -> StateCounts {
+        let now = self.now();
+        let mut c = StateCounts {
+            total: self.entities.len() as u64,
+            ..StateCounts::default()
+        };
 
         for st in self.entities.values() {
+            if self.ack_for(st, now).is_some() {
+                c.acked += 1;
+                continue;
+            }
             match self.compute_entity_liveness(st, now) {
-                Liveness::Stale => stale += 1,
-                Liveness::Dead => dead += 1,
+                Liveness::Stale => c.stale += 1,
+                Liveness::Dead => c.dead += 1,
                 _ => match st.last_level {
-                    Level::Fail => fail += 1,
-                    Level::Warn => warn += 1,
-                    Level::Ok | Level::Info => ok += 1,
-                    Level::Unknown => ok += 1,
+                    l if l.is_healthy() => c.ok += 1,
+                    Level::Critical | Level::Fail => c.fail += 1,
+                    Level::Warn => c.warn += 1,
+                    _ => c.unknown += 1,
                 },
             }
         }
 
-        let total = self.entities.len() as u64;
-        (total, ok, warn, fail, stale, dead)
+        c
     }
 
     pub fn sorted_entities(&self) -> Vec<EntityState> {
-        let now = OffsetDateTime::now_utc();
+        let now = self.now();
         let mut v: Vec<EntityState> = self.entities.values().cloned().collect();
         v.sort_by(|a, b| {
             let la = self.compute_entity_liveness(a, now);
@@ -182,9 +755,15 @@ impl App {
                 }
             };
 
+            // Acked entities sink below everything unacked.
+            let acked_a = self.ack_for(a, now).is_some();
+            let acked_b = self.ack_for(b, now).is_some();
+
             let ra = rank_live(la);
             let rb = rank_live(lb);
-            ra.cmp(&rb)
+            acked_a
+                .cmp(&acked_b)
+                .then_with(|| ra.cmp(&rb))
                 .then_with(|| LevelRank::from(a.last_level).cmp(&LevelRank::from(b.last_level)))
                 .then_with(|| b.last_seen.cmp(&a.last_seen))
                 .then_with(|| a.id.cmp(&b.id))
@@ -193,6 +772,10 @@ impl App {
     }
 }
 
+pub fn entity_key(scope: &str, id: &str) -> String {
+    format!("{}:{}", scope, id)
+}
+
 fn parse_ttl_from_kv(kv: &BTreeMap<String, String>, key: &str) -> Option<i64> {
     let v = kv.get(key)?;
     let n = v.parse::<i64>().ok()?;
@@ -203,3 +786,208 @@ fn parse_ttl_from_kv(kv: &BTreeMap<String, String>, key: &str) -> Option<i64> {
     }
 }
 // :: ∎
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use time::{macros::datetime, Duration};
+
+    const T0: OffsetDateTime = datetime!(2026-02-16 12:00:00 UTC);
+
+    fn app_at(clock: &Arc<ManualClock>) -> App {
+        let mut app = App::new(10, 10, 10, 30);
+        app.clock = clock.clone();
+        app
+    }
+
+    fn frame(id: &str, level: Level, kv: &[(&str, &str)]) -> HawkFrame {
+        HawkFrame {
+            ts: Some(T0),
+            kind: "HEALTH".to_string(),
+            scope: "svc".to_string(),
+            id: id.to_string(),
+            level,
+            level_raw: None,
+            msg: String::new(),
+            kv: kv
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn active_is_half_the_stale_window() {
+        let app = App::new(10, 10, 10, 30);
+        let at = |s: i64| app.compute_liveness_with_ttl(T0, T0 + Duration::seconds(s), 10, 30);
+        assert_eq!(at(0), Liveness::Active);
+        assert_eq!(at(5), Liveness::Active);
+        assert_eq!(at(6), Liveness::Dream);
+        assert_eq!(at(9), Liveness::Dream);
+        assert_eq!(at(10), Liveness::Stale);
+        assert_eq!(at(29), Liveness::Stale);
+        assert_eq!(at(30), Liveness::Dead);
+    }
+
+    #[test]
+    fn active_window_is_at_least_one_second() {
+        let app = App::new(10, 10, 1, 30);
+        let at =
+            |ms: i64| app.compute_liveness_with_ttl(T0, T0 + Duration::milliseconds(ms), 1, 30);
+        // stale_s / 2 rounds to 0; a frame this second still reads active.
+        assert_eq!(at(900), Liveness::Active);
+        assert_eq!(at(1000), Liveness::Stale);
+    }
+
+    #[test]
+    fn kv_ttls_override_defaults() {
+        let clock = Arc::new(ManualClock::new(T0));
+        let mut app = app_at(&clock);
+        app.ingest(IngestMsg::Frame(frame(
+            "slow",
+            Level::Ok,
+            &[("ttl_stale_s", "60"), ("ttl_dead_s", "120")],
+        )));
+        app.ingest(IngestMsg::Frame(frame("plain", Level::Ok, &[])));
+        // Zero and garbage fall back to the defaults.
+        app.ingest(IngestMsg::Frame(frame(
+            "bogus",
+            Level::Ok,
+            &[("ttl_stale_s", "0"), ("ttl_dead_s", "soon")],
+        )));
+
+        let live = |app: &App, id: &str| {
+            let st = &app.entities[&entity_key("svc", id)];
+            app.compute_entity_liveness(st, app.now())
+        };
+
+        clock.advance(Duration::seconds(20));
+        assert_eq!(live(&app, "slow"), Liveness::Active);
+        assert_eq!(live(&app, "plain"), Liveness::Stale);
+        assert_eq!(live(&app, "bogus"), Liveness::Stale);
+
+        clock.advance(Duration::seconds(20));
+        assert_eq!(live(&app, "slow"), Liveness::Dream);
+        assert_eq!(live(&app, "plain"), Liveness::Dead);
+
+        clock.advance(Duration::seconds(80));
+        assert_eq!(live(&app, "slow"), Liveness::Dead);
+    }
+
+    #[test]
+    fn counts_and_sort_follow_the_clock() {
+        let clock = Arc::new(ManualClock::new(T0));
+        let mut app = app_at(&clock);
+        app.ingest(IngestMsg::Frame(frame("a", Level::Fail, &[])));
+        app.ingest(IngestMsg::Frame(frame(
+            "b",
+            Level::Ok,
+            &[("ttl_stale_s", "5"), ("ttl_dead_s", "15")],
+        )));
+
+        let c = app.counts_by_state();
+        assert_eq!((c.fail, c.ok, c.stale, c.dead), (1, 1, 0, 0));
+        assert_eq!(app.sorted_entities()[0].id, "a");
+
+        clock.advance(Duration::seconds(16));
+        let c = app.counts_by_state();
+        assert_eq!((c.fail, c.stale, c.dead), (0, 1, 1));
+        // Dead outranks stale even though "a" is fail.
+        assert_eq!(app.sorted_entities()[0].id, "b");
+    }
+
+    #[test]
+    fn forget_uses_the_clock() {
+        let clock = Arc::new(ManualClock::new(T0));
+        let mut app = app_at(&clock);
+        app.forget_s = 60;
+        app.ingest(IngestMsg::Frame(frame("a", Level::Ok, &[])));
+
+        clock.advance(Duration::seconds(59));
+        assert_eq!(app.expire_entities(), 0);
+        clock.advance(Duration::seconds(1));
+        assert_eq!(app.expire_entities(), 1);
+        assert!(app.entities.is_empty());
+    }
+
+    #[test]
+    fn critical_sorts_first_and_unknown_is_not_ok() {
+        let clock = Arc::new(ManualClock::new(T0));
+        let mut app = app_at(&clock);
+        for (id, level) in [
+            ("f", Level::Fail),
+            ("c", Level::Critical),
+            ("u", Level::Unknown),
+            ("n", Level::Notice),
+        ] {
+            app.ingest(IngestMsg::Frame(frame(id, level, &[])));
+        }
+
+        let ids: Vec<String> = app.sorted_entities().into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, ["c", "f", "u", "n"]);
+        let c = app.counts_by_state();
+        assert_eq!((c.fail, c.unknown, c.ok), (2, 1, 1));
+    }
+
+    #[test]
+    fn picker_validates_args_and_keeps_entities() {
+        use crate::packs::{ArgSpec, ArgType};
+        use std::collections::HashMap;
+
+        let clock = Arc::new(ManualClock::new(T0));
+        let mut app = app_at(&clock);
+        app.ingest(IngestMsg::Frame(frame("a", Level::Ok, &[])));
+
+        let thread = ThreadResolved {
+            pack_id: "hawk.core".to_string(),
+            thread_id: "scope_counts".to_string(),
+            title: "Scope counts".to_string(),
+            kind: "rollup".to_string(),
+            description: String::new(),
+            script_path: "scope_counts.awk".into(),
+            args: vec![ArgSpec {
+                name: "window_s".to_string(),
+                ty: ArgType::Int,
+                default: "30".to_string(),
+                ..ArgSpec::default()
+            }],
+            output: packs::ThreadOutput::Panel,
+            tick: None,
+            sandbox: Default::default(),
+            sha256: None,
+        };
+        let (_tx_source, rx_source) = std::sync::mpsc::channel();
+        let (tx_ui, _rx_ui) = std::sync::mpsc::channel();
+        app.switch = Some(SwitchHandle {
+            switch: TransformSwitch::start(rx_source, tx_ui, Default::default()),
+            packs: PackIndex {
+                packs: Vec::new(),
+                threads: HashMap::from([(thread.qualified_id(), thread)]),
+                warnings: Vec::new(),
+            },
+            output: None,
+        });
+
+        app.open_picker();
+        app.picker_move(1);
+        app.picker_submit();
+        let form = app.picker.as_ref().and_then(|p| p.form.as_ref()).unwrap();
+        assert_eq!(form.values, ["30"]);
+
+        app.picker_input(Some('x'));
+        app.picker_submit();
+        let p = app.picker.as_ref().unwrap();
+        assert!(p.error.as_deref().unwrap().contains("expects int"));
+        assert!(p.form.is_some());
+        assert!(app.transforms.is_empty());
+
+        app.picker_back();
+        app.picker_move(-1);
+        app.picker_submit();
+        assert!(app.picker.is_none());
+        assert_eq!(app.status.as_deref(), Some("transform none"));
+        assert_eq!(app.entities.len(), 1);
+    }
+}
+// :: ∎
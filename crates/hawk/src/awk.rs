@@ -1,121 +1,1021 @@
 // ▛▞// hawk awk runner :: hawk.awk
 // @ctx ⫸ [awk.stream.bridge]
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::io::{BufRead, Write};
-use std::path::PathBuf;
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
-use std::sync::mpsc::{Receiver, Sender};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
-use hawk_core::HawkFrame;
+use clap::ValueEnum;
+use hawk_core::{HawkFrame, Level, TICK_KIND};
 use time::OffsetDateTime;
 
+use crate::sandbox::{Jail, SandboxMode, SandboxPolicy};
+use crate::signing;
+
+pub mod interp;
+pub mod lang;
+
+/// Stack of the threads that parse and run scripts. Both recurse on the
+/// script's nesting, and their depth limits are sized to this.
+pub const STACK_SIZE: usize = 64 << 20;
+
+/// Run `f` on a thread with `STACK_SIZE` of stack; the caller's may be far
+/// smaller.
+pub fn with_stack<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    std::thread::scope(|s| {
+        std::thread::Builder::new()
+            .name("awk".to_string())
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(s, f)
+            .expect("spawn awk thread")
+            .join()
+            .unwrap_or_else(|p| std::panic::resume_unwind(p))
+    })
+}
+
+// :: ∎
+
+// ▛▞// runner stats :: hawk.awk.stats
+// @ctx ⫸ [state.restarts.stderr]
+/// Stderr lines kept per transform for the TUI.
+pub const STDERR_KEEP: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunnerState {
+    Running,
+    /// Child exited; waiting out the backoff before the next spawn.
+    Restarting,
+    /// Gave up on the script; raw frames go straight through.
+    Passthrough,
+    /// Input ended and the child drained.
+    Stopped,
+}
+
+impl RunnerState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RunnerState::Running => "running",
+            RunnerState::Restarting => "restarting",
+            RunnerState::Passthrough => "passthrough",
+            RunnerState::Stopped => "stopped",
+        }
+    }
+}
+
+/// Shared between the supervisor thread and the UI.
+#[derive(Debug)]
+pub struct RunnerStats {
+    /// Transform label, e.g. `thread:scope_counts`.
+    pub name: String,
+    state: Mutex<RunnerState>,
+    pub restarts: AtomicU64,
+    pub crashes: AtomicU64,
+    /// Frames handed to the script (or passed through).
+    pub frames_in: AtomicU64,
+    /// Valid frames the script emitted.
+    pub frames_out: AtomicU64,
+    /// Output lines that did not parse as HawkFrame TSV.
+    pub invalid: AtomicU64,
+    stderr: Mutex<VecDeque<String>>,
+}
+
+impl RunnerStats {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            state: Mutex::new(RunnerState::Running),
+            restarts: AtomicU64::new(0),
+            crashes: AtomicU64::new(0),
+            frames_in: AtomicU64::new(0),
+            frames_out: AtomicU64::new(0),
+            invalid: AtomicU64::new(0),
+            stderr: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn state(&self) -> RunnerState {
+        self.state
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or(RunnerState::Stopped)
+    }
+
+    pub fn set_state(&self, state: RunnerState) {
+        if let Ok(mut s) = self.state.lock() {
+            *s = state;
+        }
+    }
+
+    pub fn push_stderr(&self, line: String) {
+        if let Ok(mut tail) = self.stderr.lock() {
+            tail.push_back(line);
+            while tail.len() > STDERR_KEEP {
+                tail.pop_front();
+            }
+        }
+    }
+
+    /// Tag a bridge error with the stage it came from.
+    fn report(&self, err_tx: &Sender<String>, msg: String) {
+        let _ = err_tx.send(format!("{}: {}", self.name, msg));
+    }
+
+    /// Oldest first.
+    pub fn stderr_tail(&self) -> Vec<String> {
+        self.stderr
+            .lock()
+            .map(|t| t.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+// :: ∎
+
+// ▛▞// supervisor options :: hawk.awk.supervise
+// ⫸ [backend.backoff.passthrough]
+/// What runs a transform script.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum AwkBackend {
+    /// In-process interpreter for the awk subset in `awk::lang`. Scripts
+    /// cannot run commands, open files or see the environment.
+    #[default]
+    Embedded,
+    /// The system `awk` binary, with full awk and no isolation.
+    External,
+}
+
+#[derive(Debug, Clone)]
+pub struct SuperviseOptions {
+    pub backend: AwkBackend,
+    /// Feed the script a TICK frame this often while input is open.
+    pub tick: Option<Duration>,
+    /// Declared sha256 of the script, checked against the bytes that run.
+    pub sha256: Option<String>,
+    /// Jail for external children. The embedded backend is confined by
+    /// what its parser accepts and takes only the limits (`embedded_limits`).
+    pub sandbox: SandboxPolicy,
+    /// First restart delay; doubles per consecutive crash.
+    pub backoff_min: Duration,
+    pub backoff_max: Duration,
+    /// A child that lived this long resets the crash streak.
+    pub stable_after: Duration,
+    /// Consecutive crashes before raw frames bypass the script.
+    /// None keeps restarting.
+    pub passthrough_after: Option<u32>,
+}
+
+impl Default for SuperviseOptions {
+    fn default() -> Self {
+        Self {
+            backend: AwkBackend::Embedded,
+            tick: None,
+            sha256: None,
+            sandbox: SandboxPolicy::default(),
+            backoff_min: Duration::from_millis(250),
+            backoff_max: Duration::from_secs(30),
+            stable_after: Duration::from_secs(30),
+            passthrough_after: None,
+        }
+    }
+}
+
+impl SuperviseOptions {
+    fn backoff(&self, streak: u32) -> Duration {
+        let factor = 1u32 << streak.saturating_sub(1).min(16);
+        self.backoff_min
+            .saturating_mul(factor)
+            .min(self.backoff_max)
+    }
+}
 // :: ∎
 
 // ▛▞// AwkRunner :: hawk.awk.runner
 // @ctx ⫸ [awk.process.spawn]
 pub struct AwkRunner {
+    tvars: BTreeMap<String, String>,
+    opts: SuperviseOptions,
+    stats: Arc<RunnerStats>,
+    engine: Engine,
+}
+
+enum Engine {
+    Embedded(Arc<lang::Program>),
+    /// The first child is spawned up front; restarts spawn fresh ones,
+    /// all from the same copy of the script.
+    External {
+        next: Option<AwkChild>,
+        jail: Option<Arc<Jail>>,
+        script: ScriptCopy,
+    },
+}
+
+/// The script as read (and checked) at startup, in a private dir. External
+/// children run this copy, so editing the pack file later cannot slip
+/// unverified code into a restart.
+struct ScriptCopy {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl ScriptCopy {
+    fn new(raw: &[u8]) -> std::io::Result<Self> {
+        static SEQ: AtomicU64 = AtomicU64::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "hawk-script-{}-{}",
+            std::process::id(),
+            SEQ.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut builder = std::fs::DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder.create(&dir)?;
+        let copy = Self {
+            path: dir.join("script.awk"),
+            dir,
+        };
+        std::fs::write(&copy.path, raw)?;
+        Ok(copy)
+    }
+}
+
+impl Drop for ScriptCopy {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+struct AwkChild {
     child: Child,
     stdin: ChildStdin,
     stdout: ChildStdout,
+    stderr: ChildStderr,
+    started: Instant,
+}
+
+/// How one child's run ended.
+enum ChildExit {
+    InputClosed,
+    Crashed { reason: String, lived: Duration },
 }
 
 impl AwkRunner {
-    pub fn spawn(script_path: PathBuf, tvars: &BTreeMap<String, String>) -> anyhow::Result<Self> {
+    /// Compiles the script (embedded) or spawns the first child (external)
+    /// right away so a bad script or missing awk fails startup.
+    pub fn spawn(
+        script_path: PathBuf,
+        tvars: &BTreeMap<String, String>,
+        name: &str,
+        opts: SuperviseOptions,
+    ) -> anyhow::Result<Self> {
         if !script_path.exists() {
             anyhow::bail!("awk script not found: {:?}", script_path);
         }
+        let raw = read_script(&script_path, opts.sha256.as_deref())?;
 
-        let mut cmd = Command::new("awk");
-
-        // Pass transform variables as -v key=value args.
-        for (k, v) in tvars {
-            cmd.arg("-v").arg(format!("{}={}", k, v));
-        }
-
-        cmd.arg("-f").arg(script_path);
-        cmd.stdin(Stdio::piped());
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::inherit());
-
-        let mut child = cmd.spawn()?;
-        let stdin = child
-            .stdin
-            .take()
-            .ok_or_else(|| anyhow::anyhow!("awk stdin missing"))?;
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| anyhow::anyhow!("awk stdout missing"))?;
-
+        let stats = Arc::new(RunnerStats::new(name));
+        let engine = match opts.backend {
+            AwkBackend::Embedded => Engine::Embedded(Arc::new(compile_source(&script_path, &raw)?)),
+            AwkBackend::External => {
+                let script = ScriptCopy::new(&raw)
+                    .map_err(|e| anyhow::anyhow!("copy {:?}: {}", script_path, e))?;
+                let jail = Jail::prepare(&opts.sandbox, &script.path)?;
+                if let Some(note) = jail.as_ref().and_then(|j| j.note()) {
+                    stats.push_stderr(note.to_string());
+                }
+                let first = spawn_child(&script.path, tvars, jail.as_ref())?;
+                Engine::External {
+                    next: Some(first),
+                    jail,
+                    script,
+                }
+            }
+        };
         Ok(Self {
-            child,
-            stdin,
-            stdout,
+            tvars: tvars.clone(),
+            opts,
+            stats,
+            engine,
         })
     }
 
+    pub fn stats(&self) -> Arc<RunnerStats> {
+        Arc::clone(&self.stats)
+    }
+
+    /// Feed `in_rx` through awk until it disconnects, restarting the child
+    /// when it dies.
     pub fn start_bridge(
         self,
         in_rx: Receiver<HawkFrame>,
         out_tx: Sender<HawkFrame>,
         err_tx: Sender<String>,
     ) {
-        let mut stdin = self.stdin;
-        let stdout = self.stdout;
-        let mut child = self.child;
-
-        let err_tx_writer = err_tx.clone();
-        std::thread::spawn(move || {
-            for frame in in_rx {
-                let now = OffsetDateTime::now_utc();
-                let line = frame.to_tsv_line(now);
-                if stdin.write_all(line.as_bytes()).is_err() {
-                    let _ = err_tx_writer.send("awk stdin write failed".to_string());
-                    break;
-                }
-                if stdin.write_all(b"\n").is_err() {
-                    let _ = err_tx_writer.send("awk stdin newline failed".to_string());
-                    break;
+        // The interpreter recurses on the script's nesting; its depth limit
+        // is sized to this stack.
+        std::thread::Builder::new()
+            .name(format!("awk-{}", self.stats.name))
+            .stack_size(STACK_SIZE)
+            .spawn(move || self.supervise(in_rx, out_tx, err_tx))
+            .expect("spawn awk supervisor");
+    }
+
+    fn supervise(
+        self,
+        in_rx: Receiver<HawkFrame>,
+        out_tx: Sender<HawkFrame>,
+        err_tx: Sender<String>,
+    ) {
+        let AwkRunner {
+            tvars,
+            opts,
+            stats,
+            mut engine,
+        } = self;
+
+        let mut streak = 0u32;
+        // A frame the last child died before taking; the next one gets it.
+        let mut pending: Option<HawkFrame> = None;
+        loop {
+            let exit = match &mut engine {
+                Engine::Embedded(prog) => {
+                    stats.set_state(RunnerState::Running);
+                    run_embedded(
+                        Arc::clone(prog),
+                        &tvars,
+                        embedded_limits(&opts.sandbox),
+                        opts.tick,
+                        &in_rx,
+                        &out_tx,
+                        &err_tx,
+                        &stats,
+                    )
                 }
-                if stdin.flush().is_err() {
-                    let _ = err_tx_writer.send("awk stdin flush failed".to_string());
-                    break;
+                Engine::External { next, jail, script } => {
+                    let spawned = match next.take() {
+                        Some(child) => Ok(child),
+                        None => spawn_child(&script.path, &tvars, jail.as_ref()),
+                    };
+                    match spawned {
+                        Ok(child) => {
+                            stats.set_state(RunnerState::Running);
+                            run_child(
+                                child,
+                                opts.tick,
+                                &mut pending,
+                                &in_rx,
+                                &out_tx,
+                                &err_tx,
+                                &stats,
+                            )
+                        }
+                        Err(e) => ChildExit::Crashed {
+                            reason: format!("spawn failed: {}", e),
+                            lived: Duration::ZERO,
+                        },
+                    }
                 }
+            };
+
+            let ChildExit::Crashed { reason, lived } = exit else {
+                stats.set_state(RunnerState::Stopped);
+                return;
+            };
+
+            stats.crashes.fetch_add(1, Ordering::Relaxed);
+            if lived >= opts.stable_after {
+                streak = 0;
             }
-        });
-
-        let err_tx_reader = err_tx.clone();
-        std::thread::spawn(move || {
-            let reader = std::io::BufReader::new(stdout);
-            for line in reader.lines() {
-                match line {
-                    Ok(line) => match HawkFrame::parse_tsv_line(&line) {
-                        Ok(Some(frame)) => {
-                            let _ = out_tx.send(frame);
-                        }
-                        Ok(None) => {}
-                        Err(_) => {
-                            let _ = err_tx_reader.send("awk emitted invalid TSV line".to_string());
-                        }
-                    },
-                    Err(_) => {
-                        let _ = err_tx_reader.send("awk stdout read failed".to_string());
-                        break;
+            streak += 1;
+
+            if opts.passthrough_after.is_some_and(|n| streak >= n) {
+                stats.report(
+                    &err_tx,
+                    format!("{}; passthrough after {} crashes in a row", reason, streak),
+                );
+                stats.set_state(RunnerState::Passthrough);
+                for frame in pending.take().into_iter().chain(in_rx) {
+                    stats.frames_in.fetch_add(1, Ordering::Relaxed);
+                    if out_tx.send(frame).is_ok() {
+                        stats.frames_out.fetch_add(1, Ordering::Relaxed);
                     }
                 }
+                return;
+            }
+
+            let delay = opts.backoff(streak);
+            stats.report(
+                &err_tx,
+                format!("{}; restarting in {}ms", reason, delay.as_millis()),
+            );
+            stats.set_state(RunnerState::Restarting);
+            std::thread::sleep(delay);
+            stats.restarts.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+fn tick_line() -> String {
+    let now = OffsetDateTime::now_utc();
+    let frame = HawkFrame {
+        ts: Some(now),
+        kind: TICK_KIND.to_string(),
+        scope: "hawk".to_string(),
+        id: "tick".to_string(),
+        level: Level::Info,
+        level_raw: None,
+        msg: "tick".to_string(),
+        kv: BTreeMap::from([("epoch".to_string(), now.unix_timestamp().to_string())]),
+    };
+    frame.to_tsv_line(now)
+}
+
+struct Ticker {
+    every: Duration,
+    next: Instant,
+}
+
+impl Ticker {
+    fn new(every: Duration) -> Self {
+        Self {
+            every,
+            next: Instant::now() + every,
+        }
+    }
+
+    fn left(&self) -> Duration {
+        self.next.saturating_duration_since(Instant::now())
+    }
+
+    fn fire(&mut self) -> bool {
+        let now = Instant::now();
+        if now < self.next {
+            return false;
+        }
+        self.next = now + self.every;
+        true
+    }
+}
+
+enum Input {
+    Frame(HawkFrame),
+    Tick,
+    /// `poll` elapsed with nothing to feed.
+    Idle,
+    Closed,
+}
+
+/// A due tick goes first so a busy stream still gets its clock.
+fn next_input(
+    in_rx: &Receiver<HawkFrame>,
+    ticker: &mut Option<Ticker>,
+    poll: Option<Duration>,
+) -> Input {
+    if ticker.as_mut().is_some_and(Ticker::fire) {
+        return Input::Tick;
+    }
+    let wait = match (ticker.as_ref().map(Ticker::left), poll) {
+        (Some(t), Some(p)) => Some(t.min(p)),
+        (t, p) => t.or(p),
+    };
+    let got = match wait {
+        Some(w) => in_rx.recv_timeout(w),
+        None => in_rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+    };
+    match got {
+        Ok(frame) => Input::Frame(frame),
+        Err(RecvTimeoutError::Timeout) if ticker.as_mut().is_some_and(Ticker::fire) => Input::Tick,
+        Err(RecvTimeoutError::Timeout) => Input::Idle,
+        Err(RecvTimeoutError::Disconnected) => Input::Closed,
+    }
+}
+
+/// Parse a script for the embedded backend.
+pub fn compile_script(script_path: &Path) -> anyhow::Result<lang::Program> {
+    compile_source(script_path, &read_script(script_path, None)?)
+}
+
+fn compile_source(script_path: &Path, raw: &[u8]) -> anyhow::Result<lang::Program> {
+    let src = std::str::from_utf8(raw)
+        .map_err(|e| anyhow::anyhow!("read {:?}: {}", script_path, e))?;
+    lang::compile(src).map_err(|e| anyhow::anyhow!("{}: {}", script_path.display(), e))
+}
+
+/// Read the script once. A declared digest is checked against these bytes,
+/// since they, not a later read of the file, are what runs.
+fn read_script(script_path: &Path, sha256: Option<&str>) -> anyhow::Result<Vec<u8>> {
+    let raw = std::fs::read(script_path)
+        .map_err(|e| anyhow::anyhow!("read {:?}: {}", script_path, e))?;
+    if let Some(want) = sha256 {
+        let got = signing::sha256_hex(&raw);
+        if !got.eq_ignore_ascii_case(want.trim()) {
+            anyhow::bail!(
+                "{}: sha256 mismatch: manifest {}, file {}",
+                script_path.display(),
+                want,
+                got
+            );
+        }
+    }
+    Ok(raw)
+}
+
+/// A jailing policy's cpu_s and mem_mb bound the interpreter the way their
+/// rlimits bound an external child; a policy that is off sets neither.
+pub fn embedded_limits(policy: &SandboxPolicy) -> interp::Limits {
+    let mut limits = interp::Limits::default();
+    if policy.mode != SandboxMode::Off {
+        limits.cpu = (policy.cpu_s > 0).then(|| Duration::from_secs(policy.cpu_s));
+        limits.mem = (policy.mem_mb > 0).then_some((policy.mem_mb as usize) << 20);
+    }
+    limits
+}
+
+/// One interpreter per run; its END block runs when input closes. A runtime
+/// error or an `exit` before then counts as a crash, like a dying child.
+#[allow(clippy::too_many_arguments)]
+fn run_embedded(
+    prog: Arc<lang::Program>,
+    tvars: &BTreeMap<String, String>,
+    limits: interp::Limits,
+    tick: Option<Duration>,
+    in_rx: &Receiver<HawkFrame>,
+    out_tx: &Sender<HawkFrame>,
+    err_tx: &Sender<String>,
+    stats: &Arc<RunnerStats>,
+) -> ChildExit {
+    let started = Instant::now();
+    let crashed = |reason: String| ChildExit::Crashed {
+        reason,
+        lived: started.elapsed(),
+    };
+
+    let mut it = interp::Interp::new(prog, tvars).with_limits(limits);
+    let mut status = it.begin();
+    drain_interp(&mut it, out_tx, err_tx, stats);
+
+    let mut input_closed = true;
+    if matches!(status, Ok(interp::Status::Running)) && it.reads_input() {
+        let mut ticker = tick.map(Ticker::new);
+        loop {
+            let line = match next_input(in_rx, &mut ticker, None) {
+                Input::Frame(frame) => {
+                    stats.frames_in.fetch_add(1, Ordering::Relaxed);
+                    frame.to_tsv_line(OffsetDateTime::now_utc())
+                }
+                Input::Tick => tick_line(),
+                Input::Idle => continue,
+                Input::Closed => break,
+            };
+            status = it.record(&line);
+            drain_interp(&mut it, out_tx, err_tx, stats);
+            if !matches!(status, Ok(interp::Status::Running)) {
+                input_closed = false;
+                break;
             }
-        });
+        }
+    } else {
+        input_closed = false;
+    }
+    if let Err(e) = status {
+        return crashed(format!("runtime error: {}", e));
+    }
+
+    let end = it.end();
+    for buf in [&mut it.out, &mut it.err] {
+        if !buf.is_empty() && !buf.ends_with('\n') {
+            buf.push('\n');
+        }
+    }
+    drain_interp(&mut it, out_tx, err_tx, stats);
+    let code = match end {
+        Ok(code) => code,
+        Err(e) if input_closed => {
+            stats.report(err_tx, format!("runtime error: {}", e));
+            return ChildExit::InputClosed;
+        }
+        Err(e) => return crashed(format!("runtime error: {}", e)),
+    };
+
+    if input_closed {
+        if code != 0 {
+            stats.report(err_tx, format!("exited with status {}", code));
+        }
+        return ChildExit::InputClosed;
+    }
+    crashed(format!("exited with status {}", code))
+}
+
+/// Hand complete output lines on; a partial line waits for its newline.
+fn drain_interp(
+    it: &mut interp::Interp,
+    out_tx: &Sender<HawkFrame>,
+    err_tx: &Sender<String>,
+    stats: &RunnerStats,
+) {
+    for line in take_lines(&mut it.out) {
+        emit_line(&line, out_tx, err_tx, stats);
+    }
+    for line in take_lines(&mut it.err) {
+        if !line.is_empty() {
+            stats.push_stderr(line);
+        }
+    }
+}
+
+fn take_lines(buf: &mut String) -> Vec<String> {
+    let Some(end) = buf.rfind('\n') else {
+        return Vec::new();
+    };
+    let rest = buf.split_off(end + 1);
+    let done = std::mem::replace(buf, rest);
+    done.lines().map(str::to_string).collect()
+}
+
+/// mawk block-buffers a piped stdin and stdout, so frames would only reach
+/// the script (and come back) at EOF. `-W interactive` makes it stream.
+fn stream_args() -> &'static [&'static str] {
+    static IS_MAWK: OnceLock<bool> = OnceLock::new();
+    let mawk = *IS_MAWK.get_or_init(|| {
+        Command::new("awk")
+            .args(["-W", "version"])
+            .stdin(Stdio::null())
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).contains("mawk"))
+            .unwrap_or(false)
+    });
+    if mawk {
+        &["-W", "interactive"]
+    } else {
+        &[]
+    }
+}
+
+fn spawn_child(
+    script_path: &Path,
+    tvars: &BTreeMap<String, String>,
+    jail: Option<&Arc<Jail>>,
+) -> anyhow::Result<AwkChild> {
+    let mut cmd = match jail {
+        Some(j) => j.command(),
+        None => Command::new("awk"),
+    };
+    cmd.args(stream_args());
+
+    // Pass transform variables as -v key=value args.
+    for (k, v) in tvars {
+        cmd.arg("-v").arg(format!("{}={}", k, v));
+    }
+
+    cmd.arg("-f")
+        .arg(jail.map_or(script_path, |j| j.script_arg()));
+    cmd.stdin(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+
+    let mut child = cmd.spawn()?;
+    let stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("awk stdin missing"))?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("awk stdout missing"))?;
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or_else(|| anyhow::anyhow!("awk stderr missing"))?;
+
+    Ok(AwkChild {
+        child,
+        stdin,
+        stdout,
+        stderr,
+        started: Instant::now(),
+    })
+}
+
+/// Pump frames into one child until input ends or the child goes away.
+/// `pending` is sent first; a frame the child could not take is left there.
+#[allow(clippy::too_many_arguments)]
+fn run_child(
+    c: AwkChild,
+    tick: Option<Duration>,
+    pending: &mut Option<HawkFrame>,
+    in_rx: &Receiver<HawkFrame>,
+    out_tx: &Sender<HawkFrame>,
+    err_tx: &Sender<String>,
+    stats: &Arc<RunnerStats>,
+) -> ChildExit {
+    let AwkChild {
+        mut child,
+        mut stdin,
+        stdout,
+        stderr,
+        started,
+    } = c;
+
+    let reader = spawn_stdout_reader(stdout, out_tx.clone(), err_tx.clone(), Arc::clone(stats));
+    let stderr_stats = Arc::clone(stats);
+    let stderr_reader = std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stderr)
+            .lines()
+            .map_while(Result::ok)
+        {
+            stderr_stats.push_stderr(line);
+        }
+    });
 
-        std::thread::spawn(move || match child.wait() {
-            Ok(status) => {
-                if !status.success() {
-                    let _ = err_tx.send(format!("awk exited with status {}", status));
+    let mut ticker = tick.map(Ticker::new);
+    let input_closed = loop {
+        let frame = match pending.take() {
+            Some(frame) => Some(frame),
+            None => match next_input(in_rx, &mut ticker, Some(Duration::from_millis(100))) {
+                Input::Frame(frame) => Some(frame),
+                Input::Tick => None,
+                Input::Idle => {
+                    if !matches!(child.try_wait(), Ok(None)) {
+                        break false;
+                    }
+                    continue;
                 }
+                Input::Closed => break true,
+            },
+        };
+        let line = match &frame {
+            Some(f) => f.to_tsv_line(OffsetDateTime::now_utc()),
+            None => tick_line(),
+        };
+        if stdin
+            .write_all(line.as_bytes())
+            .and_then(|_| stdin.write_all(b"\n"))
+            .and_then(|_| stdin.flush())
+            .is_err()
+        {
+            // The child is gone; keep the frame for its replacement.
+            *pending = frame;
+            break false;
+        }
+        if frame.is_some() {
+            stats.frames_in.fetch_add(1, Ordering::Relaxed);
+        }
+    };
+
+    // EOF lets a healthy script flush its END block.
+    drop(stdin);
+    let status = child.wait();
+    let _ = reader.join();
+    let _ = stderr_reader.join();
+
+    if input_closed {
+        if let Ok(s) = &status {
+            if !s.success() {
+                stats.report(err_tx, format!("exited with status {}", s));
             }
-            Err(e) => {
-                let _ = err_tx.send(format!("awk wait failed: {}", e));
+        }
+        return ChildExit::InputClosed;
+    }
+
+    let reason = match status {
+        Ok(s) => format!("exited with status {}", s),
+        Err(e) => format!("wait failed: {}", e),
+    };
+    ChildExit::Crashed {
+        reason,
+        lived: started.elapsed(),
+    }
+}
+
+fn spawn_stdout_reader(
+    stdout: ChildStdout,
+    out_tx: Sender<HawkFrame>,
+    err_tx: Sender<String>,
+    stats: Arc<RunnerStats>,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let reader = std::io::BufReader::new(stdout);
+        for line in reader.lines() {
+            match line {
+                Ok(line) => emit_line(&line, &out_tx, &err_tx, &stats),
+                Err(_) => {
+                    stats.report(&err_tx, "stdout read failed".to_string());
+                    break;
+                }
             }
-        });
+        }
+    })
+}
+
+fn emit_line(line: &str, out_tx: &Sender<HawkFrame>, err_tx: &Sender<String>, stats: &RunnerStats) {
+    match HawkFrame::parse_tsv_line(line) {
+        // Ticks are the bridge's own; a script echoing them back is not output.
+        Ok(Some(frame)) if frame.kind == TICK_KIND => {}
+        Ok(Some(frame)) => {
+            stats.frames_out.fetch_add(1, Ordering::Relaxed);
+            let _ = out_tx.send(frame);
+        }
+        Ok(None) => {}
+        Err(_) => {
+            stats.invalid.fetch_add(1, Ordering::Relaxed);
+            stats.report(err_tx, "emitted invalid TSV line".to_string());
+        }
+    }
+}
+// :: ∎
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(id: &str) -> HawkFrame {
+        HawkFrame::parse_tsv_line(&format!(
+            "2026-02-16T00:00:00Z\tHEALTH\tsvc\t{}\tok\tup\t",
+            id
+        ))
+        .unwrap()
+        .unwrap()
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let o = SuperviseOptions {
+            backoff_min: Duration::from_millis(100),
+            backoff_max: Duration::from_millis(500),
+            ..SuperviseOptions::default()
+        };
+        let ms: Vec<u128> = (1..=5).map(|n| o.backoff(n).as_millis()).collect();
+        assert_eq!(ms, [100, 200, 400, 500, 500]);
+    }
+
+    #[test]
+    fn crashing_script_restarts_then_passes_through() {
+        // Dies on the "boom" frame, echoes everything else.
+        let path = std::env::temp_dir().join(format!("hawk-awk-{}.awk", std::process::id()));
+        std::fs::write(
+            &path,
+            "$4 == \"boom\" { print \"bad frame\" > \"/dev/stderr\"; exit 3 } { print }\n",
+        )
+        .unwrap();
+        for backend in [AwkBackend::Embedded, AwkBackend::External] {
+            let opts = SuperviseOptions {
+                backend,
+                backoff_min: Duration::from_millis(1),
+                passthrough_after: Some(2),
+                ..SuperviseOptions::default()
+            };
+            let runner = AwkRunner::spawn(path.clone(), &BTreeMap::new(), "file:test", opts).unwrap();
+            let stats = runner.stats();
+
+            let (in_tx, in_rx) = std::sync::mpsc::channel();
+            let (out_tx, out_rx) = std::sync::mpsc::channel();
+            let (err_tx, err_rx) = std::sync::mpsc::channel();
+            runner.start_bridge(in_rx, out_tx, err_tx);
+
+            let recv = || out_rx.recv_timeout(Duration::from_secs(5)).unwrap().id;
+            in_tx.send(frame("a")).unwrap();
+            assert_eq!(recv(), "a");
+            in_tx.send(frame("boom")).unwrap();
+            assert!(err_rx
+                .recv_timeout(Duration::from_secs(5))
+                .unwrap()
+                .contains("restarting"));
+            in_tx.send(frame("b")).unwrap();
+            assert_eq!(recv(), "b");
+            in_tx.send(frame("boom")).unwrap();
+            assert!(err_rx
+                .recv_timeout(Duration::from_secs(5))
+                .unwrap()
+                .contains("passthrough"));
+            in_tx.send(frame("boom")).unwrap();
+            assert_eq!(recv(), "boom");
+
+            assert_eq!(stats.restarts.load(Ordering::Relaxed), 1);
+            assert_eq!(stats.crashes.load(Ordering::Relaxed), 2);
+            assert_eq!(stats.state(), RunnerState::Passthrough);
+            assert_eq!(stats.stderr_tail(), ["bad frame", "bad frame"]);
+        }
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn restarts_run_the_verified_bytes() {
+        let path = std::env::temp_dir().join(format!("hawk-pin-{}.awk", std::process::id()));
+        let script = "$4 == \"boom\" { exit 3 } { print }\n";
+        std::fs::write(&path, script).unwrap();
+        for backend in [AwkBackend::Embedded, AwkBackend::External] {
+            let opts = SuperviseOptions {
+                backend,
+                sha256: Some("00".repeat(32)),
+                ..SuperviseOptions::default()
+            };
+            let err = AwkRunner::spawn(path.clone(), &BTreeMap::new(), "file:test", opts)
+                .err()
+                .unwrap();
+            assert!(err.to_string().contains("sha256 mismatch"), "{}", err);
+
+            std::fs::write(&path, script).unwrap();
+            let opts = SuperviseOptions {
+                backend,
+                sha256: Some(signing::sha256_hex(script.as_bytes())),
+                backoff_min: Duration::from_millis(1),
+                ..SuperviseOptions::default()
+            };
+            let runner = AwkRunner::spawn(path.clone(), &BTreeMap::new(), "file:test", opts).unwrap();
+            let (in_tx, in_rx) = std::sync::mpsc::channel();
+            let (out_tx, out_rx) = std::sync::mpsc::channel();
+            let (err_tx, err_rx) = std::sync::mpsc::channel();
+            runner.start_bridge(in_rx, out_tx, err_tx);
+
+            // Swapped after the check; the restart must not pick it up.
+            std::fs::write(
+                &path,
+                "BEGIN { FS = OFS = \"\\t\" } { $4 = \"evil\"; print }\n",
+            )
+            .unwrap();
+            in_tx.send(frame("boom")).unwrap();
+            assert!(err_rx
+                .recv_timeout(Duration::from_secs(5))
+                .unwrap()
+                .contains("restarting"));
+            in_tx.send(frame("b")).unwrap();
+            assert_eq!(out_rx.recv_timeout(Duration::from_secs(5)).unwrap().id, "b");
+        }
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn runaway_embedded_script_is_restarted() {
+        // Spins on the "spin" frame until its cpu budget runs out.
+        let path = std::env::temp_dir().join(format!("hawk-spin-{}.awk", std::process::id()));
+        std::fs::write(&path, "$4 == \"spin\" { while (1) {} } { print }\n").unwrap();
+        let opts = SuperviseOptions {
+            sandbox: SandboxPolicy {
+                mode: SandboxMode::BestEffort,
+                cpu_s: 1,
+                mem_mb: 16,
+            },
+            backoff_min: Duration::from_millis(1),
+            ..SuperviseOptions::default()
+        };
+        let runner = AwkRunner::spawn(path.clone(), &BTreeMap::new(), "file:test", opts).unwrap();
+        let stats = runner.stats();
+
+        let (in_tx, in_rx) = std::sync::mpsc::channel();
+        let (out_tx, out_rx) = std::sync::mpsc::channel();
+        let (err_tx, err_rx) = std::sync::mpsc::channel();
+        runner.start_bridge(in_rx, out_tx, err_tx);
+
+        in_tx.send(frame("spin")).unwrap();
+        let err = err_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(err.contains("cpu budget") && err.contains("restarting"), "{}", err);
+        in_tx.send(frame("a")).unwrap();
+        assert_eq!(out_rx.recv_timeout(Duration::from_secs(5)).unwrap().id, "a");
+        assert_eq!(stats.crashes.load(Ordering::Relaxed), 1);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn ticks_reach_the_script_on_a_quiet_stream() {
+        // Echoes ticks back (dropped) and reports the frame count on each one.
+        let path = std::env::temp_dir().join(format!("hawk-tick-{}.awk", std::process::id()));
+        std::fs::write(
+            &path,
+            "BEGIN { FS = OFS = \"\\t\" }\n\
+             $2 == \"TICK\" { print; print $1, \"COUNT\", \"awk\", \"n=\" n, \"info\", \"count\", \"\"; next }\n\
+             { n++ }\n",
+        )
+        .unwrap();
+        for backend in [AwkBackend::Embedded, AwkBackend::External] {
+            let opts = SuperviseOptions {
+                backend,
+                tick: Some(Duration::from_millis(50)),
+                ..SuperviseOptions::default()
+            };
+            let runner = AwkRunner::spawn(path.clone(), &BTreeMap::new(), "file:test", opts).unwrap();
+            let stats = runner.stats();
+
+            let (in_tx, in_rx) = std::sync::mpsc::channel();
+            let (out_tx, out_rx) = std::sync::mpsc::channel();
+            let (err_tx, _err_rx) = std::sync::mpsc::channel();
+            runner.start_bridge(in_rx, out_tx, err_tx);
+
+            in_tx.send(frame("a")).unwrap();
+            in_tx.send(frame("b")).unwrap();
+            let out = loop {
+                let f = out_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+                assert_eq!(f.kind, "COUNT");
+                if f.id == "n=2" {
+                    break f;
+                }
+            };
+            assert!(out.ts.is_some());
+            assert_eq!(stats.frames_in.load(Ordering::Relaxed), 2);
+        }
+        let _ = std::fs::remove_file(path);
     }
 }
 // :: ∎
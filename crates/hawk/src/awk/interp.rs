@@ -0,0 +1,1748 @@
+// ▛▞// awk interpreter :: hawk.awk.interp
+// @ctx ⫸ [value.record.eval]
+//! Tree-walking evaluator for `lang::Program`. One `Interp` is one awk
+//! process: it keeps globals across records, and its stdout and stderr are
+//! buffers the runner drains after each record.
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+use super::STACK_SIZE;
+use super::lang::{
+    compile_regex, BinOp, Builtin, Expr, LValue, Output, Pattern, Program, Stmt, Var, G_CONVFMT,
+    G_ENVIRON, G_FILENAME, G_FNR, G_FS, G_NR, G_OFMT, G_OFS, G_ORS, G_RLENGTH, G_RS, G_RSTART,
+    G_SUBSEP,
+};
+
+// :: ∎
+
+// ▛▞// values :: hawk.awk.interp.value
+// ⫸ [strnum.compare.convert]
+#[derive(Debug, Clone)]
+pub enum Value {
+    Uninit,
+    Num(f64),
+    Str(String),
+    /// Input text (fields, -v vars, split pieces): numeric when it looks like a number.
+    StrNum(String),
+}
+
+impl Value {
+    fn is_numeric(&self) -> bool {
+        match self {
+            Value::Uninit | Value::Num(_) => true,
+            Value::Str(_) => false,
+            Value::StrNum(s) => looks_numeric(s),
+        }
+    }
+
+    fn num(&self) -> f64 {
+        match self {
+            Value::Uninit => 0.0,
+            Value::Num(n) => *n,
+            Value::Str(s) | Value::StrNum(s) => str_to_num(s),
+        }
+    }
+
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Uninit => false,
+            Value::Num(n) => *n != 0.0,
+            Value::Str(s) => !s.is_empty(),
+            Value::StrNum(s) if looks_numeric(s) => str_to_num(s) != 0.0,
+            Value::StrNum(s) => !s.is_empty(),
+        }
+    }
+}
+
+fn bool_num(b: bool) -> Value {
+    Value::Num(if b { 1.0 } else { 0.0 })
+}
+
+/// Length of the leading number in `s` (after blanks), 0 if none.
+fn number_prefix(s: &str) -> (usize, usize) {
+    let b = s.as_bytes();
+    let mut i = 0;
+    while i < b.len() && matches!(b[i], b' ' | b'\t' | b'\n' | b'\r') {
+        i += 1;
+    }
+    let start = i;
+    if i < b.len() && matches!(b[i], b'+' | b'-') {
+        i += 1;
+    }
+    let int_start = i;
+    while i < b.len() && b[i].is_ascii_digit() {
+        i += 1;
+    }
+    let mut digits = i - int_start;
+    if i < b.len() && b[i] == b'.' {
+        i += 1;
+        let frac = i;
+        while i < b.len() && b[i].is_ascii_digit() {
+            i += 1;
+        }
+        digits += i - frac;
+    }
+    if digits == 0 {
+        return (start, start);
+    }
+    if i < b.len() && matches!(b[i], b'e' | b'E') {
+        let mut j = i + 1;
+        if j < b.len() && matches!(b[j], b'+' | b'-') {
+            j += 1;
+        }
+        if j < b.len() && b[j].is_ascii_digit() {
+            while j < b.len() && b[j].is_ascii_digit() {
+                j += 1;
+            }
+            i = j;
+        }
+    }
+    (start, i)
+}
+
+fn str_to_num(s: &str) -> f64 {
+    let (start, end) = number_prefix(s);
+    s[start..end].parse().unwrap_or(0.0)
+}
+
+fn looks_numeric(s: &str) -> bool {
+    let (start, end) = number_prefix(s);
+    end > start && s[end..].trim_matches([' ', '\t', '\n', '\r']).is_empty()
+}
+
+fn num_to_str(n: f64, fmt: &str) -> String {
+    if n.is_finite() && n == n.trunc() && n.abs() < 1e16 {
+        format!("{}", n as i64)
+    } else {
+        sprintf(fmt, &[Value::Num(n)], fmt)
+    }
+}
+
+type Array = Rc<RefCell<BTreeMap<String, Value>>>;
+
+#[derive(Debug, Clone)]
+enum Cell {
+    Val(Value),
+    Arr(Array),
+}
+
+/// A resolved assignment target; subscripts are evaluated once.
+enum Place {
+    Var(Var),
+    Field(usize),
+    Elem(Array, String),
+}
+
+enum FieldSep {
+    Blank,
+    Char(char),
+    Re(Regex),
+}
+
+fn split_with(s: &str, sep: &FieldSep) -> Vec<String> {
+    if s.is_empty() {
+        return Vec::new();
+    }
+    match sep {
+        FieldSep::Blank => s
+            .split([' ', '\t', '\n'])
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect(),
+        FieldSep::Char(c) => s.split(*c).map(str::to_string).collect(),
+        FieldSep::Re(re) => re.split(s).map(str::to_string).collect(),
+    }
+}
+// :: ∎
+
+// ▛▞// interp :: hawk.awk.interp.run
+// ⫸ [begin.record.end]
+/// Unwinds statements and expressions.
+enum Abort {
+    Err(String),
+    Next,
+    Exit,
+}
+
+impl From<String> for Abort {
+    fn from(e: String) -> Self {
+        Abort::Err(e)
+    }
+}
+
+type R<T> = Result<T, Abort>;
+
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+    Return(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Running,
+    /// `exit` ran; END has not run yet.
+    Exited(i32),
+}
+
+/// Worst stack one eval or exec level takes in a debug build, with headroom.
+const LEVEL_BYTES: usize = 16 << 10;
+/// Eval and exec levels `STACK_SIZE` holds; deeper is a runtime error.
+const MAX_DEPTH: usize = STACK_SIZE / LEVEL_BYTES;
+/// Steps between clock checks.
+const CHECK_EVERY: u32 = 1024;
+/// Bookkeeping charged per array element on top of its key and value.
+const ENTRY_BYTES: usize = 64;
+
+/// Budgets that turn a runaway script into a runtime error, so the
+/// supervisor restarts it instead of the stage hanging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Wall clock for one BEGIN, record or END call.
+    pub call: Duration,
+    /// Time spent running over the interpreter's life, like RLIMIT_CPU on
+    /// an external child. None is unlimited.
+    pub cpu: Option<Duration>,
+    /// Rough cap on the bytes held in strings, arrays, the record and
+    /// pending output. None is unlimited.
+    pub mem: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            call: Duration::from_secs(10),
+            cpu: None,
+            mem: None,
+        }
+    }
+}
+
+pub struct Interp {
+    prog: Arc<Program>,
+    globals: Vec<Cell>,
+    frames: Vec<Vec<Cell>>,
+    record: String,
+    fields: Vec<String>,
+    ranges: Vec<bool>,
+    fs_cache: Option<(String, Rc<FieldSep>)>,
+    regexes: HashMap<String, Regex>,
+    seed: f64,
+    rng: u64,
+    exit_code: i32,
+    in_end: bool,
+    limits: Limits,
+    depth: usize,
+    steps: u32,
+    call_started: Instant,
+    /// Time spent inside begin, record and end so far.
+    busy: Duration,
+    /// Bytes allocated since the last memory audit.
+    grown: usize,
+    /// Pending stdout, drained by the runner.
+    pub out: String,
+    /// Pending stderr, drained by the runner.
+    pub err: String,
+}
+
+impl Interp {
+    /// `vars` are `-v` assignments; escapes are processed and values are strnums.
+    pub fn new(prog: Arc<Program>, vars: &BTreeMap<String, String>) -> Self {
+        let mut globals = vec![Cell::Val(Value::Uninit); prog.globals.len()];
+        let s = |v: &str| Cell::Val(Value::Str(v.to_string()));
+        globals[G_NR] = Cell::Val(Value::Num(0.0));
+        globals[G_FNR] = Cell::Val(Value::Num(0.0));
+        globals[G_FS] = s(" ");
+        globals[G_OFS] = s(" ");
+        globals[G_ORS] = s("\n");
+        globals[G_RS] = s("\n");
+        globals[G_FILENAME] = s("");
+        globals[G_SUBSEP] = s("\x1c");
+        globals[G_RSTART] = Cell::Val(Value::Num(0.0));
+        globals[G_RLENGTH] = Cell::Val(Value::Num(-1.0));
+        globals[G_CONVFMT] = s("%.6g");
+        globals[G_OFMT] = s("%.6g");
+        // The environment is never visible to scripts.
+        globals[G_ENVIRON] = Cell::Arr(Rc::new(RefCell::new(BTreeMap::new())));
+
+        for (k, v) in vars {
+            if let Some(g) = prog.globals.iter().position(|n| n == k) {
+                if g != G_ENVIRON {
+                    globals[g] = Cell::Val(Value::StrNum(unescape(v)));
+                }
+            }
+        }
+
+        let ranges = vec![false; count_ranges(&prog)];
+        Self {
+            prog,
+            globals,
+            frames: Vec::new(),
+            record: String::new(),
+            fields: Vec::new(),
+            ranges,
+            fs_cache: None,
+            regexes: HashMap::new(),
+            seed: 0.0,
+            rng: 0x2545_f491_4f6c_dd1d,
+            exit_code: 0,
+            in_end: false,
+            limits: Limits::default(),
+            depth: 0,
+            steps: 0,
+            call_started: Instant::now(),
+            busy: Duration::ZERO,
+            grown: 0,
+            out: String::new(),
+            err: String::new(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Whether the program reads input at all (awk exits after BEGIN otherwise).
+    pub fn reads_input(&self) -> bool {
+        !self.prog.rules.is_empty() || !self.prog.end.is_empty()
+    }
+
+    pub fn begin(&mut self) -> Result<Status, String> {
+        let prog = Arc::clone(&self.prog);
+        self.top(|i| i.exec_block(&prog.begin).map(|_| ()))
+    }
+
+    pub fn record(&mut self, line: &str) -> Result<Status, String> {
+        let nr = self.global_num(G_NR) + 1.0;
+        self.globals[G_NR] = Cell::Val(Value::Num(nr));
+        let fnr = self.global_num(G_FNR) + 1.0;
+        self.globals[G_FNR] = Cell::Val(Value::Num(fnr));
+        self.set_record(line.to_string());
+
+        let prog = Arc::clone(&self.prog);
+        self.top(|i| {
+            for rule in &prog.rules {
+                let fire = match &rule.pattern {
+                    Pattern::All => true,
+                    Pattern::Expr(e) => i.cond(e)?,
+                    Pattern::Range(start, stop, slot) => {
+                        if i.ranges[*slot] {
+                            if i.cond(stop)? {
+                                i.ranges[*slot] = false;
+                            }
+                            true
+                        } else if i.cond(start)? {
+                            i.ranges[*slot] = !i.cond(stop)?;
+                            true
+                        } else {
+                            false
+                        }
+                    }
+                };
+                if !fire {
+                    continue;
+                }
+                match &rule.action {
+                    Some(body) => match i.exec_block(body) {
+                        Err(Abort::Next) => return Ok(()),
+                        other => {
+                            other?;
+                        }
+                    },
+                    None => {
+                        let line = i.record.clone() + &i.global_str(G_ORS);
+                        i.out.push_str(&line);
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Run END (unless `exit` already ran inside it) and return the exit status.
+    pub fn end(&mut self) -> Result<i32, String> {
+        if !self.in_end {
+            self.in_end = true;
+            let prog = Arc::clone(&self.prog);
+            self.top(|i| i.exec_block(&prog.end).map(|_| ()))?;
+        }
+        Ok(self.exit_code)
+    }
+
+    fn top(&mut self, f: impl FnOnce(&mut Self) -> R<()>) -> Result<Status, String> {
+        self.call_started = Instant::now();
+        self.steps = 0;
+        let r = f(self);
+        self.busy += self.call_started.elapsed();
+        match r {
+            Ok(()) | Err(Abort::Next) => Ok(Status::Running),
+            Err(Abort::Exit) => Ok(Status::Exited(self.exit_code)),
+            Err(Abort::Err(e)) => Err(e),
+        }
+    }
+
+    // -- budgets -----------------------------------------------------------
+
+    /// One statement ran; checks the clock every `CHECK_EVERY` steps.
+    fn tick(&mut self) -> R<()> {
+        self.steps = self.steps.wrapping_add(1);
+        if !self.steps.is_multiple_of(CHECK_EVERY) {
+            return Ok(());
+        }
+        let ran = self.call_started.elapsed();
+        if ran > self.limits.call {
+            return Err(format!("ran past the {:?} budget of one call", self.limits.call).into());
+        }
+        if let Some(cpu) = self.limits.cpu {
+            if self.busy + ran > cpu {
+                return Err(format!("used up its cpu budget of {:?}", cpu).into());
+            }
+        }
+        Ok(())
+    }
+
+    fn enter(&mut self) -> R<()> {
+        if self.depth >= MAX_DEPTH {
+            return Err(format!("nested deeper than {} levels", MAX_DEPTH).into());
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Charge `bytes` of new strings or elements. A value over the cap fails
+    /// at once; otherwise every quarter of the cap allocated triggers an
+    /// audit of what the script actually holds.
+    fn grow(&mut self, bytes: usize) -> R<()> {
+        let Some(max) = self.limits.mem else {
+            return Ok(());
+        };
+        self.grown = self.grown.saturating_add(bytes);
+        if bytes <= max && self.grown <= max / 4 {
+            return Ok(());
+        }
+        self.grown = 0;
+        if bytes > max || self.heap_bytes() > max {
+            return Err(format!("used up its memory budget of {} MB", max >> 20).into());
+        }
+        Ok(())
+    }
+
+    fn heap_bytes(&self) -> usize {
+        let text = |v: &Value| match v {
+            Value::Str(s) | Value::StrNum(s) => s.len(),
+            _ => 0,
+        };
+        let mut seen = HashSet::new();
+        let mut total = self.record.len() + self.out.len() + self.err.len();
+        total += self.fields.iter().map(String::len).sum::<usize>();
+        for cell in self.globals.iter().chain(self.frames.iter().flatten()) {
+            total += match cell {
+                Cell::Val(v) => text(v),
+                Cell::Arr(a) if seen.insert(Rc::as_ptr(a)) => a
+                    .borrow()
+                    .iter()
+                    .map(|(k, v)| k.len() + text(v) + ENTRY_BYTES)
+                    .sum(),
+                Cell::Arr(_) => 0,
+            };
+        }
+        total
+    }
+
+    // -- variables ---------------------------------------------------------
+
+    fn cell(&mut self, v: Var) -> &mut Cell {
+        match v {
+            Var::Global(g) => &mut self.globals[g],
+            Var::Local(l) => {
+                let frame = self.frames.last_mut().expect("local outside a function");
+                &mut frame[l]
+            }
+            Var::Nf => unreachable!("NF has no cell"),
+        }
+    }
+
+    fn var_name(&self, v: Var) -> String {
+        match v {
+            Var::Global(g) => self.prog.globals[g].clone(),
+            Var::Local(l) => format!("parameter #{}", l + 1),
+            Var::Nf => "NF".to_string(),
+        }
+    }
+
+    fn get(&mut self, v: Var) -> R<Value> {
+        if v == Var::Nf {
+            return Ok(Value::Num(self.fields.len() as f64));
+        }
+        match self.cell(v) {
+            Cell::Val(x) => Ok(x.clone()),
+            Cell::Arr(_) => {
+                Err(format!("can't use array {} in a scalar context", self.var_name(v)).into())
+            }
+        }
+    }
+
+    fn set(&mut self, v: Var, val: Value) -> R<()> {
+        if v == Var::Nf {
+            let n = val.num();
+            if n < 0.0 {
+                return Err(format!("NF set to negative value {}", n).into());
+            }
+            self.fields.resize(n as usize, String::new());
+            self.rebuild_record();
+            return Ok(());
+        }
+        match self.cell(v) {
+            Cell::Val(x) => {
+                *x = val;
+                Ok(())
+            }
+            Cell::Arr(_) => Err(format!("can't assign to array {}", self.var_name(v)).into()),
+        }
+    }
+
+    fn array(&mut self, v: Var) -> R<Array> {
+        if v == Var::Nf {
+            return Err("can't use NF as an array".to_string().into());
+        }
+        let cell = self.cell(v);
+        match cell {
+            Cell::Arr(a) => Ok(Rc::clone(a)),
+            Cell::Val(Value::Uninit) => {
+                let a: Array = Rc::new(RefCell::new(BTreeMap::new()));
+                *cell = Cell::Arr(Rc::clone(&a));
+                Ok(a)
+            }
+            Cell::Val(_) => {
+                Err(format!("can't use scalar {} as an array", self.var_name(v)).into())
+            }
+        }
+    }
+
+    fn global_str(&self, g: usize) -> String {
+        match &self.globals[g] {
+            Cell::Val(Value::Num(n)) => num_to_str(*n, "%.6g"),
+            Cell::Val(Value::Str(s) | Value::StrNum(s)) => s.clone(),
+            _ => String::new(),
+        }
+    }
+
+    fn global_num(&self, g: usize) -> f64 {
+        match &self.globals[g] {
+            Cell::Val(v) => v.num(),
+            Cell::Arr(_) => 0.0,
+        }
+    }
+
+    fn to_str(&self, v: &Value) -> String {
+        match v {
+            Value::Uninit => String::new(),
+            Value::Num(n) => num_to_str(*n, &self.global_str(G_CONVFMT)),
+            Value::Str(s) | Value::StrNum(s) => s.clone(),
+        }
+    }
+
+    /// `print` formats numbers with OFMT rather than CONVFMT.
+    fn to_output(&self, v: &Value) -> String {
+        match v {
+            Value::Num(n) => num_to_str(*n, &self.global_str(G_OFMT)),
+            other => self.to_str(other),
+        }
+    }
+
+    // -- record and fields -------------------------------------------------
+
+    fn field_sep(&mut self) -> Rc<FieldSep> {
+        let fs = self.global_str(G_FS);
+        if let Some((cached, sep)) = &self.fs_cache {
+            if *cached == fs {
+                return Rc::clone(sep);
+            }
+        }
+        let sep = Rc::new(self.sep_for(&fs).unwrap_or(FieldSep::Blank));
+        self.fs_cache = Some((fs, Rc::clone(&sep)));
+        sep
+    }
+
+    fn sep_for(&mut self, fs: &str) -> Result<FieldSep, String> {
+        let mut chars = fs.chars();
+        Ok(match (chars.next(), chars.next()) {
+            (Some(' '), None) => FieldSep::Blank,
+            (Some(c), None) => FieldSep::Char(c),
+            _ => FieldSep::Re(self.dyn_regex(fs)?),
+        })
+    }
+
+    fn set_record(&mut self, line: String) {
+        let sep = self.field_sep();
+        self.fields = split_with(&line, &sep);
+        self.record = line;
+    }
+
+    fn rebuild_record(&mut self) {
+        self.record = self.fields.join(&self.global_str(G_OFS));
+    }
+
+    fn field(&self, i: usize) -> Value {
+        if i == 0 {
+            return Value::StrNum(self.record.clone());
+        }
+        match self.fields.get(i - 1) {
+            Some(f) => Value::StrNum(f.clone()),
+            None => Value::Uninit,
+        }
+    }
+
+    fn set_field(&mut self, i: usize, s: String) {
+        if i == 0 {
+            self.set_record(s);
+            return;
+        }
+        if self.fields.len() < i {
+            self.fields.resize(i, String::new());
+        }
+        self.fields[i - 1] = s;
+        self.rebuild_record();
+    }
+
+    fn field_index(&mut self, e: &Expr) -> R<usize> {
+        let n = self.eval(e)?.num();
+        if n < 0.0 || !n.is_finite() {
+            return Err(format!("attempt to access field {}", n).into());
+        }
+        Ok(n as usize)
+    }
+
+    // -- places --------------------------------------------------------------
+
+    fn place(&mut self, lv: &LValue) -> R<Place> {
+        Ok(match lv {
+            LValue::Var(v) => Place::Var(*v),
+            LValue::Field(e) => Place::Field(self.field_index(e)?),
+            LValue::Index(v, subs) => {
+                let key = self.subscript(subs)?;
+                Place::Elem(self.array(*v)?, key)
+            }
+        })
+    }
+
+    fn load(&mut self, p: &Place) -> R<Value> {
+        Ok(match p {
+            Place::Var(v) => self.get(*v)?,
+            Place::Field(i) => self.field(*i),
+            Place::Elem(a, k) => {
+                self.grow(k.len() + ENTRY_BYTES)?;
+                a.borrow_mut()
+                    .entry(k.clone())
+                    .or_insert(Value::Uninit)
+                    .clone()
+            }
+        })
+    }
+
+    fn store(&mut self, p: &Place, val: Value) -> R<()> {
+        let mut bytes = match &val {
+            Value::Str(s) | Value::StrNum(s) => s.len(),
+            _ => 0,
+        };
+        if let Place::Elem(_, k) = p {
+            bytes += k.len() + ENTRY_BYTES;
+        }
+        self.grow(bytes)?;
+        match p {
+            Place::Var(v) => self.set(*v, val)?,
+            Place::Field(i) => {
+                let s = self.to_str(&val);
+                self.set_field(*i, s);
+            }
+            Place::Elem(a, k) => {
+                a.borrow_mut().insert(k.clone(), val);
+            }
+        }
+        Ok(())
+    }
+
+    fn subscript(&mut self, subs: &[Expr]) -> R<String> {
+        let mut parts = Vec::with_capacity(subs.len());
+        for s in subs {
+            let v = self.eval(s)?;
+            parts.push(self.to_str(&v));
+        }
+        Ok(parts.join(&self.global_str(G_SUBSEP)))
+    }
+
+    // -- regex ---------------------------------------------------------------
+
+    fn dyn_regex(&mut self, re: &str) -> Result<Regex, String> {
+        if let Some(r) = self.regexes.get(re) {
+            return Ok(r.clone());
+        }
+        let r = compile_regex(re)?;
+        self.regexes.insert(re.to_string(), r.clone());
+        Ok(r)
+    }
+
+    /// A `/re/` literal is used as is; anything else is a dynamic regex.
+    fn regex_arg(&mut self, e: &Expr) -> R<Regex> {
+        if let Expr::Regex(r) = e {
+            return Ok(r.clone());
+        }
+        let v = self.eval(e)?;
+        let s = self.to_str(&v);
+        Ok(self.dyn_regex(&s)?)
+    }
+    // :: ∎
+
+    // ▛▞// statements :: hawk.awk.interp.exec
+    // ⫸ [print.loop.delete]
+    fn exec_block(&mut self, body: &[Stmt]) -> R<Flow> {
+        for s in body {
+            match self.exec(s)? {
+                Flow::Normal => {}
+                other => return Ok(other),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn exec(&mut self, s: &Stmt) -> R<Flow> {
+        self.tick()?;
+        self.enter()?;
+        let flow = self.exec_stmt(s);
+        self.depth -= 1;
+        flow
+    }
+
+    fn exec_stmt(&mut self, s: &Stmt) -> R<Flow> {
+        match s {
+            Stmt::Expr(e) => {
+                self.eval(e)?;
+            }
+            Stmt::Print(args, out) => {
+                let line = if args.is_empty() {
+                    self.record.clone()
+                } else {
+                    let mut parts = Vec::with_capacity(args.len());
+                    for a in args {
+                        let v = self.eval(a)?;
+                        parts.push(self.to_output(&v));
+                    }
+                    parts.join(&self.global_str(G_OFS))
+                };
+                let ors = self.global_str(G_ORS);
+                self.write(*out, &line)?;
+                self.write(*out, &ors)?;
+            }
+            Stmt::Printf(args, out) => {
+                let mut vals = Vec::with_capacity(args.len());
+                for a in args {
+                    vals.push(self.eval(a)?);
+                }
+                let fmt = self.to_str(&vals[0]);
+                let text = sprintf(&fmt, &vals[1..], &self.global_str(G_CONVFMT));
+                self.write(*out, &text)?;
+            }
+            Stmt::If(cond, then, els) => {
+                if self.cond(cond)? {
+                    return self.exec(then);
+                } else if let Some(els) = els {
+                    return self.exec(els);
+                }
+            }
+            Stmt::While(cond, body) => {
+                while self.cond(cond)? {
+                    match self.exec(body)? {
+                        Flow::Break => break,
+                        Flow::Return(v) => return Ok(Flow::Return(v)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                }
+            }
+            Stmt::Do(body, cond) => loop {
+                match self.exec(body)? {
+                    Flow::Break => break,
+                    Flow::Return(v) => return Ok(Flow::Return(v)),
+                    Flow::Normal | Flow::Continue => {}
+                }
+                if !self.cond(cond)? {
+                    break;
+                }
+            },
+            Stmt::For(init, cond, step, body) => {
+                if let Some(init) = init {
+                    self.exec(init)?;
+                }
+                loop {
+                    if let Some(c) = cond {
+                        if !self.cond(c)? {
+                            break;
+                        }
+                    }
+                    match self.exec(body)? {
+                        Flow::Break => break,
+                        Flow::Return(v) => return Ok(Flow::Return(v)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                    if let Some(step) = step {
+                        self.exec(step)?;
+                    }
+                }
+            }
+            Stmt::ForIn(key, arr, body) => {
+                let a = self.array(*arr)?;
+                let keys: Vec<String> = a.borrow().keys().cloned().collect();
+                for k in keys {
+                    if !a.borrow().contains_key(&k) {
+                        continue;
+                    }
+                    self.set(*key, Value::StrNum(k))?;
+                    match self.exec(body)? {
+                        Flow::Break => break,
+                        Flow::Return(v) => return Ok(Flow::Return(v)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                }
+            }
+            Stmt::Block(body) => return self.exec_block(body),
+            Stmt::Delete(arr, subs) => {
+                let a = self.array(*arr)?;
+                match subs {
+                    None => a.borrow_mut().clear(),
+                    Some(subs) => {
+                        let k = self.subscript(subs)?;
+                        a.borrow_mut().remove(&k);
+                    }
+                }
+            }
+            Stmt::Next => {
+                if self.in_end
+                    || self.frames.is_empty()
+                        && self.record.is_empty()
+                        && self.fields.is_empty()
+                        && self.global_num(G_NR) == 0.0
+                {
+                    return Err("next used outside a record rule".to_string().into());
+                }
+                return Err(Abort::Next);
+            }
+            Stmt::Exit(code) => {
+                if let Some(c) = code {
+                    self.exit_code = self.eval(c)?.num() as i32;
+                }
+                return Err(Abort::Exit);
+            }
+            Stmt::Return(v) => {
+                let v = match v {
+                    Some(e) => self.eval(e)?,
+                    None => Value::Uninit,
+                };
+                return Ok(Flow::Return(v));
+            }
+            Stmt::Break => return Ok(Flow::Break),
+            Stmt::Continue => return Ok(Flow::Continue),
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn write(&mut self, out: Output, s: &str) -> R<()> {
+        self.grow(s.len())?;
+        match out {
+            Output::Stdout => self.out.push_str(s),
+            Output::Stderr => self.err.push_str(s),
+        }
+        Ok(())
+    }
+    // :: ∎
+
+    // ▛▞// expressions :: hawk.awk.interp.eval
+    // ⫸ [arith.compare.call]
+    fn cond(&mut self, e: &Expr) -> R<bool> {
+        Ok(self.eval(e)?.truthy())
+    }
+
+    fn eval(&mut self, e: &Expr) -> R<Value> {
+        self.enter()?;
+        let v = self.eval_expr(e);
+        self.depth -= 1;
+        v
+    }
+
+    fn eval_expr(&mut self, e: &Expr) -> R<Value> {
+        Ok(match e {
+            Expr::Num(n) => Value::Num(*n),
+            Expr::Str(s) => Value::Str(s.clone()),
+            Expr::Regex(r) => bool_num(r.is_match(&self.record)),
+            Expr::Var(v) => self.get(*v)?,
+            Expr::Field(i) => {
+                let i = self.field_index(i)?;
+                self.field(i)
+            }
+            Expr::Index(v, subs) => {
+                let key = self.subscript(subs)?;
+                self.grow(key.len() + ENTRY_BYTES)?;
+                let a = self.array(*v)?;
+                let mut a = a.borrow_mut();
+                a.entry(key).or_insert(Value::Uninit).clone()
+            }
+            Expr::Group(_) => {
+                return Err("(a, b) grouping is only valid before 'in' or in print"
+                    .to_string()
+                    .into())
+            }
+            Expr::Assign(lv, op, rhs) => {
+                let p = self.place(lv)?;
+                let r = self.eval(rhs)?;
+                let v = match op {
+                    None => r,
+                    Some(op) => {
+                        let cur = self.load(&p)?;
+                        Value::Num(arith(*op, cur.num(), r.num())?)
+                    }
+                };
+                self.store(&p, v.clone())?;
+                v
+            }
+            Expr::Cond(c, a, b) => {
+                if self.cond(c)? {
+                    self.eval(a)?
+                } else {
+                    self.eval(b)?
+                }
+            }
+            Expr::And(a, b) => bool_num(self.cond(a)? && self.cond(b)?),
+            Expr::Or(a, b) => bool_num(self.cond(a)? || self.cond(b)?),
+            Expr::Not(a) => bool_num(!self.cond(a)?),
+            Expr::Neg(a) => Value::Num(-self.eval(a)?.num()),
+            Expr::Plus(a) => Value::Num(self.eval(a)?.num()),
+            Expr::Binary(op, a, b) => {
+                let a = self.eval(a)?;
+                let b = self.eval(b)?;
+                match op {
+                    BinOp::Concat => {
+                        let (a, b) = (self.to_str(&a), self.to_str(&b));
+                        self.grow(a.len() + b.len())?;
+                        Value::Str(a + &b)
+                    }
+                    BinOp::Lt => bool_num(self.compare(&a, &b) == Ordering::Less),
+                    BinOp::Le => bool_num(self.compare(&a, &b) != Ordering::Greater),
+                    BinOp::Gt => bool_num(self.compare(&a, &b) == Ordering::Greater),
+                    BinOp::Ge => bool_num(self.compare(&a, &b) != Ordering::Less),
+                    BinOp::Eq => bool_num(self.compare(&a, &b) == Ordering::Equal),
+                    BinOp::Ne => bool_num(self.compare(&a, &b) != Ordering::Equal),
+                    op => Value::Num(arith(*op, a.num(), b.num())?),
+                }
+            }
+            Expr::Match(neg, lhs, re) => {
+                let v = self.eval(lhs)?;
+                let s = self.to_str(&v);
+                let re = self.regex_arg(re)?;
+                bool_num(re.is_match(&s) != *neg)
+            }
+            Expr::In(subs, arr) => {
+                let key = self.subscript(subs)?;
+                let a = self.array(*arr)?;
+                let found = a.borrow().contains_key(&key);
+                bool_num(found)
+            }
+            Expr::Incr(lv, delta, pre) => {
+                let p = self.place(lv)?;
+                let old = self.load(&p)?.num();
+                self.store(&p, Value::Num(old + delta))?;
+                Value::Num(if *pre { old + delta } else { old })
+            }
+            Expr::Call(slot, args) => self.call(*slot, args)?,
+            Expr::Builtin(b, args) => self.builtin(*b, args)?,
+        })
+    }
+
+    fn compare(&self, a: &Value, b: &Value) -> Ordering {
+        if a.is_numeric() && b.is_numeric() {
+            a.num().partial_cmp(&b.num()).unwrap_or(Ordering::Equal)
+        } else {
+            self.to_str(a).cmp(&self.to_str(b))
+        }
+    }
+
+    fn call(&mut self, slot: usize, args: &[Expr]) -> R<Value> {
+        let prog = Arc::clone(&self.prog);
+        let f = &prog.funcs[slot];
+        if args.len() > f.params {
+            return Err(format!(
+                "function {} called with {} args, accepts {}",
+                f.name,
+                args.len(),
+                f.params
+            )
+            .into());
+        }
+        // Arrays go by reference. An untyped variable may become an array
+        // inside the call; that array is handed back afterwards.
+        let mut locals = Vec::with_capacity(f.params);
+        let mut hand_back = Vec::new();
+        for (i, a) in args.iter().enumerate() {
+            match a {
+                Expr::Var(v) if *v != Var::Nf => match self.cell(*v).clone() {
+                    Cell::Val(Value::Uninit) => {
+                        hand_back.push((i, *v));
+                        locals.push(Cell::Val(Value::Uninit));
+                    }
+                    cell => locals.push(cell),
+                },
+                _ => locals.push(Cell::Val(self.eval(a)?)),
+            }
+        }
+        locals.resize(f.params, Cell::Val(Value::Uninit));
+
+        self.frames.push(locals);
+        let flow = self.exec_block(&f.body);
+        let frame = self.frames.pop().expect("call frame");
+        for (i, v) in hand_back {
+            if let Cell::Arr(a) = &frame[i] {
+                *self.cell(v) = Cell::Arr(Rc::clone(a));
+            }
+        }
+
+        match flow? {
+            Flow::Return(v) => Ok(v),
+            _ => Ok(Value::Uninit),
+        }
+    }
+    // :: ∎
+
+    // ▛▞// builtins :: hawk.awk.interp.builtin
+    // ⫸ [string.regex.math]
+    fn str_arg(&mut self, args: &[Expr], i: usize) -> R<String> {
+        let v = self.eval(&args[i])?;
+        Ok(self.to_str(&v))
+    }
+
+    fn num_arg(&mut self, args: &[Expr], i: usize) -> R<f64> {
+        Ok(self.eval(&args[i])?.num())
+    }
+
+    fn builtin(&mut self, b: Builtin, args: &[Expr]) -> R<Value> {
+        Ok(match b {
+            Builtin::Length => match args.first() {
+                None => Value::Num(self.record.chars().count() as f64),
+                Some(Expr::Var(v)) if *v != Var::Nf && matches!(self.cell(*v), Cell::Arr(_)) => {
+                    let a = self.array(*v)?;
+                    let n = a.borrow().len();
+                    Value::Num(n as f64)
+                }
+                Some(_) => Value::Num(self.str_arg(args, 0)?.chars().count() as f64),
+            },
+            Builtin::Substr => {
+                let s = self.str_arg(args, 0)?;
+                let len = s.chars().count() as f64;
+                let m = round_half_even(self.num_arg(args, 1)?);
+                let end = match args.get(2) {
+                    Some(_) => m + round_half_even(self.num_arg(args, 2)?),
+                    None => f64::INFINITY,
+                };
+                let start = m.max(1.0);
+                let end = end.min(len + 1.0);
+                if end <= start || end.is_nan() || start.is_nan() {
+                    Value::Str(String::new())
+                } else {
+                    let skip = (start - 1.0) as usize;
+                    let take = (end - start) as usize;
+                    Value::Str(s.chars().skip(skip).take(take).collect())
+                }
+            }
+            Builtin::Index => {
+                let s = self.str_arg(args, 0)?;
+                let t = self.str_arg(args, 1)?;
+                let pos = match s.find(&t) {
+                    Some(byte) if !t.is_empty() => s[..byte].chars().count() + 1,
+                    _ => 0,
+                };
+                Value::Num(pos as f64)
+            }
+            Builtin::Split => {
+                let s = self.str_arg(args, 0)?;
+                let sep = match args.get(2) {
+                    None => None,
+                    Some(Expr::Regex(r)) => Some(FieldSep::Re(r.clone())),
+                    Some(_) => {
+                        let fs = self.str_arg(args, 2)?;
+                        Some(self.sep_for(&fs)?)
+                    }
+                };
+                let parts = match sep {
+                    Some(sep) => split_with(&s, &sep),
+                    None => split_with(&s, &self.field_sep()),
+                };
+                let Expr::Var(v) = &args[1] else {
+                    return Err("split needs an array name".to_string().into());
+                };
+                let bytes = parts.iter().map(|p| p.len() + ENTRY_BYTES).sum();
+                self.grow(bytes)?;
+                let a = self.array(*v)?;
+                let mut a = a.borrow_mut();
+                a.clear();
+                for (i, p) in parts.iter().enumerate() {
+                    a.insert((i + 1).to_string(), Value::StrNum(p.clone()));
+                }
+                Value::Num(parts.len() as f64)
+            }
+            Builtin::Sub | Builtin::Gsub => {
+                let re = self.regex_arg(&args[0])?;
+                let repl = self.str_arg(args, 1)?;
+                let target = match args.get(2) {
+                    Some(e) => {
+                        let lv = match e {
+                            Expr::Var(v) => LValue::Var(*v),
+                            Expr::Field(i) => {
+                                let i = self.field_index(i)?;
+                                return self.substitute(b, &re, &repl, Place::Field(i));
+                            }
+                            Expr::Index(v, subs) => {
+                                let key = self.subscript(subs)?;
+                                let a = self.array(*v)?;
+                                return self.substitute(b, &re, &repl, Place::Elem(a, key));
+                            }
+                            _ => {
+                                return Err("sub/gsub target must be a variable".to_string().into())
+                            }
+                        };
+                        self.place(&lv)?
+                    }
+                    None => Place::Field(0),
+                };
+                return self.substitute(b, &re, &repl, target);
+            }
+            Builtin::Match => {
+                let s = self.str_arg(args, 0)?;
+                let re = self.regex_arg(&args[1])?;
+                let (start, len) = match re.find(&s) {
+                    Some(m) => (
+                        s[..m.start()].chars().count() as f64 + 1.0,
+                        m.as_str().chars().count() as f64,
+                    ),
+                    None => (0.0, -1.0),
+                };
+                self.globals[G_RSTART] = Cell::Val(Value::Num(start));
+                self.globals[G_RLENGTH] = Cell::Val(Value::Num(len));
+                Value::Num(start)
+            }
+            Builtin::Sprintf => {
+                let mut vals = Vec::with_capacity(args.len());
+                for a in args {
+                    vals.push(self.eval(a)?);
+                }
+                let fmt = self.to_str(&vals[0]);
+                let text = sprintf(&fmt, &vals[1..], &self.global_str(G_CONVFMT));
+                self.grow(text.len())?;
+                Value::Str(text)
+            }
+            Builtin::Tolower => Value::Str(self.str_arg(args, 0)?.to_lowercase()),
+            Builtin::Toupper => Value::Str(self.str_arg(args, 0)?.to_uppercase()),
+            Builtin::Int => Value::Num(self.num_arg(args, 0)?.trunc()),
+            Builtin::Sqrt => Value::Num(self.num_arg(args, 0)?.sqrt()),
+            Builtin::Exp => Value::Num(self.num_arg(args, 0)?.exp()),
+            Builtin::Log => Value::Num(self.num_arg(args, 0)?.ln()),
+            Builtin::Sin => Value::Num(self.num_arg(args, 0)?.sin()),
+            Builtin::Cos => Value::Num(self.num_arg(args, 0)?.cos()),
+            Builtin::Atan2 => {
+                let y = self.num_arg(args, 0)?;
+                let x = self.num_arg(args, 1)?;
+                Value::Num(y.atan2(x))
+            }
+            Builtin::Rand => {
+                // xorshift64*: same sequence for the same seed on every host.
+                self.rng ^= self.rng >> 12;
+                self.rng ^= self.rng << 25;
+                self.rng ^= self.rng >> 27;
+                let r = self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d);
+                Value::Num((r >> 11) as f64 / (1u64 << 53) as f64)
+            }
+            Builtin::Srand => {
+                let prev = self.seed;
+                self.seed = match args.first() {
+                    Some(_) => self.num_arg(args, 0)?,
+                    None => unix_now() as f64,
+                };
+                self.rng = (self.seed.to_bits() ^ 0x2545_f491_4f6c_dd1d).max(1);
+                Value::Num(prev)
+            }
+            Builtin::Systime => Value::Num(unix_now() as f64),
+            Builtin::Fflush => Value::Num(0.0),
+            Builtin::Close => Value::Num(-1.0),
+        })
+    }
+
+    /// `&` in the replacement is the match, `\&` a literal `&`.
+    fn substitute(&mut self, b: Builtin, re: &Regex, repl: &str, target: Place) -> R<Value> {
+        let cur = self.load(&target)?;
+        let s = self.to_str(&cur);
+        let mut out = String::with_capacity(s.len());
+        let mut last = 0;
+        let mut count = 0;
+        for m in re.find_iter(&s) {
+            out.push_str(&s[last..m.start()]);
+            let mut chars = repl.chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' if matches!(chars.peek(), Some('&') | Some('\\')) => {
+                        out.push(chars.next().unwrap_or('\\'));
+                    }
+                    '&' => out.push_str(m.as_str()),
+                    c => out.push(c),
+                }
+            }
+            last = m.end();
+            count += 1;
+            if b == Builtin::Sub {
+                break;
+            }
+        }
+        if count > 0 {
+            out.push_str(&s[last..]);
+            self.store(&target, Value::Str(out))?;
+        }
+        Ok(Value::Num(count as f64))
+    }
+}
+// :: ∎
+
+// ▛▞// helpers :: hawk.awk.interp.helpers
+// ⫸ [arith.printf.escape]
+fn arith(op: BinOp, a: f64, b: f64) -> Result<f64, String> {
+    Ok(match op {
+        BinOp::Add => a + b,
+        BinOp::Sub => a - b,
+        BinOp::Mul => a * b,
+        BinOp::Div => {
+            if b == 0.0 {
+                return Err("division by zero".to_string());
+            }
+            a / b
+        }
+        BinOp::Mod => {
+            if b == 0.0 {
+                return Err("division by zero in %".to_string());
+            }
+            a % b
+        }
+        BinOp::Pow => a.powf(b),
+        _ => unreachable!("not an arithmetic operator"),
+    })
+}
+
+fn round_half_even(n: f64) -> f64 {
+    let r = n.round();
+    if (n - n.trunc()).abs() == 0.5 && r % 2.0 != 0.0 {
+        r - n.signum()
+    } else {
+        r
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn count_ranges(prog: &Program) -> usize {
+    prog.rules
+        .iter()
+        .filter_map(|r| match r.pattern {
+            Pattern::Range(_, _, slot) => Some(slot + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Escape processing for `-v` values, as awk does for command line assignments.
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('\\') => out.push('\\'),
+            Some('"') => out.push('"'),
+            Some('/') => out.push('/'),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+struct Spec {
+    left: bool,
+    plus: bool,
+    space: bool,
+    alt: bool,
+    zero: bool,
+    width: usize,
+    prec: Option<usize>,
+}
+
+impl Spec {
+    fn pad(&self, sign: &str, body: &str, numeric: bool) -> String {
+        let len = sign.chars().count() + body.chars().count();
+        if len >= self.width {
+            return format!("{}{}", sign, body);
+        }
+        let fill = self.width - len;
+        if self.left {
+            format!("{}{}{}", sign, body, " ".repeat(fill))
+        } else if self.zero && numeric {
+            format!("{}{}{}", sign, "0".repeat(fill), body)
+        } else {
+            format!("{}{}{}", " ".repeat(fill), sign, body)
+        }
+    }
+
+    fn sign(&self, neg: bool) -> &'static str {
+        if neg {
+            "-"
+        } else if self.plus {
+            "+"
+        } else if self.space {
+            " "
+        } else {
+            ""
+        }
+    }
+}
+
+/// Widths and precisions past this are clamped, so a format cannot ask
+/// for gigabytes of padding.
+const MAX_WIDTH: usize = 1 << 16;
+
+/// C printf for awk: %c %d %i %o %x %X %u %e %E %f %F %g %G %s %%.
+fn sprintf(fmt: &str, args: &[Value], convfmt: &str) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut chars = fmt.chars().peekable();
+    let to_str = |v: &Value| match v {
+        Value::Uninit => String::new(),
+        Value::Num(n) => {
+            if n.is_finite() && *n == n.trunc() && n.abs() < 1e16 {
+                format!("{}", *n as i64)
+            } else {
+                format_float(*n, 'g', 6, false)
+            }
+        }
+        Value::Str(s) | Value::StrNum(s) => s.clone(),
+    };
+    let _ = convfmt;
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            out.push('%');
+            continue;
+        }
+
+        let mut spec = Spec {
+            left: false,
+            plus: false,
+            space: false,
+            alt: false,
+            zero: false,
+            width: 0,
+            prec: None,
+        };
+        let mut raw = String::from("%");
+        while let Some(&f) = chars.peek() {
+            match f {
+                '-' => spec.left = true,
+                '+' => spec.plus = true,
+                ' ' => spec.space = true,
+                '#' => spec.alt = true,
+                '0' => spec.zero = true,
+                _ => break,
+            }
+            raw.push(f);
+            chars.next();
+        }
+        if chars.peek() == Some(&'*') {
+            chars.next();
+            let w = args.next().map(Value::num).unwrap_or(0.0);
+            if w < 0.0 {
+                spec.left = true;
+            }
+            spec.width = w.abs().min(MAX_WIDTH as f64) as usize;
+        } else {
+            while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+                spec.width = (spec.width * 10 + d as usize).min(MAX_WIDTH);
+                chars.next();
+            }
+        }
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            let mut p = 0usize;
+            if chars.peek() == Some(&'*') {
+                chars.next();
+                p = args.next().map(Value::num).unwrap_or(0.0).clamp(0.0, MAX_WIDTH as f64) as usize;
+            } else {
+                while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+                    p = (p * 10 + d as usize).min(MAX_WIDTH);
+                    chars.next();
+                }
+            }
+            spec.prec = Some(p);
+        }
+        while matches!(chars.peek(), Some('h' | 'l' | 'L' | 'q' | 'j' | 'z' | 't')) {
+            chars.next();
+        }
+
+        let Some(conv) = chars.next() else {
+            out.push_str(&raw);
+            break;
+        };
+        let arg = args.next().cloned().unwrap_or(Value::Uninit);
+        match conv {
+            'c' => {
+                let body = match &arg {
+                    Value::Num(n) => char::from_u32(*n as u32)
+                        .map(String::from)
+                        .unwrap_or_default(),
+                    other => to_str(other)
+                        .chars()
+                        .next()
+                        .map(String::from)
+                        .unwrap_or_default(),
+                };
+                out.push_str(&spec.pad("", &body, false));
+            }
+            's' => {
+                let mut s = to_str(&arg);
+                if let Some(p) = spec.prec {
+                    s = s.chars().take(p).collect();
+                }
+                out.push_str(&spec.pad("", &s, false));
+            }
+            'd' | 'i' => {
+                let n = arg.num();
+                if !n.is_finite() {
+                    out.push_str(&spec.pad(
+                        spec.sign(n < 0.0),
+                        &format_float(n.abs(), 'f', 0, false),
+                        false,
+                    ));
+                    continue;
+                }
+                let n = n.trunc();
+                let mut body = format!("{}", n.abs() as u128);
+                if let Some(p) = spec.prec {
+                    if p == 0 && n == 0.0 {
+                        body.clear();
+                    }
+                    while body.len() < p {
+                        body.insert(0, '0');
+                    }
+                }
+                let zero_ok = spec.prec.is_none();
+                out.push_str(&spec.pad(spec.sign(n < 0.0), &body, zero_ok));
+            }
+            'o' | 'x' | 'X' | 'u' => {
+                let n = arg.num().trunc();
+                let u = if n < 0.0 { n as i64 as u64 } else { n as u64 };
+                let mut body = match conv {
+                    'o' => format!("{:o}", u),
+                    'x' => format!("{:x}", u),
+                    'X' => format!("{:X}", u),
+                    _ => format!("{}", u),
+                };
+                if let Some(p) = spec.prec {
+                    while body.len() < p {
+                        body.insert(0, '0');
+                    }
+                }
+                let prefix = match conv {
+                    'o' if spec.alt && !body.starts_with('0') => "0",
+                    'x' if spec.alt && u != 0 => "0x",
+                    'X' if spec.alt && u != 0 => "0X",
+                    _ => "",
+                };
+                out.push_str(&spec.pad(prefix, &body, spec.prec.is_none()));
+            }
+            'e' | 'E' | 'f' | 'F' | 'g' | 'G' => {
+                let n = arg.num();
+                let body = format_float(n.abs(), conv, spec.prec.unwrap_or(6), spec.alt);
+                let neg = n < 0.0 || (n == 0.0 && n.is_sign_negative());
+                out.push_str(&spec.pad(spec.sign(neg), &body, n.is_finite()));
+            }
+            other => {
+                // Unknown conversion: emit it as written.
+                out.push_str(&raw);
+                out.push(other);
+            }
+        }
+    }
+    out
+}
+
+/// `n` is non-negative; the sign is the caller's.
+fn format_float(n: f64, conv: char, prec: usize, alt: bool) -> String {
+    let upper = conv.is_ascii_uppercase();
+    if n.is_nan() {
+        return if upper { "NAN" } else { "nan" }.to_string();
+    }
+    if n.is_infinite() {
+        return if upper { "INF" } else { "inf" }.to_string();
+    }
+    let body = match conv.to_ascii_lowercase() {
+        'f' => {
+            let mut s = format!("{:.*}", prec, n);
+            if alt && prec == 0 {
+                s.push('.');
+            }
+            s
+        }
+        'e' => format_exp(n, prec, alt),
+        _ => {
+            let p = prec.max(1);
+            let x = exponent_of(n, p - 1);
+            let mut s = if x < -4 || x >= p as i32 {
+                format_exp(n, p - 1, alt)
+            } else {
+                format!("{:.*}", (p as i32 - 1 - x).max(0) as usize, n)
+            };
+            if !alt {
+                s = strip_zeros(&s);
+            }
+            s
+        }
+    };
+    if upper {
+        body.to_uppercase()
+    } else {
+        body
+    }
+}
+
+/// Decimal exponent of `n` once rounded to `prec` fraction digits.
+fn exponent_of(n: f64, prec: usize) -> i32 {
+    if n == 0.0 {
+        return 0;
+    }
+    let s = format!("{:.*e}", prec, n);
+    s.split_once('e')
+        .and_then(|(_, e)| e.parse().ok())
+        .unwrap_or(0)
+}
+
+fn format_exp(n: f64, prec: usize, alt: bool) -> String {
+    let s = format!("{:.*e}", prec, n);
+    let (mant, exp) = s.split_once('e').unwrap_or((&s, "0"));
+    let exp: i32 = exp.parse().unwrap_or(0);
+    let dot = if alt && prec == 0 { "." } else { "" };
+    format!(
+        "{}{}e{}{:02}",
+        mant,
+        dot,
+        if exp < 0 { '-' } else { '+' },
+        exp.abs()
+    )
+}
+
+/// `%g` drops trailing fraction zeros (and a bare `.`), keeping any exponent.
+fn strip_zeros(s: &str) -> String {
+    let (mant, exp) = match s.find('e') {
+        Some(i) => (&s[..i], &s[i..]),
+        None => (s, ""),
+    };
+    if !mant.contains('.') {
+        return s.to_string();
+    }
+    let mant = mant.trim_end_matches('0').trim_end_matches('.');
+    format!("{}{}", mant, exp)
+}
+// :: ∎
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::awk::lang::compile;
+
+    fn run(src: &str, vars: &[(&str, &str)], input: &[&str]) -> (String, i32) {
+        let prog = Arc::new(compile(src).unwrap());
+        let vars = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let mut i = Interp::new(prog, &vars);
+        let mut status = i.begin().unwrap();
+        if status == Status::Running && i.reads_input() {
+            for line in input {
+                status = i.record(line).unwrap();
+                if status != Status::Running {
+                    break;
+                }
+            }
+        }
+        let code = i.end().unwrap();
+        (i.out, code)
+    }
+
+    /// BEGIN only, on the runner's stack, with the error a runner would see.
+    fn run_limited(src: &str, limits: Limits) -> Result<String, String> {
+        let prog = Arc::new(compile(src)?);
+        crate::awk::with_stack(move || {
+            let mut i = Interp::new(prog, &BTreeMap::new()).with_limits(limits);
+            i.begin()?;
+            i.end()?;
+            Ok(i.out)
+        })
+    }
+
+    #[test]
+    fn fields_patterns_and_arrays() {
+        let src = r#"
+            BEGIN { FS = "\t"; OFS = "\t" }
+            $5 ~ /^(fail|warn)$/ && $3 != skip { n[$3]++; $6 = toupper($6); print }
+            END { for (k in n) printf "%s=%d\n", k, n[k] }
+        "#;
+        let input = [
+            "t\tHEALTH\tgrpc\ta\tfail\tdown\t",
+            "t\tHEALTH\tdocker\tb\twarn\tslow\t",
+            "t\tHEALTH\tgrpc\tc\tok\tup\t",
+            "t\tHEALTH\tgrpc\td\twarn\tflaky\t",
+        ];
+        let (out, code) = run(src, &[("skip", "docker")], &input);
+        assert_eq!(
+            out,
+            "t\tHEALTH\tgrpc\ta\tfail\tDOWN\t\nt\tHEALTH\tgrpc\td\twarn\tFLAKY\t\ngrpc=2\n"
+        );
+        assert_eq!(code, 0);
+    }
+
+    #[test]
+    fn numbers_strings_and_printf() {
+        let src = r#"
+            function fact(n) { return n <= 1 ? 1 : n * fact(n - 1) }
+            BEGIN {
+                x = "10"; y = 9
+                print (x < y), ("10" < "9"), 1/4, 2^10, -7 % 3, fact(5)
+                printf "%5.2f|%-4s|%03d|%x|%e|%g|%c\n", 3.14159, "ab", 7, 255, 12345.678, 0.0001, 65
+                s = "a.b.c"; n = split(s, parts, ".")
+                print n, parts[3], substr("hawkframe", 5), index("hawk", "wk"), length("héllo")
+                t = "aaa"; print gsub(/a/, "<&>", t), t
+                if (match("xxabcx", /b+c/)) print RSTART, RLENGTH
+                exit 3
+            }
+        "#;
+        let (out, code) = run(src, &[], &[]);
+        assert_eq!(
+            out,
+            "1 1 0.25 1024 -1 120\n 3.14|ab  |007|ff|1.234568e+04|0.0001|A\n3 c frame 3 5\n3 <a><a><a>\n4 2\n"
+        );
+        assert_eq!(code, 3);
+    }
+
+
+    #[test]
+    fn runaway_recursion_is_an_error_not_a_crash() {
+        for src in [
+            "function f(n) { return n ? f(n + 1) : 0 } BEGIN { f(1) }",
+            r#"function f(n) { return substr(f(n + 1) "x", 1) } BEGIN { f(1) }"#,
+            "function f(n) { gsub(/x/, \"y\", a[f(n + 1)]) } BEGIN { f(1) }",
+            r#"function f(n) { if (1) { while (1) { return sprintf("%s", f(n + 1)) } } } BEGIN { f(1) }"#,
+        ] {
+            let err = run_limited(src, Limits::default()).unwrap_err();
+            assert!(err.contains("nested deeper than"), "{}: {}", src, err);
+        }
+        let ok = "function f(n) { return n ? n + f(n - 1) : 0 } BEGIN { print f(500) }";
+        assert_eq!(run_limited(ok, Limits::default()).unwrap(), "125250\n");
+    }
+
+    #[test]
+    fn budgets_stop_runaway_scripts() {
+        let call = Limits {
+            call: Duration::from_millis(50),
+            ..Limits::default()
+        };
+        let err = run_limited("BEGIN { while (1) {} }", call).unwrap_err();
+        assert!(err.contains("budget of one call"), "{}", err);
+
+        let cpu = Limits {
+            cpu: Some(Duration::ZERO),
+            ..Limits::default()
+        };
+        let err = run_limited("BEGIN { for (i = 0; i < 1e6; i++) ; }", cpu).unwrap_err();
+        assert!(err.contains("cpu budget"), "{}", err);
+
+        let mem = Limits {
+            mem: Some(1 << 20),
+            ..Limits::default()
+        };
+        for src in [
+            r#"BEGIN { s = "x"; while (1) s = s s }"#,
+            r#"BEGIN { while (1) a[n++] = "xxxxxxxx" }"#,
+            r#"BEGIN { while (1) printf "%1000s", "" }"#,
+        ] {
+            let err = run_limited(src, mem).unwrap_err();
+            assert!(err.contains("memory budget of 1 MB"), "{}: {}", src, err);
+        }
+        let small = r#"BEGIN { for (i = 0; i < 100; i++) a[i] = i; print length(a) }"#;
+        assert_eq!(run_limited(small, mem).unwrap(), "100\n");
+    }
+
+    #[test]
+    fn printf_widths_are_clamped() {
+        let (out, _) = run(r#"BEGIN { printf "%99999999999999999999d|%.99999999999999999999d", 1, 2 }"#, &[], &[]);
+        assert_eq!(out.len(), 2 * MAX_WIDTH + 1);
+        assert!(out.ends_with("02"), "{}", &out[out.len() - 4..]);
+    }
+
+    #[test]
+    fn runtime_errors() {
+        for (src, want) in [
+            ("BEGIN { a[1] = 1; a = 2 }", "can't assign to array a"),
+            ("BEGIN { x = 1; x[1] = 2 }", "can't use scalar x as an array"),
+            ("BEGIN { $(-1) = 1 }", "attempt to access field -1"),
+            ("BEGIN { NF = -1 }", "NF set to negative value"),
+            ("function f(a) { } BEGIN { f(1, 2) }", "called with 2 args, accepts 1"),
+            ("BEGIN { next }", "next used outside a record rule"),
+            ("BEGIN { print 1 / 0 }", "division by zero"),
+        ] {
+            let err = run_limited(src, Limits::default()).unwrap_err();
+            assert!(err.contains(want), "{}: {}", src, err);
+        }
+    }
+
+    #[test]
+    fn rejects_commands_and_files() {
+        for src in [
+            r#"{ system("id") }"#,
+            r#"{ print | "sh" }"#,
+            r#"{ "date" | getline d }"#,
+            r#"{ print > "/tmp/x" }"#,
+            r#"{ getline line < "/etc/passwd" }"#,
+        ] {
+            assert!(compile(src).is_err(), "{}", src);
+        }
+        assert!(compile(r#"{ print "x" > "/dev/stderr" }"#).is_ok());
+    }
+}
+// :: ∎
@@ -0,0 +1,1637 @@
+// ▛▞// awk language :: hawk.awk.lang
+// @ctx ⫸ [lex.parse.ast]
+//! The awk subset the embedded backend runs: patterns, ranges, user
+//! functions, arrays, fields, printf and the string/math builtins. Commands,
+//! pipes, getline and file output are rejected at compile time, so a compiled
+//! program can only read its input frames and write stdout and stderr.
+use std::collections::HashMap;
+
+use regex::Regex;
+
+// :: ∎
+
+// ▛▞// ast :: hawk.awk.lang.ast
+// ⫸ [program.rule.stmt.expr]
+#[derive(Debug)]
+pub struct Program {
+    pub begin: Vec<Stmt>,
+    pub rules: Vec<Rule>,
+    pub end: Vec<Stmt>,
+    pub funcs: Vec<Func>,
+    /// Global names by slot; specials first (see `G_*`).
+    pub globals: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Rule {
+    pub pattern: Pattern,
+    /// None prints the record.
+    pub action: Option<Vec<Stmt>>,
+}
+
+#[derive(Debug)]
+pub enum Pattern {
+    All,
+    Expr(Expr),
+    /// `start, stop`; the slot holds the in-range flag at runtime.
+    Range(Expr, Expr, usize),
+}
+
+#[derive(Debug)]
+pub struct Func {
+    pub name: String,
+    pub params: usize,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug)]
+pub enum Stmt {
+    Expr(Expr),
+    Print(Vec<Expr>, Output),
+    Printf(Vec<Expr>, Output),
+    If(Expr, Box<Stmt>, Option<Box<Stmt>>),
+    While(Expr, Box<Stmt>),
+    Do(Box<Stmt>, Expr),
+    For(
+        Option<Box<Stmt>>,
+        Option<Expr>,
+        Option<Box<Stmt>>,
+        Box<Stmt>,
+    ),
+    ForIn(Var, Var, Box<Stmt>),
+    Block(Vec<Stmt>),
+    Delete(Var, Option<Vec<Expr>>),
+    Next,
+    Exit(Option<Expr>),
+    Return(Option<Expr>),
+    Break,
+    Continue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Var {
+    Global(usize),
+    Local(usize),
+    /// NF is derived from the split record.
+    Nf,
+}
+
+#[derive(Debug)]
+pub enum LValue {
+    Var(Var),
+    Field(Box<Expr>),
+    Index(Var, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    Concat,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Builtin {
+    Length,
+    Substr,
+    Index,
+    Split,
+    Sub,
+    Gsub,
+    Match,
+    Sprintf,
+    Tolower,
+    Toupper,
+    Int,
+    Sqrt,
+    Exp,
+    Log,
+    Sin,
+    Cos,
+    Atan2,
+    Rand,
+    Srand,
+    Systime,
+    Fflush,
+    Close,
+}
+
+#[derive(Debug)]
+pub enum Expr {
+    Num(f64),
+    Str(String),
+    /// Bare `/re/` matches the record; as a builtin or `~` operand it is the pattern.
+    Regex(Regex),
+    Var(Var),
+    Field(Box<Expr>),
+    Index(Var, Vec<Expr>),
+    /// `(a, b)`: only valid before `in` or as print arguments.
+    Group(Vec<Expr>),
+    Assign(Box<LValue>, Option<BinOp>, Box<Expr>),
+    Cond(Box<Expr>, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Plus(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    /// `lhs ~ rhs`, negated for `!~`.
+    Match(bool, Box<Expr>, Box<Expr>),
+    In(Vec<Expr>, Var),
+    /// Pre or post increment by `delta`.
+    Incr(Box<LValue>, f64, bool),
+    Call(usize, Vec<Expr>),
+    Builtin(Builtin, Vec<Expr>),
+}
+
+pub const G_NR: usize = 0;
+pub const G_FNR: usize = 1;
+pub const G_FS: usize = 2;
+pub const G_OFS: usize = 3;
+pub const G_ORS: usize = 4;
+pub const G_RS: usize = 5;
+pub const G_SUBSEP: usize = 6;
+pub const G_RSTART: usize = 7;
+pub const G_RLENGTH: usize = 8;
+pub const G_FILENAME: usize = 9;
+pub const G_CONVFMT: usize = 10;
+pub const G_OFMT: usize = 11;
+pub const G_ENVIRON: usize = 12;
+
+const SPECIALS: [&str; 13] = [
+    "NR", "FNR", "FS", "OFS", "ORS", "RS", "SUBSEP", "RSTART", "RLENGTH", "FILENAME", "CONVFMT",
+    "OFMT", "ENVIRON",
+];
+// :: ∎
+
+// ▛▞// lexer :: hawk.awk.lang.lex
+// ⫸ [token.regex.division]
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Newline,
+    Eof,
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Semi,
+    Comma,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Caret,
+    Not,
+    Gt,
+    Lt,
+    Pipe,
+    Question,
+    Colon,
+    Tilde,
+    NoMatch,
+    Dollar,
+    Le,
+    Ge,
+    Eq,
+    Ne,
+    Incr,
+    Decr,
+    Assign,
+    OpAssign(BinOp),
+    And,
+    Or,
+    Append,
+    Number(f64),
+    Str(String),
+    Regex(String),
+    Name(String),
+    /// A name directly followed by `(`: a user function call.
+    FuncName(String),
+    Builtin(Builtin),
+    Kw(Kw),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kw {
+    Begin,
+    End,
+    Function,
+    If,
+    Else,
+    While,
+    For,
+    Do,
+    Break,
+    Continue,
+    Next,
+    Exit,
+    Return,
+    Delete,
+    In,
+    Getline,
+    Print,
+    Printf,
+}
+
+fn keyword(s: &str) -> Option<Kw> {
+    Some(match s {
+        "BEGIN" => Kw::Begin,
+        "END" => Kw::End,
+        "function" | "func" => Kw::Function,
+        "if" => Kw::If,
+        "else" => Kw::Else,
+        "while" => Kw::While,
+        "for" => Kw::For,
+        "do" => Kw::Do,
+        "break" => Kw::Break,
+        "continue" => Kw::Continue,
+        "next" => Kw::Next,
+        "exit" => Kw::Exit,
+        "return" => Kw::Return,
+        "delete" => Kw::Delete,
+        "in" => Kw::In,
+        "getline" => Kw::Getline,
+        "print" => Kw::Print,
+        "printf" => Kw::Printf,
+        _ => return None,
+    })
+}
+
+fn builtin(s: &str) -> Option<Builtin> {
+    Some(match s {
+        "length" => Builtin::Length,
+        "substr" => Builtin::Substr,
+        "index" => Builtin::Index,
+        "split" => Builtin::Split,
+        "sub" => Builtin::Sub,
+        "gsub" => Builtin::Gsub,
+        "match" => Builtin::Match,
+        "sprintf" => Builtin::Sprintf,
+        "tolower" => Builtin::Tolower,
+        "toupper" => Builtin::Toupper,
+        "int" => Builtin::Int,
+        "sqrt" => Builtin::Sqrt,
+        "exp" => Builtin::Exp,
+        "log" => Builtin::Log,
+        "sin" => Builtin::Sin,
+        "cos" => Builtin::Cos,
+        "atan2" => Builtin::Atan2,
+        "rand" => Builtin::Rand,
+        "srand" => Builtin::Srand,
+        "systime" => Builtin::Systime,
+        "fflush" => Builtin::Fflush,
+        "close" => Builtin::Close,
+        _ => return None,
+    })
+}
+
+struct Lexer<'a> {
+    src: &'a [u8],
+    pos: usize,
+    line: usize,
+    /// Whether a `/` here would divide (after an operand) or open a regex.
+    after_operand: bool,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src: src.as_bytes(),
+            pos: 0,
+            line: 1,
+            after_operand: false,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, n: usize) -> Option<u8> {
+        self.src.get(self.pos + n).copied()
+    }
+
+    fn err(&self, msg: impl std::fmt::Display) -> String {
+        format!("line {}: {}", self.line, msg)
+    }
+
+    fn tokens(mut self) -> Result<Vec<(Tok, usize)>, String> {
+        let mut out = Vec::new();
+        loop {
+            let t = self.next_tok()?;
+            self.after_operand = matches!(
+                t,
+                Tok::Number(_)
+                    | Tok::Str(_)
+                    | Tok::Name(_)
+                    | Tok::RParen
+                    | Tok::RBracket
+                    | Tok::Incr
+                    | Tok::Decr
+                    | Tok::Builtin(Builtin::Length)
+            );
+            let done = t == Tok::Eof;
+            out.push((t, self.line));
+            if done {
+                return Ok(out);
+            }
+        }
+    }
+
+    fn next_tok(&mut self) -> Result<Tok, String> {
+        // Blanks, comments and backslash-newline continuations.
+        loop {
+            match self.peek() {
+                Some(b' ' | b'\t' | b'\r') => self.pos += 1,
+                Some(b'\\') if matches!(self.peek_at(1), Some(b'\n')) => {
+                    self.pos += 2;
+                    self.line += 1;
+                }
+                Some(b'\\') if self.peek_at(1) == Some(b'\r') && self.peek_at(2) == Some(b'\n') => {
+                    self.pos += 3;
+                    self.line += 1;
+                }
+                Some(b'#') => {
+                    while !matches!(self.peek(), None | Some(b'\n')) {
+                        self.pos += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+
+        let Some(c) = self.peek() else {
+            return Ok(Tok::Eof);
+        };
+        self.pos += 1;
+        let two = |l: &mut Self, next: u8, yes: Tok, no: Tok| {
+            if l.peek() == Some(next) {
+                l.pos += 1;
+                yes
+            } else {
+                no
+            }
+        };
+
+        let tok = match c {
+            b'\n' => {
+                self.line += 1;
+                Tok::Newline
+            }
+            b'{' => Tok::LBrace,
+            b'}' => Tok::RBrace,
+            b'(' => Tok::LParen,
+            b')' => Tok::RParen,
+            b'[' => Tok::LBracket,
+            b']' => Tok::RBracket,
+            b';' => Tok::Semi,
+            b',' => Tok::Comma,
+            b'?' => Tok::Question,
+            b':' => Tok::Colon,
+            b'~' => Tok::Tilde,
+            b'$' => Tok::Dollar,
+            b'+' => match self.peek() {
+                Some(b'+') => {
+                    self.pos += 1;
+                    Tok::Incr
+                }
+                Some(b'=') => {
+                    self.pos += 1;
+                    Tok::OpAssign(BinOp::Add)
+                }
+                _ => Tok::Plus,
+            },
+            b'-' => match self.peek() {
+                Some(b'-') => {
+                    self.pos += 1;
+                    Tok::Decr
+                }
+                Some(b'=') => {
+                    self.pos += 1;
+                    Tok::OpAssign(BinOp::Sub)
+                }
+                _ => Tok::Minus,
+            },
+            b'*' => match (self.peek(), self.peek_at(1)) {
+                (Some(b'*'), Some(b'=')) => {
+                    self.pos += 2;
+                    Tok::OpAssign(BinOp::Pow)
+                }
+                (Some(b'*'), _) => {
+                    self.pos += 1;
+                    Tok::Caret
+                }
+                (Some(b'='), _) => {
+                    self.pos += 1;
+                    Tok::OpAssign(BinOp::Mul)
+                }
+                _ => Tok::Star,
+            },
+            b'%' => two(self, b'=', Tok::OpAssign(BinOp::Mod), Tok::Percent),
+            b'^' => two(self, b'=', Tok::OpAssign(BinOp::Pow), Tok::Caret),
+            b'=' => two(self, b'=', Tok::Eq, Tok::Assign),
+            b'!' => match self.peek() {
+                Some(b'=') => {
+                    self.pos += 1;
+                    Tok::Ne
+                }
+                Some(b'~') => {
+                    self.pos += 1;
+                    Tok::NoMatch
+                }
+                _ => Tok::Not,
+            },
+            b'<' => two(self, b'=', Tok::Le, Tok::Lt),
+            b'>' => match self.peek() {
+                Some(b'=') => {
+                    self.pos += 1;
+                    Tok::Ge
+                }
+                Some(b'>') => {
+                    self.pos += 1;
+                    Tok::Append
+                }
+                _ => Tok::Gt,
+            },
+            b'&' => {
+                if self.peek() != Some(b'&') {
+                    return Err(self.err("unexpected '&'"));
+                }
+                self.pos += 1;
+                Tok::And
+            }
+            b'|' => two(self, b'|', Tok::Or, Tok::Pipe),
+            b'/' if self.after_operand => two(self, b'=', Tok::OpAssign(BinOp::Div), Tok::Slash),
+            b'/' => Tok::Regex(self.regex()?),
+            b'"' => Tok::Str(self.string()?),
+            b'0'..=b'9' | b'.' => {
+                self.pos -= 1;
+                Tok::Number(self.number()?)
+            }
+            c if c == b'_' || c.is_ascii_alphabetic() => {
+                let start = self.pos - 1;
+                while matches!(self.peek(), Some(c) if c == b'_' || c.is_ascii_alphanumeric()) {
+                    self.pos += 1;
+                }
+                let word = std::str::from_utf8(&self.src[start..self.pos])
+                    .unwrap_or_default()
+                    .to_string();
+                if let Some(kw) = keyword(&word) {
+                    Tok::Kw(kw)
+                } else if let Some(b) = builtin(&word) {
+                    Tok::Builtin(b)
+                } else if word == "system" {
+                    return Err(self.err("system() is not allowed"));
+                } else if self.peek() == Some(b'(') {
+                    Tok::FuncName(word)
+                } else {
+                    Tok::Name(word)
+                }
+            }
+            other => return Err(self.err(format!("unexpected character '{}'", other as char))),
+        };
+        Ok(tok)
+    }
+
+    fn number(&mut self) -> Result<f64, String> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(b'0'..=b'9' | b'.')) {
+            self.pos += 1;
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let digits_at = match self.peek_at(1) {
+                Some(b'+' | b'-') => 2,
+                _ => 1,
+            };
+            if matches!(self.peek_at(digits_at), Some(b'0'..=b'9')) {
+                self.pos += digits_at;
+                while matches!(self.peek(), Some(b'0'..=b'9')) {
+                    self.pos += 1;
+                }
+            }
+        }
+        let text = std::str::from_utf8(&self.src[start..self.pos]).unwrap_or_default();
+        text.parse::<f64>()
+            .map_err(|_| self.err(format!("bad number '{}'", text)))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let mut out = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(b'\n') => return Err(self.err("unterminated string")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(String::from_utf8_lossy(&out).into_owned());
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let c = self.peek().ok_or_else(|| self.err("unterminated string"))?;
+                    self.pos += 1;
+                    match c {
+                        b'n' => out.push(b'\n'),
+                        b't' => out.push(b'\t'),
+                        b'r' => out.push(b'\r'),
+                        b'\\' => out.push(b'\\'),
+                        b'"' => out.push(b'"'),
+                        b'/' => out.push(b'/'),
+                        b'a' => out.push(7),
+                        b'b' => out.push(8),
+                        b'f' => out.push(12),
+                        b'v' => out.push(11),
+                        b'\n' => self.line += 1,
+                        b'0'..=b'7' => {
+                            let mut v = (c - b'0') as u32;
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(d @ b'0'..=b'7') => {
+                                        v = v * 8 + (d - b'0') as u32;
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            out.push(v as u8);
+                        }
+                        other => {
+                            out.push(b'\\');
+                            out.push(other);
+                        }
+                    }
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn regex(&mut self) -> Result<String, String> {
+        let mut out = Vec::new();
+        let mut in_class = false;
+        loop {
+            match self.peek() {
+                None | Some(b'\n') => return Err(self.err("unterminated regex")),
+                Some(b'/') if !in_class => {
+                    self.pos += 1;
+                    return Ok(String::from_utf8_lossy(&out).into_owned());
+                }
+                Some(b'\\') if self.peek_at(1) == Some(b'/') => {
+                    out.push(b'/');
+                    self.pos += 2;
+                }
+                Some(b'\\') => {
+                    out.push(b'\\');
+                    self.pos += 1;
+                    if let Some(c) = self.peek() {
+                        out.push(c);
+                        self.pos += 1;
+                    }
+                }
+                Some(c) => {
+                    if c == b'[' && !in_class {
+                        in_class = true;
+                        out.push(c);
+                        self.pos += 1;
+                        // A leading `]` (or `^]`) is literal inside a class.
+                        if self.peek() == Some(b'^') {
+                            out.push(b'^');
+                            self.pos += 1;
+                        }
+                        if self.peek() == Some(b']') {
+                            out.push(b']');
+                            self.pos += 1;
+                        }
+                        continue;
+                    }
+                    if c == b']' && in_class {
+                        in_class = false;
+                    }
+                    out.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Compile an awk ERE. Shared with dynamic regexes at runtime.
+pub fn compile_regex(re: &str) -> Result<Regex, String> {
+    Regex::new(re).map_err(|e| format!("bad regex /{}/: {}", re, e))
+}
+// :: ∎
+
+// ▛▞// parser :: hawk.awk.lang.parse
+// ⫸ [rule.stmt.precedence]
+struct Parser {
+    toks: Vec<(Tok, usize)>,
+    pos: usize,
+    globals: HashMap<String, usize>,
+    global_names: Vec<String>,
+    funcs: HashMap<String, usize>,
+    /// Calls seen before their definition: (name, line).
+    calls: Vec<(String, usize)>,
+    /// Params of the function being parsed.
+    locals: Option<Vec<String>>,
+    ranges: usize,
+    loop_depth: usize,
+    /// Open nesting levels (see `deepen`).
+    depth: usize,
+}
+
+/// Deepest the parser recurses, and so the deepest tree the interpreter
+/// walks. The costliest level (a paren or subscript) takes about 20 KiB of
+/// `STACK_SIZE` in a debug build.
+const MAX_NESTING: usize = 256;
+
+/// Parse and resolve a whole program. Errors carry the source line.
+pub fn compile(src: &str) -> Result<Program, String> {
+    super::with_stack(|| parse(src))
+}
+
+fn parse(src: &str) -> Result<Program, String> {
+    let toks = Lexer::new(src).tokens()?;
+    let mut p = Parser {
+        toks,
+        pos: 0,
+        globals: HashMap::new(),
+        global_names: Vec::new(),
+        funcs: HashMap::new(),
+        calls: Vec::new(),
+        locals: None,
+        ranges: 0,
+        loop_depth: 0,
+        depth: 0,
+    };
+    for name in SPECIALS {
+        p.global(name);
+    }
+
+    // Function slots first so calls resolve before the definition.
+    let mut i = 0;
+    while i + 1 < p.toks.len() {
+        if p.toks[i].0 == Tok::Kw(Kw::Function) {
+            if let Tok::Name(n) | Tok::FuncName(n) = &p.toks[i + 1].0 {
+                if p.funcs.contains_key(n) {
+                    return Err(format!(
+                        "line {}: function {} defined twice",
+                        p.toks[i].1, n
+                    ));
+                }
+                let slot = p.funcs.len();
+                p.funcs.insert(n.clone(), slot);
+            }
+        }
+        i += 1;
+    }
+
+    let mut prog = Program {
+        begin: Vec::new(),
+        rules: Vec::new(),
+        end: Vec::new(),
+        funcs: Vec::new(),
+        globals: Vec::new(),
+    };
+    let mut funcs: Vec<Option<Func>> = (0..p.funcs.len()).map(|_| None).collect();
+
+    p.skip_terms();
+    while p.peek() != &Tok::Eof {
+        match p.peek().clone() {
+            Tok::Kw(Kw::Begin) => {
+                p.advance();
+                prog.begin.extend(p.action()?);
+            }
+            Tok::Kw(Kw::End) => {
+                p.advance();
+                prog.end.extend(p.action()?);
+            }
+            Tok::Kw(Kw::Function) => {
+                let f = p.function()?;
+                let slot = p.funcs[&f.name];
+                funcs[slot] = Some(f);
+            }
+            Tok::LBrace => {
+                let action = p.action()?;
+                prog.rules.push(Rule {
+                    pattern: Pattern::All,
+                    action: Some(action),
+                });
+            }
+            _ => {
+                let start = p.expr(false)?;
+                let pattern = if p.eat(&Tok::Comma) {
+                    p.skip_newlines();
+                    let stop = p.expr(false)?;
+                    p.ranges += 1;
+                    Pattern::Range(start, stop, p.ranges - 1)
+                } else {
+                    Pattern::Expr(start)
+                };
+                let action = if p.peek() == &Tok::LBrace {
+                    Some(p.action()?)
+                } else {
+                    None
+                };
+                prog.rules.push(Rule { pattern, action });
+            }
+        }
+        p.skip_terms();
+    }
+
+    if let Some((name, line)) = p.calls.iter().find(|(n, _)| !p.funcs.contains_key(n)) {
+        return Err(format!(
+            "line {}: call to undefined function {}",
+            line, name
+        ));
+    }
+    prog.funcs = funcs.into_iter().flatten().collect();
+    prog.globals = p.global_names;
+    Ok(prog)
+}
+
+impl Parser {
+    fn peek(&self) -> &Tok {
+        &self.toks[self.pos].0
+    }
+
+    fn line(&self) -> usize {
+        self.toks[self.pos].1
+    }
+
+    fn advance(&mut self) -> Tok {
+        let t = self.toks[self.pos].0.clone();
+        if self.pos + 1 < self.toks.len() {
+            self.pos += 1;
+        }
+        t
+    }
+
+    fn eat(&mut self, t: &Tok) -> bool {
+        if self.peek() == t {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn err<T>(&self, msg: impl std::fmt::Display) -> Result<T, String> {
+        Err(format!("line {}: {}", self.line(), msg))
+    }
+
+    fn expect(&mut self, t: &Tok) -> Result<(), String> {
+        if self.eat(t) {
+            Ok(())
+        } else {
+            self.err(format!("expected {:?}, got {:?}", t, self.peek()))
+        }
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.peek() == &Tok::Newline {
+            self.advance();
+        }
+    }
+
+    fn skip_terms(&mut self) {
+        while matches!(self.peek(), Tok::Newline | Tok::Semi) {
+            self.advance();
+        }
+    }
+
+    fn global(&mut self, name: &str) -> usize {
+        if let Some(&g) = self.globals.get(name) {
+            return g;
+        }
+        let g = self.global_names.len();
+        self.globals.insert(name.to_string(), g);
+        self.global_names.push(name.to_string());
+        g
+    }
+
+    fn var(&mut self, name: &str) -> Result<Var, String> {
+        if self.funcs.contains_key(name) {
+            return self.err(format!("function {} used as a variable", name));
+        }
+        if let Some(i) = self
+            .locals
+            .as_ref()
+            .and_then(|l| l.iter().position(|p| p == name))
+        {
+            return Ok(Var::Local(i));
+        }
+        if name == "NF" {
+            return Ok(Var::Nf);
+        }
+        Ok(Var::Global(self.global(name)))
+    }
+
+    fn function(&mut self) -> Result<Func, String> {
+        self.advance();
+        let name = match self.advance() {
+            Tok::Name(n) | Tok::FuncName(n) => n,
+            other => return self.err(format!("expected function name, got {:?}", other)),
+        };
+        self.expect(&Tok::LParen)?;
+        let mut params = Vec::new();
+        while self.peek() != &Tok::RParen {
+            self.skip_newlines();
+            match self.advance() {
+                Tok::Name(p) => params.push(p),
+                other => return self.err(format!("expected parameter name, got {:?}", other)),
+            }
+            self.skip_newlines();
+            if !self.eat(&Tok::Comma) {
+                break;
+            }
+        }
+        self.expect(&Tok::RParen)?;
+        self.skip_newlines();
+
+        let n = params.len();
+        self.locals = Some(params);
+        let body = self.action();
+        self.locals = None;
+        Ok(Func {
+            name,
+            params: n,
+            body: body?,
+        })
+    }
+
+    /// One more level of nesting. Parse errors end the compile, so only
+    /// the success paths need to give the level back.
+    fn deepen(&mut self) -> Result<(), String> {
+        if self.depth >= MAX_NESTING {
+            return self.err(format!("nested more than {} levels deep", MAX_NESTING));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, String>) -> Result<T, String> {
+        self.deepen()?;
+        let r = f(self);
+        self.depth -= 1;
+        r
+    }
+
+    fn action(&mut self) -> Result<Vec<Stmt>, String> {
+        self.skip_newlines();
+        self.expect(&Tok::LBrace)?;
+        let mut body = Vec::new();
+        loop {
+            self.skip_terms();
+            if self.eat(&Tok::RBrace) {
+                return Ok(body);
+            }
+            if self.peek() == &Tok::Eof {
+                return self.err("missing '}'");
+            }
+            body.push(self.nested(Self::stmt)?);
+        }
+    }
+
+    /// Statement end: `;`, newline, or a following `}`.
+    fn end_simple(&mut self) -> Result<(), String> {
+        match self.peek() {
+            Tok::Semi | Tok::Newline => {
+                self.advance();
+                self.skip_newlines();
+                Ok(())
+            }
+            Tok::RBrace | Tok::Eof => Ok(()),
+            Tok::Pipe => self.err("pipes to or from commands are not allowed"),
+            other => self.err(format!("unexpected {:?}", other)),
+        }
+    }
+
+    /// Body of if/while/for: a statement, possibly on the next line.
+    fn body(&mut self) -> Result<Box<Stmt>, String> {
+        self.skip_newlines();
+        if self.eat(&Tok::Semi) {
+            return Ok(Box::new(Stmt::Block(Vec::new())));
+        }
+        Ok(Box::new(self.nested(Self::stmt)?))
+    }
+
+    fn loop_body(&mut self) -> Result<Box<Stmt>, String> {
+        self.loop_depth += 1;
+        let body = self.body();
+        self.loop_depth -= 1;
+        body
+    }
+
+    fn stmt(&mut self) -> Result<Stmt, String> {
+        let stmt = match self.peek().clone() {
+            Tok::LBrace => {
+                let b = self.action()?;
+                self.skip_terms_opt();
+                return Ok(Stmt::Block(b));
+            }
+            Tok::Kw(Kw::If) => {
+                self.advance();
+                self.expect(&Tok::LParen)?;
+                let cond = self.expr(false)?;
+                self.expect(&Tok::RParen)?;
+                let then = self.body()?;
+                // `else` may follow a terminator or newlines.
+                let save = self.pos;
+                self.skip_terms();
+                let els = if self.eat(&Tok::Kw(Kw::Else)) {
+                    Some(self.body()?)
+                } else {
+                    self.pos = save;
+                    None
+                };
+                return Ok(Stmt::If(cond, then, els));
+            }
+            Tok::Kw(Kw::While) => {
+                self.advance();
+                self.expect(&Tok::LParen)?;
+                let cond = self.expr(false)?;
+                self.expect(&Tok::RParen)?;
+                if self.peek() == &Tok::Semi {
+                    self.advance();
+                    return Ok(Stmt::While(cond, Box::new(Stmt::Block(Vec::new()))));
+                }
+                return Ok(Stmt::While(cond, self.loop_body()?));
+            }
+            Tok::Kw(Kw::Do) => {
+                self.advance();
+                let body = self.loop_body()?;
+                self.skip_terms();
+                self.expect(&Tok::Kw(Kw::While))?;
+                self.expect(&Tok::LParen)?;
+                let cond = self.expr(false)?;
+                self.expect(&Tok::RParen)?;
+                Stmt::Do(body, cond)
+            }
+            Tok::Kw(Kw::For) => return self.for_stmt(),
+            Tok::Semi => {
+                self.advance();
+                return Ok(Stmt::Block(Vec::new()));
+            }
+            _ => self.simple_stmt()?,
+        };
+        self.end_simple()?;
+        Ok(stmt)
+    }
+
+    fn skip_terms_opt(&mut self) {
+        if matches!(self.peek(), Tok::Semi | Tok::Newline) {
+            self.skip_terms();
+        }
+    }
+
+    fn for_stmt(&mut self) -> Result<Stmt, String> {
+        self.advance();
+        self.expect(&Tok::LParen)?;
+
+        // `for (k in arr)`
+        let ahead = |n: usize| self.toks.get(self.pos + n).map(|t| &t.0);
+        if let (Some(Tok::Name(k)), Some(Tok::Kw(Kw::In)), Some(Tok::Name(a)), Some(Tok::RParen)) =
+            (ahead(0), ahead(1), ahead(2), ahead(3))
+        {
+            let (k, a) = (k.clone(), a.clone());
+            self.pos += 4;
+            let key = self.var(&k)?;
+            let arr = self.var(&a)?;
+            return Ok(Stmt::ForIn(key, arr, self.loop_body()?));
+        }
+
+        let init = if self.peek() == &Tok::Semi {
+            None
+        } else {
+            Some(Box::new(self.simple_stmt()?))
+        };
+        self.expect(&Tok::Semi)?;
+        self.skip_newlines();
+        let cond = if self.peek() == &Tok::Semi {
+            None
+        } else {
+            Some(self.expr(false)?)
+        };
+        self.expect(&Tok::Semi)?;
+        self.skip_newlines();
+        let step = if self.peek() == &Tok::RParen {
+            None
+        } else {
+            Some(Box::new(self.simple_stmt()?))
+        };
+        self.expect(&Tok::RParen)?;
+        if self.peek() == &Tok::Semi {
+            self.advance();
+            return Ok(Stmt::For(
+                init,
+                cond,
+                step,
+                Box::new(Stmt::Block(Vec::new())),
+            ));
+        }
+        Ok(Stmt::For(init, cond, step, self.loop_body()?))
+    }
+
+    fn simple_stmt(&mut self) -> Result<Stmt, String> {
+        Ok(match self.peek().clone() {
+            Tok::Kw(Kw::Print) | Tok::Kw(Kw::Printf) => {
+                let printf = self.advance() == Tok::Kw(Kw::Printf);
+                let mut args = Vec::new();
+                if !matches!(
+                    self.peek(),
+                    Tok::Semi
+                        | Tok::Newline
+                        | Tok::RBrace
+                        | Tok::Gt
+                        | Tok::Append
+                        | Tok::Pipe
+                        | Tok::Eof
+                ) {
+                    args = self.expr_list(true)?;
+                }
+                if let [Expr::Group(_)] = args.as_slice() {
+                    if let Some(Expr::Group(list)) = args.pop() {
+                        args = list;
+                    }
+                }
+                if printf && args.is_empty() {
+                    return self.err("printf needs a format");
+                }
+                let out = self.redirect()?;
+                if printf {
+                    Stmt::Printf(args, out)
+                } else {
+                    Stmt::Print(args, out)
+                }
+            }
+            Tok::Kw(Kw::Next) => {
+                self.advance();
+                Stmt::Next
+            }
+            Tok::Kw(Kw::Exit) => {
+                self.advance();
+                let code = if self.at_simple_end() {
+                    None
+                } else {
+                    Some(self.expr(false)?)
+                };
+                Stmt::Exit(code)
+            }
+            Tok::Kw(Kw::Return) => {
+                self.advance();
+                if self.locals.is_none() {
+                    return self.err("return outside a function");
+                }
+                let v = if self.at_simple_end() {
+                    None
+                } else {
+                    Some(self.expr(false)?)
+                };
+                Stmt::Return(v)
+            }
+            Tok::Kw(Kw::Break) => {
+                self.advance();
+                if self.loop_depth == 0 {
+                    return self.err("break outside a loop");
+                }
+                Stmt::Break
+            }
+            Tok::Kw(Kw::Continue) => {
+                self.advance();
+                if self.loop_depth == 0 {
+                    return self.err("continue outside a loop");
+                }
+                Stmt::Continue
+            }
+            Tok::Kw(Kw::Delete) => {
+                self.advance();
+                let name = match self.advance() {
+                    Tok::Name(n) => n,
+                    other => return self.err(format!("delete expects an array, got {:?}", other)),
+                };
+                let arr = self.var(&name)?;
+                let subs = if self.eat(&Tok::LBracket) {
+                    let s = self.expr_list(false)?;
+                    self.expect(&Tok::RBracket)?;
+                    Some(s)
+                } else {
+                    None
+                };
+                Stmt::Delete(arr, subs)
+            }
+            _ => Stmt::Expr(self.expr(false)?),
+        })
+    }
+
+    fn at_simple_end(&self) -> bool {
+        matches!(
+            self.peek(),
+            Tok::Semi | Tok::Newline | Tok::RBrace | Tok::Eof
+        )
+    }
+
+    /// Only stderr is writable: `> "/dev/stderr"` or `> "/dev/fd/2"`.
+    fn redirect(&mut self) -> Result<Output, String> {
+        match self.peek() {
+            Tok::Pipe => self.err("piping output to a command is not allowed"),
+            Tok::Gt | Tok::Append => {
+                self.advance();
+                match self.advance() {
+                    Tok::Str(s) if s == "/dev/stderr" || s == "/dev/fd/2" => Ok(Output::Stderr),
+                    Tok::Str(s) if s == "/dev/stdout" || s == "/dev/fd/1" || s == "-" => {
+                        Ok(Output::Stdout)
+                    }
+                    _ => self.err("file redirection is not allowed (only /dev/stderr)"),
+                }
+            }
+            _ => Ok(Output::Stdout),
+        }
+    }
+
+    fn expr_list(&mut self, no_gt: bool) -> Result<Vec<Expr>, String> {
+        let mut list = vec![self.expr(no_gt)?];
+        while self.eat(&Tok::Comma) {
+            self.skip_newlines();
+            list.push(self.expr(no_gt)?);
+        }
+        Ok(list)
+    }
+
+    // Precedence, lowest first: assignment, ?:, ||, &&, in, ~ !~,
+    // comparison, concatenation, + -, * / %, unary, ^, ++ --, $.
+
+    fn expr(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let lhs = self.ternary(no_gt)?;
+        let op = match self.peek() {
+            Tok::Assign => None,
+            Tok::OpAssign(op) => Some(*op),
+            _ => return Ok(lhs),
+        };
+        let Some(lv) = to_lvalue(lhs) else {
+            return self.err("assignment to a non-variable");
+        };
+        self.advance();
+        self.skip_newlines();
+        let rhs = self.nested(|p| p.expr(no_gt))?;
+        Ok(Expr::Assign(Box::new(lv), op, Box::new(rhs)))
+    }
+
+    fn ternary(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let cond = self.or(no_gt)?;
+        if !self.eat(&Tok::Question) {
+            return Ok(cond);
+        }
+        self.skip_newlines();
+        let a = self.nested(|p| p.expr(no_gt))?;
+        self.skip_newlines();
+        self.expect(&Tok::Colon)?;
+        self.skip_newlines();
+        let b = self.nested(|p| p.expr(no_gt))?;
+        Ok(Expr::Cond(Box::new(cond), Box::new(a), Box::new(b)))
+    }
+
+    fn or(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let base = self.depth;
+        let mut lhs = self.and(no_gt)?;
+        while self.eat(&Tok::Or) {
+            self.deepen()?;
+            self.skip_newlines();
+            let rhs = self.and(no_gt)?;
+            lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        self.depth = base;
+        Ok(lhs)
+    }
+
+    fn and(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let base = self.depth;
+        let mut lhs = self.in_expr(no_gt)?;
+        while self.eat(&Tok::And) {
+            self.deepen()?;
+            self.skip_newlines();
+            let rhs = self.in_expr(no_gt)?;
+            lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+        }
+        self.depth = base;
+        Ok(lhs)
+    }
+
+    fn in_expr(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let base = self.depth;
+        let mut lhs = self.matching(no_gt)?;
+        while self.eat(&Tok::Kw(Kw::In)) {
+            self.deepen()?;
+            let name = match self.advance() {
+                Tok::Name(n) => n,
+                other => return self.err(format!("'in' expects an array, got {:?}", other)),
+            };
+            let arr = self.var(&name)?;
+            let keys = match lhs {
+                Expr::Group(list) => list,
+                e => vec![e],
+            };
+            lhs = Expr::In(keys, arr);
+        }
+        self.depth = base;
+        Ok(lhs)
+    }
+
+    fn matching(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let base = self.depth;
+        let mut lhs = self.comparison(no_gt)?;
+        loop {
+            let neg = match self.peek() {
+                Tok::Tilde => false,
+                Tok::NoMatch => true,
+                _ => break,
+            };
+            self.advance();
+            self.deepen()?;
+            let rhs = self.comparison(no_gt)?;
+            lhs = Expr::Match(neg, Box::new(lhs), Box::new(rhs));
+        }
+        self.depth = base;
+        Ok(lhs)
+    }
+
+    fn comparison(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let lhs = self.concat(no_gt)?;
+        let op = match self.peek() {
+            Tok::Lt => BinOp::Lt,
+            Tok::Le => BinOp::Le,
+            Tok::Gt if !no_gt => BinOp::Gt,
+            Tok::Ge => BinOp::Ge,
+            Tok::Eq => BinOp::Eq,
+            Tok::Ne => BinOp::Ne,
+            _ => return Ok(lhs),
+        };
+        self.advance();
+        let rhs = self.concat(no_gt)?;
+        Ok(Expr::Binary(op, Box::new(lhs), Box::new(rhs)))
+    }
+
+    fn starts_operand(&self) -> bool {
+        matches!(
+            self.peek(),
+            Tok::Number(_)
+                | Tok::Str(_)
+                | Tok::Regex(_)
+                | Tok::Name(_)
+                | Tok::FuncName(_)
+                | Tok::Builtin(_)
+                | Tok::Dollar
+                | Tok::LParen
+                | Tok::Incr
+                | Tok::Decr
+        )
+    }
+
+    fn concat(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let base = self.depth;
+        let mut lhs = self.additive(no_gt)?;
+        // `a -1` is subtraction, so a concatenated operand never starts
+        // with a sign or `!`.
+        while self.starts_operand() {
+            self.deepen()?;
+            let rhs = self.additive(no_gt)?;
+            lhs = Expr::Binary(BinOp::Concat, Box::new(lhs), Box::new(rhs));
+        }
+        self.depth = base;
+        Ok(lhs)
+    }
+
+    fn additive(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let base = self.depth;
+        let mut lhs = self.multiplicative(no_gt)?;
+        loop {
+            let op = match self.peek() {
+                Tok::Plus => BinOp::Add,
+                Tok::Minus => BinOp::Sub,
+                _ => break,
+            };
+            self.advance();
+            self.deepen()?;
+            let rhs = self.multiplicative(no_gt)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        self.depth = base;
+        Ok(lhs)
+    }
+
+    fn multiplicative(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let base = self.depth;
+        let mut lhs = self.unary(no_gt)?;
+        loop {
+            let op = match self.peek() {
+                Tok::Star => BinOp::Mul,
+                Tok::Slash => BinOp::Div,
+                Tok::Percent => BinOp::Mod,
+                _ => break,
+            };
+            self.advance();
+            self.deepen()?;
+            let rhs = self.unary(no_gt)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        self.depth = base;
+        Ok(lhs)
+    }
+
+    fn unary(&mut self, no_gt: bool) -> Result<Expr, String> {
+        match self.peek() {
+            Tok::Not => {
+                self.advance();
+                Ok(Expr::Not(Box::new(self.nested(|p| p.unary(no_gt))?)))
+            }
+            Tok::Minus => {
+                self.advance();
+                Ok(Expr::Neg(Box::new(self.nested(|p| p.unary(no_gt))?)))
+            }
+            Tok::Plus => {
+                self.advance();
+                Ok(Expr::Plus(Box::new(self.nested(|p| p.unary(no_gt))?)))
+            }
+            _ => self.power(no_gt),
+        }
+    }
+
+    fn power(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let base = self.postfix(no_gt)?;
+        if !self.eat(&Tok::Caret) {
+            return Ok(base);
+        }
+        // Right associative, and `2^-1` is fine.
+        let exp = match self.peek() {
+            Tok::Minus | Tok::Plus | Tok::Not => self.nested(|p| p.unary(no_gt))?,
+            _ => self.nested(|p| p.power(no_gt))?,
+        };
+        Ok(Expr::Binary(BinOp::Pow, Box::new(base), Box::new(exp)))
+    }
+
+    fn postfix(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let e = self.nested(|p| p.primary(no_gt))?;
+        let delta = match self.peek() {
+            Tok::Incr => 1.0,
+            Tok::Decr => -1.0,
+            _ => return Ok(e),
+        };
+        if !is_lvalue(&e) {
+            return Ok(e);
+        }
+        self.advance();
+        let lv = to_lvalue(e).expect("checked lvalue");
+        Ok(Expr::Incr(Box::new(lv), delta, false))
+    }
+
+    fn primary(&mut self, no_gt: bool) -> Result<Expr, String> {
+        let line = self.line();
+        match self.advance() {
+            Tok::Number(n) => Ok(Expr::Num(n)),
+            Tok::Str(s) => Ok(Expr::Str(s)),
+            Tok::Regex(r) => Ok(Expr::Regex(
+                compile_regex(&r).map_err(|e| format!("line {}: {}", line, e))?,
+            )),
+            Tok::Dollar => {
+                let e = match self.peek() {
+                    Tok::Incr | Tok::Decr | Tok::Minus => self.nested(|p| p.unary(no_gt))?,
+                    _ => self.nested(|p| p.primary(no_gt))?,
+                };
+                Ok(Expr::Field(Box::new(e)))
+            }
+            Tok::LParen => {
+                // Inside parens `>` compares again.
+                self.skip_newlines();
+                let first = self.expr(false)?;
+                if self.eat(&Tok::Comma) {
+                    let mut list = vec![first];
+                    loop {
+                        self.skip_newlines();
+                        list.push(self.expr(false)?);
+                        if !self.eat(&Tok::Comma) {
+                            break;
+                        }
+                    }
+                    self.skip_newlines();
+                    self.expect(&Tok::RParen)?;
+                    return Ok(Expr::Group(list));
+                }
+                self.skip_newlines();
+                self.expect(&Tok::RParen)?;
+                Ok(first)
+            }
+            t @ (Tok::Incr | Tok::Decr) => {
+                let delta = if t == Tok::Incr { 1.0 } else { -1.0 };
+                let e = self.nested(|p| p.primary(no_gt))?;
+                match to_lvalue(e) {
+                    Some(lv) => Ok(Expr::Incr(Box::new(lv), delta, true)),
+                    None => self.err("++ or -- needs a variable"),
+                }
+            }
+            Tok::Minus => Ok(Expr::Neg(Box::new(self.nested(|p| p.unary(no_gt))?))),
+            Tok::Plus => Ok(Expr::Plus(Box::new(self.nested(|p| p.unary(no_gt))?))),
+            Tok::Not => Ok(Expr::Not(Box::new(self.nested(|p| p.unary(no_gt))?))),
+            Tok::Name(n) => {
+                let v = self.var(&n)?;
+                if self.eat(&Tok::LBracket) {
+                    let subs = self.expr_list(false)?;
+                    self.expect(&Tok::RBracket)?;
+                    Ok(Expr::Index(v, subs))
+                } else {
+                    Ok(Expr::Var(v))
+                }
+            }
+            Tok::FuncName(n) => {
+                self.expect(&Tok::LParen)?;
+                let args = self.call_args()?;
+                let Some(&slot) = self.funcs.get(&n) else {
+                    return Err(format!("line {}: call to undefined function {}", line, n));
+                };
+                self.calls.push((n, line));
+                Ok(Expr::Call(slot, args))
+            }
+            Tok::Builtin(b) => {
+                let args = if self.eat(&Tok::LParen) {
+                    self.call_args()?
+                } else if b == Builtin::Length {
+                    Vec::new()
+                } else {
+                    return self.err(format!("{:?} needs arguments", b).to_lowercase());
+                };
+                check_arity(b, args.len()).map_err(|e| format!("line {}: {}", line, e))?;
+                if matches!(b, Builtin::Sub | Builtin::Gsub)
+                    && args.len() == 3
+                    && !is_lvalue(&args[2])
+                {
+                    return self.err("sub/gsub target must be a variable");
+                }
+                if b == Builtin::Split && !matches!(args.get(1), Some(Expr::Var(_))) {
+                    return self.err("split needs an array name");
+                }
+                Ok(Expr::Builtin(b, args))
+            }
+            Tok::Kw(Kw::Getline) => self.err("getline is not supported"),
+            other => Err(format!("line {}: unexpected {:?}", line, other)),
+        }
+    }
+
+    fn call_args(&mut self) -> Result<Vec<Expr>, String> {
+        self.skip_newlines();
+        if self.eat(&Tok::RParen) {
+            return Ok(Vec::new());
+        }
+        let args = self.expr_list(false)?;
+        self.skip_newlines();
+        self.expect(&Tok::RParen)?;
+        Ok(args)
+    }
+}
+
+fn is_lvalue(e: &Expr) -> bool {
+    matches!(e, Expr::Var(_) | Expr::Field(_) | Expr::Index(..))
+}
+
+fn to_lvalue(e: Expr) -> Option<LValue> {
+    match e {
+        Expr::Var(v) => Some(LValue::Var(v)),
+        Expr::Field(i) => Some(LValue::Field(i)),
+        Expr::Index(v, subs) => Some(LValue::Index(v, subs)),
+        _ => None,
+    }
+}
+
+fn check_arity(b: Builtin, n: usize) -> Result<(), String> {
+    let (min, max) = match b {
+        Builtin::Length => (0, 1),
+        Builtin::Substr => (2, 3),
+        Builtin::Index => (2, 2),
+        Builtin::Split => (2, 3),
+        Builtin::Sub | Builtin::Gsub => (2, 3),
+        Builtin::Match => (2, 2),
+        Builtin::Sprintf => (1, usize::MAX),
+        Builtin::Tolower | Builtin::Toupper => (1, 1),
+        Builtin::Int
+        | Builtin::Sqrt
+        | Builtin::Exp
+        | Builtin::Log
+        | Builtin::Sin
+        | Builtin::Cos => (1, 1),
+        Builtin::Atan2 => (2, 2),
+        Builtin::Rand | Builtin::Systime => (0, 0),
+        Builtin::Srand | Builtin::Fflush => (0, 1),
+        Builtin::Close => (1, 1),
+    };
+    if n < min || n > max {
+        return Err(format!(
+            "{} takes {} argument(s), got {}",
+            format!("{:?}", b).to_lowercase(),
+            if min == max {
+                min.to_string()
+            } else if max == usize::MAX {
+                format!("{}+", min)
+            } else {
+                format!("{}-{}", min, max)
+            },
+            n
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn err(src: &str) -> String {
+        match compile(src) {
+            Ok(_) => panic!("compiled: {}", src),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn resolves_globals_functions_and_ranges() {
+        let prog = compile(
+            r#"
+            function add(a, b) { return a + b }
+            BEGIN { total = add(1, 2) }
+            /start/, /stop/ { seen[$1]++ }
+            $5 == "fail"
+            END { print total }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(prog.begin.len(), 1);
+        assert_eq!(prog.end.len(), 1);
+        assert_eq!(prog.funcs.len(), 1);
+        assert_eq!(prog.funcs[0].params, 2);
+        assert!(matches!(prog.rules[0].pattern, Pattern::Range(_, _, 0)));
+        assert!(prog.rules[1].action.is_none());
+        assert!(prog.globals.iter().any(|g| g == "total"));
+        assert!(prog.globals.iter().any(|g| g == "seen"));
+    }
+
+    #[test]
+    fn syntax_errors_carry_the_line() {
+        for (src, want) in [
+            ("BEGIN {\n  x = 1\n", "missing '}'"),
+            ("BEGIN {\n  f(1)\n}", "line 2: call to undefined function f"),
+            ("function f() {}\nfunction f() {}", "line 2: function f defined twice"),
+            ("BEGIN {\n  return 1\n}", "line 2: return outside a function"),
+            ("BEGIN { break }", "break outside a loop"),
+            ("BEGIN { continue }", "continue outside a loop"),
+            ("BEGIN { 1 = 2 }", "assignment to a non-variable"),
+            ("BEGIN { x = substr() }", "substr takes 2-3 argument(s), got 0"),
+            ("BEGIN { split(s, a[1]) }", "split needs an array name"),
+            ("BEGIN { sub(/x/, \"y\", 1) }", "sub/gsub target must be a variable"),
+            ("BEGIN { printf }", "printf needs a format"),
+        ] {
+            let e = err(src);
+            assert!(e.contains(want), "{:?}: {}", src, e);
+        }
+    }
+
+    #[test]
+    fn nesting_is_limited_not_a_stack_overflow() {
+        let deep = 20_000;
+        for src in [
+            format!("BEGIN {{ x = {}1{} }}", "(".repeat(deep), ")".repeat(deep)),
+            format!("BEGIN {{ x = {}1 }}", "- ".repeat(deep)),
+            format!("BEGIN {{ x = {}1 }}", "$".repeat(deep)),
+            format!("BEGIN {{ x = {}1 }}", "2^".repeat(deep)),
+            format!("BEGIN {{ x = 1{} }}", " + 1".repeat(deep)),
+            format!("BEGIN {{ x = 1{} }}", " \"a\"".repeat(deep)),
+            format!("BEGIN {{ {}x = 1 }}", "y = ".repeat(deep)),
+            format!("BEGIN {{ x = {}1 }}", "1 ? 2 : ".repeat(deep)),
+            format!("BEGIN {{ x = {}1{} }}", "a[".repeat(deep), "]".repeat(deep)),
+            format!("BEGIN {}{}", "{".repeat(deep), "}".repeat(deep)),
+            format!("BEGIN {{ {}x = 1 }}", "if (1) ".repeat(deep)),
+        ] {
+            let e = err(&src);
+            assert!(e.contains("nested more than 256 levels deep"), "{}: {}", &src[..20], e);
+        }
+
+        let fine = format!("BEGIN {{ x = {}1{} }}", "(".repeat(50), ")".repeat(50));
+        assert!(compile(&fine).is_ok());
+    }
+}
+// :: ∎
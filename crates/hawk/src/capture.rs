@@ -0,0 +1,463 @@
+// ▛▞// hawk capture :: hawk.capture
+// @ctx ⫸ [record.replay.gzip]
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hawk_core::{HawkFrame, HawkFrameParseError, RejectedLine};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::clock::Clock;
+use crate::io::IngestMsg;
+
+// :: ∎
+
+// ▛▞// capture format :: hawk.capture.format
+// @ctx ⫸ [header.offset.tag.payload]
+// Gzip'd text. First line is a header, then one record per IngestMsg:
+//   # hawk capture v1 started=<rfc3339>
+//   <arrival_ms>\tF\t<7-column HawkFrame TSV>
+//   <arrival_ms>\tP\t<reason>\t<source>\t<line_no>\t<offset>\t<raw>
+//   <arrival_ms>\tI\t<io error>
+//   <arrival_ms>\tD\t<7-column HawkFrame TSV, derived panel>
+// arrival_ms counts from `started`. Frames without ts are stamped with
+// their arrival time so replay sees the same timestamps.
+const HEADER_PREFIX: &str = "# hawk capture v1 started=";
+
+#[derive(Debug, Clone)]
+pub struct Record {
+    pub at_ms: u64,
+    pub msg: RecordMsg,
+}
+
+#[derive(Debug, Clone)]
+pub enum RecordMsg {
+    Frame(HawkFrame),
+    ParseError(RejectedLine),
+    IoError(String),
+    Derived(HawkFrame),
+}
+
+impl RecordMsg {
+    fn to_ingest(&self) -> IngestMsg {
+        match self {
+            RecordMsg::Frame(f) => IngestMsg::Frame(f.clone()),
+            RecordMsg::ParseError(e) => IngestMsg::ParseError(e.clone()),
+            RecordMsg::IoError(s) => IngestMsg::IoError(s.clone()),
+            RecordMsg::Derived(f) => IngestMsg::Derived(f.clone()),
+        }
+    }
+}
+
+fn encode_parse_error(e: &HawkFrameParseError) -> String {
+    match e {
+        HawkFrameParseError::Empty => "empty".to_string(),
+        HawkFrameParseError::TooFewColumns { found } => format!("too_few_columns {}", found),
+        HawkFrameParseError::TooManyColumns { found } => format!("too_many_columns {}", found),
+        HawkFrameParseError::BadTimestamp { raw } => format!("bad_timestamp {}", raw),
+    }
+}
+
+fn encode_rejected(r: &RejectedLine) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}",
+        encode_parse_error(&r.error),
+        r.source,
+        r.line_no,
+        r.offset,
+        r.raw
+    )
+}
+
+fn decode_rejected(s: &str) -> Option<RejectedLine> {
+    let mut parts = s.splitn(5, '\t');
+    let error = decode_parse_error(parts.next()?)?;
+    let source = parts.next().unwrap_or_default();
+    let line_no = parts.next().and_then(|v| v.parse().ok()).unwrap_or(0);
+    let offset = parts.next().and_then(|v| v.parse().ok()).unwrap_or(0);
+    let raw = parts.next().unwrap_or_default();
+    Some(RejectedLine::new(source, line_no, offset, raw, error))
+}
+
+fn decode_parse_error(s: &str) -> Option<HawkFrameParseError> {
+    let (tag, rest) = s.split_once(' ').unwrap_or((s, ""));
+    match tag {
+        "empty" => Some(HawkFrameParseError::Empty),
+        "too_few_columns" => Some(HawkFrameParseError::TooFewColumns {
+            found: rest.parse().ok()?,
+        }),
+        "too_many_columns" => Some(HawkFrameParseError::TooManyColumns {
+            found: rest.parse().ok()?,
+        }),
+        "bad_timestamp" => Some(HawkFrameParseError::BadTimestamp {
+            raw: rest.to_string(),
+        }),
+        _ => None,
+    }
+}
+// :: ∎
+
+// ▛▞// recorder :: hawk.capture.record
+// ⫸ [tee.stamp.flush]
+pub struct Recorder {
+    out: GzEncoder<BufWriter<File>>,
+    started: Instant,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> anyhow::Result<Self> {
+        let file = File::create(path).with_context(|| format!("create capture {:?}", path))?;
+        let mut out = GzEncoder::new(BufWriter::new(file), Compression::default());
+
+        let started = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_default();
+        writeln!(out, "{}{}", HEADER_PREFIX, started)?;
+
+        Ok(Self {
+            out,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn write(&mut self, msg: &IngestMsg) -> std::io::Result<()> {
+        let at_ms = self.started.elapsed().as_millis() as u64;
+        let (tag, payload) = match msg {
+            IngestMsg::Frame(f) => ("F", f.to_tsv_line(OffsetDateTime::now_utc())),
+            IngestMsg::ParseError(r) => ("P", encode_rejected(r)),
+            IngestMsg::IoError(s) => ("I", s.replace(['\n', '\r'], " ")),
+            IngestMsg::Derived(f) => ("D", f.to_tsv_line(OffsetDateTime::now_utc())),
+            // Control messages are not part of what the operator saw, and
+            // repaired frames are recorded as the frame itself.
+            IngestMsg::ReplayDone | IngestMsg::Reset | IngestMsg::Repaired(_) => return Ok(()),
+        };
+        writeln!(self.out, "{}\t{}\t{}", at_ms, tag, payload)?;
+        // Sync flush keeps a killed recording readable up to the last record.
+        self.out.flush()
+    }
+
+    pub fn finish(self) -> std::io::Result<()> {
+        self.out.finish()?.flush()
+    }
+}
+
+/// Forward `rx` to a new channel, writing every message to `rec` on the way.
+/// Take the recorder out of the mutex and `finish` it to close the capture.
+pub fn spawn_tee(
+    rx: Receiver<IngestMsg>,
+    rec: Arc<Mutex<Option<Recorder>>>,
+) -> Receiver<IngestMsg> {
+    let (tx, out) = std::sync::mpsc::channel::<IngestMsg>();
+    std::thread::spawn(move || {
+        for msg in rx {
+            if let Ok(mut lock) = rec.lock() {
+                if let Some(r) = lock.as_mut() {
+                    if let Err(e) = r.write(&msg) {
+                        let _ = tx.send(IngestMsg::IoError(format!("record: {}", e)));
+                        *lock = None;
+                    }
+                }
+            }
+            if tx.send(msg).is_err() {
+                break;
+            }
+        }
+    });
+    out
+}
+// :: ∎
+
+// ▛▞// reader :: hawk.capture.read
+// ⫸ [gunzip.parse.truncated]
+pub struct Capture {
+    pub started: OffsetDateTime,
+    pub records: Vec<Record>,
+}
+
+impl Capture {
+    pub fn total_ms(&self) -> u64 {
+        self.records.last().map(|r| r.at_ms).unwrap_or(0)
+    }
+}
+
+pub fn read_capture(path: &Path) -> anyhow::Result<Capture> {
+    let file = File::open(path).with_context(|| format!("open capture {:?}", path))?;
+    let reader = BufReader::new(MultiGzDecoder::new(file));
+
+    let mut started: Option<OffsetDateTime> = None;
+    let mut records = Vec::new();
+
+    for (n, line) in reader.lines().enumerate() {
+        let line = match line {
+            Ok(v) => v,
+            // A recording cut short (kill, crash) ends without a gzip trailer.
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e).with_context(|| format!("read capture {:?}", path)),
+        };
+
+        if n == 0 {
+            let raw = line
+                .strip_prefix(HEADER_PREFIX)
+                .ok_or_else(|| anyhow::anyhow!("{:?} is not a hawk capture", path))?;
+            started = Some(
+                OffsetDateTime::parse(raw.trim(), &Rfc3339)
+                    .with_context(|| format!("capture start time {:?}", raw))?,
+            );
+            continue;
+        }
+
+        let mut parts = line.splitn(3, '\t');
+        let (Some(at), Some(tag), Some(payload)) = (parts.next(), parts.next(), parts.next())
+        else {
+            anyhow::bail!("capture line {}: expected ms, tag, payload", n + 1);
+        };
+        let at_ms: u64 = at
+            .parse()
+            .map_err(|_| anyhow::anyhow!("capture line {}: bad offset {}", n + 1, at))?;
+
+        let msg = match tag {
+            "F" => match HawkFrame::parse_tsv_line(payload) {
+                Ok(Some(f)) => RecordMsg::Frame(f),
+                _ => anyhow::bail!("capture line {}: bad frame", n + 1),
+            },
+            "P" => RecordMsg::ParseError(
+                decode_rejected(payload)
+                    .ok_or_else(|| anyhow::anyhow!("capture line {}: bad parse error", n + 1))?,
+            ),
+            "I" => RecordMsg::IoError(payload.to_string()),
+            "D" => match HawkFrame::parse_tsv_line(payload) {
+                Ok(Some(f)) => RecordMsg::Derived(f),
+                _ => anyhow::bail!("capture line {}: bad derived frame", n + 1),
+            },
+            other => anyhow::bail!("capture line {}: unknown tag {}", n + 1, other),
+        };
+        records.push(Record { at_ms, msg });
+    }
+
+    let started = started.ok_or_else(|| anyhow::anyhow!("{:?} is empty", path))?;
+    Ok(Capture { started, records })
+}
+// :: ∎
+
+// ▛▞// player :: hawk.capture.play
+// ⫸ [speed.seek.pause]
+#[derive(Debug, Clone, Copy)]
+pub enum ReplayCtl {
+    TogglePause,
+    /// Relative seek in capture milliseconds.
+    Seek(i64),
+    /// Multiply the current speed.
+    Speed(f64),
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayState {
+    pub started: OffsetDateTime,
+    pub pos_ms: u64,
+    pub total_ms: u64,
+    pub speed: f64,
+    pub paused: bool,
+    /// When the player last wrote `pos_ms`.
+    pub updated: Instant,
+}
+
+impl ReplayState {
+    /// Current position, moving between player updates while playing.
+    pub fn position_ms(&self) -> u64 {
+        if self.paused {
+            return self.pos_ms;
+        }
+        let run = self.updated.elapsed().as_secs_f64() * 1000.0 * self.speed;
+        (self.pos_ms + run as u64).min(self.total_ms)
+    }
+
+    /// Capture wall time at the current position.
+    pub fn position_time(&self) -> OffsetDateTime {
+        self.started + time::Duration::milliseconds(self.position_ms() as i64)
+    }
+}
+
+/// UI side of a running replay.
+#[derive(Debug, Clone)]
+pub struct ReplayHandle {
+    pub ctl: Sender<ReplayCtl>,
+    pub state: Arc<Mutex<ReplayState>>,
+}
+
+impl ReplayHandle {
+    pub fn send(&self, c: ReplayCtl) {
+        let _ = self.ctl.send(c);
+    }
+
+    pub fn snapshot(&self) -> Option<ReplayState> {
+        self.state.lock().ok().map(|s| s.clone())
+    }
+}
+
+/// Replays judge liveness against the capture timeline, not the wall clock.
+impl Clock for ReplayHandle {
+    fn now(&self) -> OffsetDateTime {
+        self.snapshot()
+            .map(|s| s.position_time())
+            .unwrap_or_else(OffsetDateTime::now_utc)
+    }
+}
+
+/// Accepts `10x`, `10`, `0.5x`.
+pub fn parse_speed(s: &str) -> Result<f64, String> {
+    let t = s.trim().trim_end_matches(['x', 'X']);
+    let v: f64 = t.parse().map_err(|_| format!("bad speed: {}", s))?;
+    if !(v > 0.0 && v.is_finite()) {
+        return Err(format!("speed must be > 0, got {}", s));
+    }
+    Ok(v)
+}
+
+/// Feed the capture into `tx` on the capture's own timeline, scaled by speed.
+pub fn spawn_player(cap: Capture, speed: f64, tx: Sender<IngestMsg>) -> ReplayHandle {
+    let (ctl_tx, ctl_rx) = std::sync::mpsc::channel::<ReplayCtl>();
+    let state = Arc::new(Mutex::new(ReplayState {
+        started: cap.started,
+        pos_ms: 0,
+        total_ms: cap.total_ms(),
+        speed,
+        paused: false,
+        updated: Instant::now(),
+    }));
+
+    let shared = Arc::clone(&state);
+    std::thread::spawn(move || play(cap, speed, tx, ctl_rx, shared));
+
+    ReplayHandle { ctl: ctl_tx, state }
+}
+
+fn play(
+    cap: Capture,
+    mut speed: f64,
+    tx: Sender<IngestMsg>,
+    ctl_rx: Receiver<ReplayCtl>,
+    shared: Arc<Mutex<ReplayState>>,
+) {
+    let total = cap.total_ms();
+    let mut idx = 0usize;
+    let mut pos: f64 = 0.0;
+    let mut paused = false;
+    let mut last = Instant::now();
+
+    loop {
+        if !paused {
+            pos = (pos + last.elapsed().as_secs_f64() * 1000.0 * speed).min(total as f64);
+        }
+        last = Instant::now();
+
+        // Emit everything due at the current position.
+        while idx < cap.records.len() && cap.records[idx].at_ms as f64 <= pos {
+            if tx.send(cap.records[idx].msg.to_ingest()).is_err() {
+                return;
+            }
+            idx += 1;
+        }
+
+        if let Ok(mut s) = shared.lock() {
+            s.pos_ms = pos as u64;
+            s.speed = speed;
+            s.paused = paused;
+            s.updated = Instant::now();
+        }
+
+        let ctl = if paused || idx >= cap.records.len() {
+            match ctl_rx.recv() {
+                Ok(c) => c,
+                Err(_) => return,
+            }
+        } else {
+            let due = cap.records[idx].at_ms as f64 - pos;
+            let wait = Duration::from_secs_f64((due / speed / 1000.0).max(0.0));
+            match ctl_rx.recv_timeout(wait) {
+                Ok(c) => c,
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        };
+
+        if !paused {
+            pos = (pos + last.elapsed().as_secs_f64() * 1000.0 * speed).min(total as f64);
+        }
+        last = Instant::now();
+
+        match ctl {
+            ReplayCtl::TogglePause => paused = !paused,
+            ReplayCtl::Speed(m) => speed = (speed * m).clamp(0.125, 1024.0),
+            ReplayCtl::Seek(delta) => {
+                let target = (pos + delta as f64).clamp(0.0, total as f64);
+                if target < pos {
+                    // Rebuild state from the start up to the target.
+                    if tx.send(IngestMsg::Reset).is_err() {
+                        return;
+                    }
+                    idx = 0;
+                }
+                pos = target;
+            }
+        }
+    }
+}
+// :: ∎
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_round_trips() {
+        let path = std::env::temp_dir().join(format!("hawk-cap-{}.gz", std::process::id()));
+        let mut rec = Recorder::create(&path).unwrap();
+        let line = "2026-02-16T12:34:56Z\tHEALTH\tservice\talpha\tok\talive\tpid=1";
+        let f = HawkFrame::parse_tsv_line(line).unwrap().unwrap();
+        rec.write(&IngestMsg::Frame(f)).unwrap();
+        let rejected = RejectedLine::new(
+            "stdin",
+            2,
+            64,
+            "a\tb\tc",
+            HawkFrameParseError::TooFewColumns { found: 3 },
+        );
+        rec.write(&IngestMsg::ParseError(rejected)).unwrap();
+        rec.finish().unwrap();
+
+        let cap = read_capture(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(cap.records.len(), 2);
+        match &cap.records[0].msg {
+            RecordMsg::Frame(f) => assert_eq!(f.id, "alpha"),
+            other => panic!("unexpected {:?}", other),
+        }
+        match &cap.records[1].msg {
+            RecordMsg::ParseError(r) => {
+                assert!(matches!(
+                    r.error,
+                    HawkFrameParseError::TooFewColumns { found: 3 }
+                ));
+                assert_eq!((r.source.as_str(), r.line_no, r.offset), ("stdin", 2, 64));
+                assert_eq!(r.raw, "a\tb\tc");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_speed() {
+        assert_eq!(parse_speed("10x").unwrap(), 10.0);
+        assert_eq!(parse_speed("0.5").unwrap(), 0.5);
+        assert!(parse_speed("0x").is_err());
+    }
+}
+// :: ∎
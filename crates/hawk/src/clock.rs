@@ -0,0 +1,53 @@
+// ▛▞// hawk clock :: hawk.clock
+// @ctx ⫸ [system.replay.manual]
+use std::fmt::Debug;
+
+use time::OffsetDateTime;
+
+// :: ∎
+
+// ▛▞// clock :: hawk.clock.source
+// ⫸ [now.inject]
+/// Where the mirror gets "now" for liveness, ages and forget TTLs.
+/// Live runs use the system clock, `hawk replay` uses the capture position.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> OffsetDateTime;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+}
+
+/// Settable clock for tests.
+#[cfg(test)]
+#[derive(Debug)]
+pub struct ManualClock {
+    now: std::sync::Mutex<OffsetDateTime>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new(now: OffsetDateTime) -> Self {
+        Self {
+            now: std::sync::Mutex::new(now),
+        }
+    }
+
+    pub fn advance(&self, d: time::Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now += d;
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> OffsetDateTime {
+        *self.now.lock().unwrap()
+    }
+}
+// :: ∎
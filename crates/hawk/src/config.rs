@@ -0,0 +1,565 @@
+// ▛▞// hawk ui config :: hawk.config
+// @ctx ⫸ [theme.columns.layout]
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::Context;
+use hawk_core::Level;
+use ratatui::style::Color;
+use ratatui::widgets::{Block, BorderType, Borders};
+use serde::Deserialize;
+
+use crate::app::Liveness;
+
+// :: ∎
+
+// ▛▞// public model :: hawk.config.model
+// @ctx ⫸ [theme.column.resolved]
+#[derive(Debug, Clone)]
+pub struct UiConfig {
+    pub theme: Theme,
+    pub columns: Vec<ColumnSpec>,
+    /// Entity table share of the body width, in percent. Tail gets the rest.
+    pub split_pct: u16,
+    pub tick_rate: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    pub accent: Color,
+    /// Background for the selected row and tail lines that match it.
+    pub highlight: Color,
+    pub border: BorderStyle,
+    pub level_debug: Color,
+    pub level_ok: Color,
+    pub level_info: Color,
+    pub level_notice: Color,
+    pub level_warn: Color,
+    pub level_fail: Color,
+    pub level_critical: Color,
+    pub level_unknown: Color,
+    pub live_active: Color,
+    pub live_dream: Color,
+    pub live_stale: Color,
+    pub live_dead: Color,
+    /// Profile refresh hint, used when neither CLI nor file sets tick_ms.
+    pub tick_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorderStyle {
+    Plain,
+    Rounded,
+    Double,
+    Thick,
+    /// Top rule only.
+    Minimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Scope,
+    Id,
+    Kind,
+    Level,
+    State,
+    Age,
+    Msg,
+    Kv(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ColumnSpec {
+    pub column: Column,
+    pub width: u16,
+}
+
+/// CLI values that win over the config file.
+#[derive(Debug, Clone, Default)]
+pub struct UiOverrides {
+    pub theme: Option<String>,
+    pub columns: Option<Vec<String>>,
+    pub split_pct: Option<u16>,
+    pub tick_ms: Option<u64>,
+}
+// :: ∎
+
+// ▛▞// file types :: hawk.config.file
+// @ctx ⫸ [toml.ui.layout.themes]
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    ui: FileUi,
+    #[serde(default)]
+    layout: FileLayout,
+    #[serde(default)]
+    themes: BTreeMap<String, FileTheme>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FileUi {
+    theme: Option<String>,
+    tick_ms: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FileLayout {
+    split: Option<u16>,
+    columns: Option<Vec<String>>,
+    #[serde(default)]
+    widths: BTreeMap<String, u16>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FileTheme {
+    base: Option<String>,
+    accent: Option<String>,
+    highlight: Option<String>,
+    border: Option<String>,
+    tick_ms: Option<u64>,
+    #[serde(default)]
+    level: BTreeMap<String, String>,
+    #[serde(default)]
+    liveness: BTreeMap<String, String>,
+}
+// :: ∎
+
+// ▛▞// theme :: hawk.config.theme
+// ⫸ [profile.operator.calm.aggressive]
+pub const DEFAULT_COLUMNS: [&str; 7] = ["scope", "id", "kind", "level", "state", "age", "msg"];
+
+impl Theme {
+    /// Built-in profiles mirror the sets in docs/STYLE_OPTIONS.md.
+    pub fn builtin(name: &str) -> Option<Theme> {
+        let base = Theme {
+            name: "default".to_string(),
+            accent: Color::Reset,
+            highlight: Color::DarkGray,
+            border: BorderStyle::Plain,
+            level_debug: Color::DarkGray,
+            level_ok: Color::Green,
+            level_info: Color::Cyan,
+            level_notice: Color::LightBlue,
+            level_warn: Color::Yellow,
+            level_fail: Color::Red,
+            level_critical: Color::Magenta,
+            level_unknown: Color::Gray,
+            live_active: Color::Green,
+            live_dream: Color::Blue,
+            live_stale: Color::Yellow,
+            live_dead: Color::Red,
+            tick_ms: None,
+        };
+
+        match name {
+            "default" => Some(base),
+            "operator" => Some(Theme {
+                name: "operator".to_string(),
+                accent: Color::Indexed(208),
+                border: BorderStyle::Thick,
+                level_warn: Color::Indexed(208),
+                ..base
+            }),
+            "calm" => Some(Theme {
+                name: "calm".to_string(),
+                accent: Color::Indexed(73),
+                highlight: Color::Indexed(237),
+                border: BorderStyle::Minimal,
+                level_ok: Color::Indexed(73),
+                level_info: Color::Indexed(110),
+                level_warn: Color::Indexed(179),
+                level_fail: Color::Indexed(167),
+                level_critical: Color::Indexed(132),
+                live_active: Color::Indexed(73),
+                live_dream: Color::Indexed(66),
+                live_stale: Color::Indexed(179),
+                live_dead: Color::Indexed(167),
+                ..base
+            }),
+            "aggressive" => Some(Theme {
+                name: "aggressive".to_string(),
+                accent: Color::LightRed,
+                border: BorderStyle::Double,
+                level_ok: Color::LightGreen,
+                level_info: Color::LightCyan,
+                level_warn: Color::LightYellow,
+                level_fail: Color::LightRed,
+                level_critical: Color::LightMagenta,
+                live_stale: Color::LightYellow,
+                live_dead: Color::LightRed,
+                tick_ms: Some(100),
+                ..base
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn level_color(&self, level: Level) -> Color {
+        match level {
+            Level::Debug => self.level_debug,
+            Level::Ok => self.level_ok,
+            Level::Info => self.level_info,
+            Level::Notice => self.level_notice,
+            Level::Warn => self.level_warn,
+            Level::Fail => self.level_fail,
+            Level::Critical => self.level_critical,
+            Level::Unknown => self.level_unknown,
+        }
+    }
+
+    pub fn liveness_color(&self, live: Liveness) -> Color {
+        match live {
+            Liveness::Active => self.live_active,
+            Liveness::Dream => self.live_dream,
+            Liveness::Stale => self.live_stale,
+            Liveness::Dead => self.live_dead,
+        }
+    }
+
+    /// Bordered panel block in this theme's border style and accent.
+    pub fn block<'a>(&self, title: &'a str) -> Block<'a> {
+        let (borders, ty) = match self.border {
+            BorderStyle::Plain => (Borders::ALL, BorderType::Plain),
+            BorderStyle::Rounded => (Borders::ALL, BorderType::Rounded),
+            BorderStyle::Double => (Borders::ALL, BorderType::Double),
+            BorderStyle::Thick => (Borders::ALL, BorderType::Thick),
+            BorderStyle::Minimal => (Borders::TOP, BorderType::Plain),
+        };
+        Block::default()
+            .borders(borders)
+            .border_type(ty)
+            .border_style(ratatui::style::Style::default().fg(self.accent))
+            .title(title)
+    }
+
+    fn apply(&mut self, ft: &FileTheme) -> Result<(), String> {
+        if let Some(a) = &ft.accent {
+            self.accent = parse_color(a)?;
+        }
+        if let Some(h) = &ft.highlight {
+            self.highlight = parse_color(h)?;
+        }
+        if let Some(b) = &ft.border {
+            self.border = parse_border(b)?;
+        }
+        if ft.tick_ms.is_some() {
+            self.tick_ms = ft.tick_ms;
+        }
+
+        for (k, v) in &ft.level {
+            let c = parse_color(v)?;
+            match Level::parse(k) {
+                Level::Debug => self.level_debug = c,
+                Level::Ok => self.level_ok = c,
+                Level::Info => self.level_info = c,
+                Level::Notice => self.level_notice = c,
+                Level::Warn => self.level_warn = c,
+                Level::Fail => self.level_fail = c,
+                Level::Critical => self.level_critical = c,
+                Level::Unknown => self.level_unknown = c,
+            }
+        }
+
+        for (k, v) in &ft.liveness {
+            let c = parse_color(v)?;
+            match k.trim().to_ascii_lowercase().as_str() {
+                "active" => self.live_active = c,
+                "dream" => self.live_dream = c,
+                "stale" => self.live_stale = c,
+                "dead" => self.live_dead = c,
+                other => return Err(format!("unknown liveness key: {}", other)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_color(s: &str) -> Result<Color, String> {
+    Color::from_str(s.trim()).map_err(|_| format!("bad color: {}", s))
+}
+
+fn parse_border(s: &str) -> Result<BorderStyle, String> {
+    match s.trim().to_ascii_lowercase().as_str() {
+        "plain" => Ok(BorderStyle::Plain),
+        "rounded" => Ok(BorderStyle::Rounded),
+        "double" => Ok(BorderStyle::Double),
+        "thick" => Ok(BorderStyle::Thick),
+        "minimal" => Ok(BorderStyle::Minimal),
+        _ => Err(format!(
+            "unknown border '{}', expected plain|rounded|double|thick|minimal",
+            s
+        )),
+    }
+}
+// :: ∎
+
+// ▛▞// columns :: hawk.config.columns
+// ⫸ [column.name.kv.width]
+impl Column {
+    pub fn parse(s: &str) -> Result<Column, String> {
+        let t = s.trim();
+        if let Some(key) = t.strip_prefix("kv:") {
+            let key = key.trim();
+            if key.is_empty() {
+                return Err("kv column needs a key, e.g. kv:unit".to_string());
+            }
+            return Ok(Column::Kv(key.to_string()));
+        }
+
+        match t.to_ascii_lowercase().as_str() {
+            "scope" => Ok(Column::Scope),
+            "id" => Ok(Column::Id),
+            "kind" => Ok(Column::Kind),
+            "level" => Ok(Column::Level),
+            "state" => Ok(Column::State),
+            "age" => Ok(Column::Age),
+            "msg" => Ok(Column::Msg),
+            _ => Err(format!(
+                "unknown column '{}', expected scope|id|kind|level|state|age|msg|kv:<key>",
+                s
+            )),
+        }
+    }
+
+    /// Config name, as written in `columns` and `widths`.
+    pub fn name(&self) -> String {
+        match self {
+            Column::Kv(k) => format!("kv:{}", k),
+            Column::Msg => "msg".to_string(),
+            other => other.header(),
+        }
+    }
+
+    pub fn header(&self) -> String {
+        match self {
+            Column::Scope => "scope".to_string(),
+            Column::Id => "id".to_string(),
+            Column::Kind => "kind".to_string(),
+            Column::Level => "level".to_string(),
+            Column::State => "state".to_string(),
+            Column::Age => "age".to_string(),
+            Column::Msg => "last msg".to_string(),
+            Column::Kv(k) => k.clone(),
+        }
+    }
+
+    pub fn default_width(&self) -> u16 {
+        match self {
+            Column::Scope => 10,
+            Column::Id => 20,
+            Column::Kind => 14,
+            Column::Level => 8,
+            Column::State => 8,
+            Column::Age => 6,
+            Column::Msg => 10,
+            Column::Kv(_) => 14,
+        }
+    }
+}
+// :: ∎
+
+// ▛▞// load :: hawk.config.load
+// ⫸ [xdg.search.merge.override]
+impl Default for UiConfig {
+    fn default() -> Self {
+        let columns = DEFAULT_COLUMNS
+            .iter()
+            .filter_map(|c| Column::parse(c).ok())
+            .map(|column| ColumnSpec {
+                width: column.default_width(),
+                column,
+            })
+            .collect();
+
+        Self {
+            theme: Theme::builtin("default").expect("default theme"),
+            columns,
+            split_pct: 68,
+            tick_rate: Duration::from_millis(200),
+        }
+    }
+}
+
+/// Search order: $XDG_CONFIG_HOME/hawk/hawk.toml, ~/.config/hawk/hawk.toml,
+/// then each entry of $XDG_CONFIG_DIRS (default /etc/xdg).
+pub fn find_config_file() -> Option<PathBuf> {
+    let mut candidates: Vec<PathBuf> = Vec::new();
+
+    match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(v) if !v.is_empty() => candidates.push(PathBuf::from(v)),
+        _ => {
+            if let Some(home) = std::env::var_os("HOME") {
+                candidates.push(PathBuf::from(home).join(".config"));
+            }
+        }
+    }
+
+    let dirs = std::env::var("XDG_CONFIG_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/etc/xdg".to_string());
+    for d in dirs.split(':') {
+        if !d.is_empty() {
+            candidates.push(PathBuf::from(d));
+        }
+    }
+
+    candidates
+        .into_iter()
+        .map(|d| d.join("hawk").join("hawk.toml"))
+        .find(|p| p.is_file())
+}
+
+/// Load hawk.toml (explicit path, or XDG search) and apply CLI overrides.
+/// An explicit path must exist; a missing searched file means defaults.
+pub fn load_ui_config(explicit: Option<&Path>, ov: &UiOverrides) -> anyhow::Result<UiConfig> {
+    let path = match explicit {
+        Some(p) => {
+            if !p.is_file() {
+                anyhow::bail!("config file not found: {:?}", p);
+            }
+            Some(p.to_path_buf())
+        }
+        None => find_config_file(),
+    };
+
+    let file = match &path {
+        Some(p) => {
+            let raw = fs::read_to_string(p).with_context(|| format!("read config {:?}", p))?;
+            toml::from_str::<ConfigFile>(&raw).with_context(|| format!("parse config {:?}", p))?
+        }
+        None => ConfigFile::default(),
+    };
+
+    resolve(file, ov).map_err(|e| match &path {
+        Some(p) => anyhow::anyhow!("config {:?}: {}", p, e),
+        None => anyhow::anyhow!("{}", e),
+    })
+}
+
+fn resolve(file: ConfigFile, ov: &UiOverrides) -> Result<UiConfig, String> {
+    let mut cfg = UiConfig::default();
+
+    let theme_name = ov
+        .theme
+        .clone()
+        .or(file.ui.theme.clone())
+        .unwrap_or_else(|| "default".to_string());
+    cfg.theme = resolve_theme(&theme_name, &file.themes)?;
+
+    let split = ov.split_pct.or(file.layout.split).unwrap_or(cfg.split_pct);
+    if !(10..=90).contains(&split) {
+        return Err(format!("split must be within 10..=90, got {}", split));
+    }
+    cfg.split_pct = split;
+
+    let tick_ms = ov
+        .tick_ms
+        .or(file.ui.tick_ms)
+        .or(cfg.theme.tick_ms)
+        .unwrap_or(200);
+    if tick_ms == 0 {
+        return Err("tick_ms must be > 0".to_string());
+    }
+    cfg.tick_rate = Duration::from_millis(tick_ms);
+
+    if let Some(names) = ov.columns.as_ref().or(file.layout.columns.as_ref()) {
+        let mut columns = Vec::with_capacity(names.len());
+        for n in names {
+            let column = Column::parse(n)?;
+            let width = file
+                .layout
+                .widths
+                .get(&column.name())
+                .copied()
+                .unwrap_or_else(|| column.default_width());
+            columns.push(ColumnSpec { column, width });
+        }
+        if columns.is_empty() {
+            return Err("columns cannot be empty".to_string());
+        }
+        cfg.columns = columns;
+    } else {
+        for spec in cfg.columns.iter_mut() {
+            if let Some(w) = file.layout.widths.get(&spec.column.name()) {
+                spec.width = *w;
+            }
+        }
+    }
+
+    Ok(cfg)
+}
+
+fn resolve_theme(name: &str, custom: &BTreeMap<String, FileTheme>) -> Result<Theme, String> {
+    let Some(ft) = custom.get(name) else {
+        return Theme::builtin(name).ok_or_else(|| format!("unknown theme: {}", name));
+    };
+
+    let base = ft.base.as_deref().unwrap_or("default");
+    let mut theme =
+        Theme::builtin(base).ok_or_else(|| format!("theme {} has unknown base: {}", name, base))?;
+    theme.name = name.to_string();
+    theme
+        .apply(ft)
+        .map_err(|e| format!("theme {}: {}", name, e))?;
+    Ok(theme)
+}
+// :: ∎
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> ConfigFile {
+        toml::from_str(raw).unwrap()
+    }
+
+    #[test]
+    fn cli_overrides_file() {
+        let file = parse("[ui]\ntheme = \"calm\"\ntick_ms = 500\n[layout]\nsplit = 50\n");
+        let ov = UiOverrides {
+            split_pct: Some(75),
+            ..UiOverrides::default()
+        };
+        let cfg = resolve(file, &ov).unwrap();
+        assert_eq!(cfg.theme.name, "calm");
+        assert_eq!(cfg.split_pct, 75);
+        assert_eq!(cfg.tick_rate, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn custom_theme_inherits_base() {
+        let file = parse(
+            "[ui]\ntheme = \"mine\"\n[themes.mine]\nbase = \"operator\"\n[themes.mine.level]\nfail = \"#ff0000\"\n",
+        );
+        let cfg = resolve(file, &UiOverrides::default()).unwrap();
+        assert_eq!(cfg.theme.border, BorderStyle::Thick);
+        assert_eq!(cfg.theme.level_fail, Color::Rgb(255, 0, 0));
+    }
+
+    #[test]
+    fn kv_columns_take_widths() {
+        let file =
+            parse("[layout]\ncolumns = [\"id\", \"kv:unit\"]\n[layout.widths]\n\"kv:unit\" = 22\n");
+        let cfg = resolve(file, &UiOverrides::default()).unwrap();
+        assert_eq!(cfg.columns.len(), 2);
+        assert_eq!(cfg.columns[1].column, Column::Kv("unit".to_string()));
+        assert_eq!(cfg.columns[1].width, 22);
+    }
+
+    #[test]
+    fn aggressive_profile_sets_tick() {
+        let ov = UiOverrides {
+            theme: Some("aggressive".to_string()),
+            ..UiOverrides::default()
+        };
+        let cfg = resolve(ConfigFile::default(), &ov).unwrap();
+        assert_eq!(cfg.tick_rate, Duration::from_millis(100));
+    }
+}
+// :: ∎
@@ -7,10 +7,15 @@ use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::Arc;
 
-use hawk_core::HawkFrame;
+use hawk_core::{HawkFrame, ParseOptions};
 
+use crate::awk::{self, interp, AwkBackend};
+use crate::fixtures::Fixture;
 use crate::packs;
+use crate::sandbox::{Jail, SandboxPolicy};
+use crate::signing::{Signature, TrustPolicy};
 
 // :: ∎
 
@@ -27,6 +32,10 @@ pub enum SecurityMode {
 pub struct DoctorOptions {
     pub smoke: bool,
     pub security: SecurityMode,
+    /// Embedded compiles each script; external runs the pattern scan.
+    pub backend: AwkBackend,
+    /// Signature policy each pack is checked against.
+    pub trust: TrustPolicy,
 }
 
 #[derive(Debug, Clone)]
@@ -77,7 +86,7 @@ pub fn run_pack_doctor(packs_dir: &Path, opts: DoctorOptions) -> DoctorReport {
         Ok(rd) => {
             for entry in rd.flatten() {
                 let p = entry.path();
-                if p.is_dir() {
+                if p.is_dir() && !packs::is_hidden(&p) {
                     entries.push(p);
                 }
             }
@@ -92,86 +101,222 @@ pub fn run_pack_doctor(packs_dir: &Path, opts: DoctorOptions) -> DoctorReport {
 
     let mut seen_thread_ids: BTreeMap<String, PathBuf> = BTreeMap::new();
 
+    let mut packs = Vec::new();
     for pack_dir in entries {
-        let manifest = pack_dir.join("pack.toml");
-        if !manifest.exists() {
-            continue;
+        packs.extend(check_pack(&pack_dir, &opts, &mut seen_thread_ids, &mut rep));
+    }
+
+    for (i, pack) in packs.iter().enumerate() {
+        if let Some(other) = packs[..i].iter().find(|p| p.id == pack.id) {
+            rep.errors.push(format!(
+                "pack id {} used by both {:?} and {:?}",
+                pack.id, other.root_dir, pack.root_dir
+            ));
+        }
+        if let Err(e) = packs::check_dependencies(pack, &packs) {
+            rep.errors.push(format!("pack {}: {}", pack.id, e));
         }
+    }
 
-        let pack = match packs::parse_pack_manifest(&pack_dir, &manifest) {
-            Ok(v) => v,
-            Err(e) => {
-                rep.errors
-                    .push(format!("pack parse failed {:?}: {}", manifest, e));
-                continue;
-            }
-        };
+    rep
+}
 
-        if pack.threads.is_empty() {
-            rep.warnings
-                .push(format!("pack {:?} has zero threads", manifest));
+/// Doctor a single pack directory, e.g. one staged by `hawk pack install`.
+pub fn run_pack_dir_doctor(pack_dir: &Path, opts: DoctorOptions) -> DoctorReport {
+    let mut rep = DoctorReport::new();
+    if !pack_dir.join("pack.toml").exists() {
+        rep.errors
+            .push(format!("no pack.toml in {:?}", pack_dir));
+        return rep;
+    }
+    check_pack(pack_dir, &opts, &mut BTreeMap::new(), &mut rep);
+    rep
+}
+
+fn check_pack(
+    pack_dir: &Path,
+    opts: &DoctorOptions,
+    seen_thread_ids: &mut BTreeMap<String, PathBuf>,
+    rep: &mut DoctorReport,
+) -> Option<packs::Pack> {
+    let manifest = pack_dir.join("pack.toml");
+    if !manifest.exists() {
+        return None;
+    }
+
+    let pack = match packs::parse_pack_manifest(pack_dir, &manifest) {
+        Ok(v) => v,
+        Err(e) => {
+            rep.errors
+                .push(format!("pack parse failed {:?}: {}", manifest, e));
+            return None;
         }
+    };
+
+    match opts.trust.verify(&pack) {
+        Ok(Signature::Untrusted(e)) => rep
+            .warnings
+            .push(format!("pack {:?} signature: {}", manifest, e)),
+        Ok(_) => {}
+        Err(e) => rep
+            .errors
+            .push(format!("pack {:?} signature: {}", manifest, e)),
+    }
+
+    if pack.threads.is_empty() {
+        rep.warnings
+            .push(format!("pack {:?} has zero threads", manifest));
+    }
 
-        for th in &pack.threads {
-            if let Some(prev) = seen_thread_ids.get(&th.id) {
+    for th in &pack.threads {
+        // Across packs the ids stay addressable as pack_id/thread_id.
+        if let Some(prev) = seen_thread_ids.get(&th.id) {
+            if *prev == manifest {
                 rep.errors.push(format!(
-                    "thread id collision '{}' between {:?} and {:?}",
-                    th.id, prev, manifest
+                    "thread id '{}' declared twice in {:?}",
+                    th.id, manifest
                 ));
             } else {
-                seen_thread_ids.insert(th.id.clone(), manifest.clone());
-            }
-
-            let script_path = pack.root_dir.join(&th.file);
-            if !script_path.exists() {
-                rep.errors.push(format!(
-                    "thread '{}' missing file {:?}",
-                    th.id, script_path
+                rep.warnings.push(format!(
+                    "thread id '{}' in both {:?} and {:?}; the bare id is ambiguous, use {}/{}",
+                    th.id, prev, manifest, pack.id, th.id
                 ));
-                continue;
             }
+        } else {
+            seen_thread_ids.insert(th.id.clone(), manifest.clone());
+        }
 
-            if let Err(e) = check_header_and_terminator(&script_path) {
-                rep.warnings
-                    .push(format!("thread '{}' style: {}", th.id, e));
-            }
+        let script_path = pack.root_dir.join(&th.file);
+        if !script_path.exists() {
+            rep.errors.push(format!(
+                "thread '{}' missing file {:?}",
+                th.id, script_path
+            ));
+            continue;
+        }
 
-            for w in scan_declared_arg_usage(&script_path, &th.args) {
-                rep.warnings
-                    .push(format!("thread '{}' schema: {}", th.id, w));
-            }
+        if let Err(e) = check_header_and_terminator(&script_path) {
+            rep.warnings
+                .push(format!("thread '{}' style: {}", th.id, e));
+        }
 
-            match security_scan_awk(&script_path, opts.security) {
-                Ok(findings) => {
-                    for f in findings {
-                        match f.severity {
-                            FindingSeverity::Error => {
-                                rep.errors.push(format!("thread '{}' security: {}", th.id, f.message));
-                            }
-                            FindingSeverity::Warning => {
-                                rep.warnings
-                                    .push(format!("thread '{}' security: {}", th.id, f.message));
-                            }
+        for w in scan_declared_arg_usage(&script_path, &th.args) {
+            rep.warnings
+                .push(format!("thread '{}' schema: {}", th.id, w));
+        }
+
+        // The embedded parser rejects system(), pipes, getline and file
+        // output outright, so compiling is the security check there.
+        let program = match opts.backend {
+            AwkBackend::Embedded => match awk::compile_script(&script_path) {
+                Ok(p) => Some(Arc::new(p)),
+                Err(e) => {
+                    rep.errors
+                        .push(format!("thread '{}' compile: {}", th.id, e));
+                    continue;
+                }
+            },
+            AwkBackend::External => None,
+        };
+
+        let scan = match program {
+            Some(_) => Ok(Vec::new()),
+            None => security_scan_awk(&script_path, opts.security),
+        };
+        match scan {
+            Ok(findings) => {
+                for f in findings {
+                    match f.severity {
+                        FindingSeverity::Error => {
+                            rep.errors.push(format!("thread '{}' security: {}", th.id, f.message));
+                        }
+                        FindingSeverity::Warning => {
+                            rep.warnings
+                                .push(format!("thread '{}' security: {}", th.id, f.message));
                         }
                     }
                 }
+            }
+            Err(e) => {
+                rep.errors
+                    .push(format!("thread '{}' security scan failed: {}", th.id, e));
+            }
+        }
+
+        let jail = match program {
+            Some(_) => None,
+            None => match Jail::prepare(&pack.sandbox, &script_path) {
+                Ok(jail) => {
+                    if let Some(note) = jail.as_ref().and_then(|j| j.note()) {
+                        rep.warnings
+                            .push(format!("thread '{}' sandbox: {}", th.id, note));
+                    }
+                    jail
+                }
                 Err(e) => {
                     rep.errors
-                        .push(format!("thread '{}' security scan failed: {}", th.id, e));
+                        .push(format!("thread '{}' sandbox: {}", th.id, e));
+                    continue;
                 }
-            }
+            },
+        };
 
-            if opts.smoke {
-                let defaults = packs::default_tvars(&th.args);
-                if let Err(e) = smoke_test_awk(&script_path, &defaults) {
+        let unset: Vec<&str> = th
+            .args
+            .iter()
+            .filter(|a| a.required)
+            .map(|a| a.name.as_str())
+            .collect();
+        if opts.smoke && !unset.is_empty() {
+            rep.warnings.push(format!(
+                "thread '{}' smoke skipped: required args {} have no default",
+                th.id,
+                unset.join(", ")
+            ));
+        } else if opts.smoke {
+            let defaults = packs::awk_tvars(&th.args, packs::default_tvars(&th.args));
+            let sample = smoke_sample(th.tick.is_some());
+            let out = match &program {
+                Some(p) => smoke_embedded(Arc::clone(p), &defaults, &pack.sandbox, &sample),
+                None => smoke_external(&script_path, &defaults, jail.as_ref(), &sample),
+            };
+            match out.and_then(|out| check_smoke_output(&out)) {
+                Ok(repairs) => {
+                    for w in repairs {
+                        rep.warnings
+                            .push(format!("thread '{}' smoke: {}", th.id, w));
+                    }
+                }
+                Err(e) => {
                     rep.errors
                         .push(format!("thread '{}' smoke: {}", th.id, e));
                 }
             }
         }
+
+        if opts.smoke {
+            let fixture_dir = pack.root_dir.join("tests").join(&th.id);
+            let res = match Fixture::load(&fixture_dir) {
+                Ok(Some(fx)) => {
+                    run_fixture(&fx, th, &program, &pack.sandbox, &script_path, jail.as_ref())
+                }
+                Ok(None) => Ok(()),
+                Err(e) => Err(format!("{:#}", e)),
+            };
+            if let Err(e) = res {
+                rep.errors
+                    .push(format!("thread '{}' fixture: {}", th.id, e));
+            }
+        }
     }
 
-    rep
+    if opts.smoke {
+        for w in stray_fixture_dirs(&pack) {
+            rep.warnings.push(w);
+        }
+    }
+
+    Some(pack)
 }
 // :: ∎
 
@@ -333,14 +478,70 @@ fn security_scan_awk(script_path: &Path, mode: SecurityMode) -> Result<Vec<Secur
 
 // ▛▞// smoke test :: hawk.doctor.smoke
 // ⫸ [spawn.feed.validate]
-fn smoke_test_awk(script_path: &Path, tvars: &BTreeMap<String, String>) -> Result<(), String> {
-    let mut cmd = Command::new("awk");
+const SMOKE_SAMPLE: [&str; 3] = [
+    "2026-02-16T00:00:00Z\tHEALTH\tsystemd\tspine.hawkd\tok\tactive\tunit=hawkd.service",
+    "2026-02-16T00:00:01Z\tHEALTH\tgrpc\tproto.alpha\tfail\tNOT_SERVING\tendpoint=svc:443;service=;error=x",
+    "2026-02-16T00:00:02Z\tRECEIPT_EVENT\thawkd\tingest\twarn\tingest parse error\terror=bad;raw=clip",
+];
+
+/// Two ticks an hour apart, so any sane aggregate window flushes once.
+const SMOKE_TICKS: [&str; 2] = [
+    "2026-02-16T00:00:03Z\tTICK\thawk\ttick\tinfo\ttick\tepoch=1771200003",
+    "2026-02-16T01:00:03Z\tTICK\thawk\ttick\tinfo\ttick\tepoch=1771203603",
+];
+
+fn smoke_sample(needs_tick: bool) -> Vec<&'static str> {
+    let mut lines = SMOKE_SAMPLE.to_vec();
+    if needs_tick {
+        lines.extend(SMOKE_TICKS);
+    }
+    lines
+}
+
+/// Same stack and limits as the runner, so a script that fails there fails
+/// here too.
+fn smoke_embedded(
+    program: Arc<awk::lang::Program>,
+    tvars: &BTreeMap<String, String>,
+    policy: &SandboxPolicy,
+    sample: &[&str],
+) -> Result<String, String> {
+    let limits = awk::embedded_limits(policy);
+    awk::with_stack(move || {
+        let mut it = interp::Interp::new(program, tvars).with_limits(limits);
+        let mut status = it.begin()?;
+        if it.reads_input() {
+            for line in sample {
+                if status != interp::Status::Running {
+                    break;
+                }
+                status = it.record(line)?;
+            }
+        }
+        if it.end()? != 0 {
+            return Err("awk exited nonzero".to_string());
+        }
+        Ok(it.out)
+    })
+}
+
+fn smoke_external(
+    script_path: &Path,
+    tvars: &BTreeMap<String, String>,
+    jail: Option<&Arc<Jail>>,
+    sample: &[&str],
+) -> Result<String, String> {
+    let mut cmd = match jail {
+        Some(j) => j.command(),
+        None => Command::new("awk"),
+    };
 
     for (k, v) in tvars {
         cmd.arg("-v").arg(format!("{}={}", k, v));
     }
 
-    cmd.arg("-f").arg(script_path);
+    cmd.arg("-f")
+        .arg(jail.map_or(script_path, |j| j.script_arg()));
     cmd.stdin(Stdio::piped());
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::null());
@@ -350,13 +551,7 @@ fn smoke_test_awk(script_path: &Path, tvars: &BTreeMap<String, String>) -> Resul
     let mut stdin = child.stdin.take().ok_or_else(|| "awk stdin missing".to_string())?;
     let mut stdout = child.stdout.take().ok_or_else(|| "awk stdout missing".to_string())?;
 
-    let sample = [
-        "2026-02-16T00:00:00Z\tHEALTH\tsystemd\tspine.hawkd\tok\tactive\tunit=hawkd.service",
-        "2026-02-16T00:00:01Z\tHEALTH\tgrpc\tproto.alpha\tfail\tNOT_SERVING\tendpoint=svc:443;service=;error=x",
-        "2026-02-16T00:00:02Z\tRECEIPT_EVENT\thawkd\tingest\twarn\tingest parse error\terror=bad;raw=clip",
-    ];
-
-    for line in &sample {
+    for line in sample {
         stdin
             .write_all(line.as_bytes())
             .map_err(|e| format!("write stdin: {}", e))?;
@@ -375,21 +570,101 @@ fn smoke_test_awk(script_path: &Path, tvars: &BTreeMap<String, String>) -> Resul
     if !status.success() {
         return Err("awk exited nonzero".to_string());
     }
+    Ok(out)
+}
+
+/// Fixture tvars layer over the thread defaults, then go through the same
+/// validation and awk conversion as `--transform` args.
+fn run_fixture(
+    fx: &Fixture,
+    th: &packs::Thread,
+    program: &Option<Arc<awk::lang::Program>>,
+    policy: &SandboxPolicy,
+    script_path: &Path,
+    jail: Option<&Arc<Jail>>,
+) -> Result<(), String> {
+    let mut tvars = packs::default_tvars(&th.args);
+    tvars.extend(fx.tvars.clone());
+    packs::validate_tvars(&th.args, &tvars)?;
+    let tvars = packs::awk_tvars(&th.args, tvars);
+
+    let input: Vec<&str> = fx.input.iter().map(String::as_str).collect();
+    let out = match program {
+        Some(p) => smoke_embedded(Arc::clone(p), &tvars, policy, &input)?,
+        None => smoke_external(script_path, &tvars, jail, &input)?,
+    };
+    fx.check(&out)
+}
+
+/// `tests/<dir>` entries that match no thread of the pack.
+fn stray_fixture_dirs(pack: &packs::Pack) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(pack.root_dir.join("tests")) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for e in entries.flatten() {
+        let name = e.file_name().to_string_lossy().into_owned();
+        if e.path().is_dir() && !pack.threads.iter().any(|t| t.id == name) {
+            out.push(format!(
+                "pack {} fixture dir tests/{} matches no thread",
+                pack.id, name
+            ));
+        }
+    }
+    out.sort();
+    out
+}
 
-    for line in out.lines() {
+/// Parsed exactly as the runtime does (`emit_line`, lenient): a line hawk
+/// would reject is an error. Ok carries one warning per line that only
+/// parsed after repairs, such as dropped extra columns.
+fn check_smoke_output(out: &str) -> Result<Vec<String>, String> {
+    let opts = ParseOptions::default();
+    let mut repairs = Vec::new();
+    for (n, line) in out.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
         let t = line.trim();
         if t.is_empty() || t.starts_with('#') {
             continue;
         }
 
-        match HawkFrame::parse_tsv_line(t) {
-            Ok(Some(_)) => {}
-            Ok(None) => {}
-            Err(_) => {
-                return Err(format!("invalid output line (not HawkFrame TSV): {}", t));
+        match HawkFrame::parse_tsv_line_with(line, &opts) {
+            Ok(Some(p)) if !p.repairs.is_empty() => {
+                let what: Vec<String> = p.repairs.iter().map(|r| r.to_string()).collect();
+                repairs.push(format!("output line {} needs repair: {}", n + 1, what.join(", ")));
+            }
+            Ok(_) => {}
+            Err(e) => {
+                return Err(format!(
+                    "output line {} is dropped at runtime ({}): {}",
+                    n + 1,
+                    e,
+                    t
+                ));
             }
         }
     }
 
-    Ok(())
+    Ok(repairs)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_output_is_judged_like_the_runtime() {
+        let ok = "2026-02-16T00:00:00Z\tHEALTH\ta\t1\tok\tm\t\n";
+        assert_eq!(check_smoke_output(ok), Ok(Vec::new()));
+
+        let extra = "2026-02-16T00:00:00Z\tHEALTH\ta\t1\tok\tm\tk=v\tx\n";
+        assert_eq!(check_smoke_output(extra).unwrap().len(), 1);
+
+        // Six columns: only Repair mode would take it, the runtime drops it.
+        let six = "2026-02-16T00:00:00Z\tHEALTH\ta\t1\tok\tm\n";
+        assert!(check_smoke_output(six)
+            .unwrap_err()
+            .contains("dropped at runtime"));
+    }
+}
+// :: ∎
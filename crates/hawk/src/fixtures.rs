@@ -0,0 +1,207 @@
+// ▛▞// thread fixtures :: hawk.fixtures
+// @ctx ⫸ [tests.input.expected]
+// @ctx ⫸ [compare.ts.kv.order]
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+use hawk_core::TICK_KIND;
+use serde::Deserialize;
+
+use crate::packs;
+
+// :: ∎
+
+// ▛▞// fixture files :: hawk.fixtures.load
+// ⫸ [input.expected.fixture_toml]
+/// One `tests/<thread_id>/` dir of a pack: `input.tsv` is fed to the thread,
+/// its output must match `expected.tsv`. `fixture.toml` is optional.
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub input: Vec<String>,
+    pub expected: Vec<String>,
+    /// Layered over the thread's defaults.
+    pub tvars: BTreeMap<String, String>,
+    pub compare: CompareOptions,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CompareOptions {
+    /// Mask column 1 on both sides, for threads that stamp their own time.
+    pub ignore_ts: bool,
+    /// kv keys dropped from column 7 on both sides.
+    pub ignore_kv: Vec<String>,
+    /// Compare sorted lines, for aggregates that emit in hash order.
+    pub unordered: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FixtureToml {
+    /// exact (default) | ignore
+    #[serde(default)]
+    ts: Option<String>,
+    #[serde(default)]
+    ignore_kv: Vec<String>,
+    #[serde(default)]
+    unordered: bool,
+    #[serde(default)]
+    tvars: BTreeMap<String, toml::Value>,
+}
+
+impl Fixture {
+    /// `Ok(None)` when `dir` has no `input.tsv`.
+    pub fn load(dir: &Path) -> anyhow::Result<Option<Self>> {
+        let input_path = dir.join("input.tsv");
+        if !input_path.exists() {
+            return Ok(None);
+        }
+        let expected_path = dir.join("expected.tsv");
+        if !expected_path.exists() {
+            anyhow::bail!("{:?} has input.tsv but no expected.tsv", dir);
+        }
+
+        let spec_path = dir.join("fixture.toml");
+        let spec: FixtureToml = if spec_path.exists() {
+            let raw =
+                fs::read_to_string(&spec_path).with_context(|| format!("read {:?}", spec_path))?;
+            toml::from_str(&raw).with_context(|| format!("parse {:?}", spec_path))?
+        } else {
+            FixtureToml::default()
+        };
+
+        let ignore_ts = match spec.ts.as_deref().unwrap_or("exact") {
+            "exact" => false,
+            "ignore" => true,
+            other => anyhow::bail!("fixture ts '{}', expected exact|ignore", other),
+        };
+
+        Ok(Some(Self {
+            input: read_lines(&input_path)?,
+            expected: read_lines(&expected_path)?,
+            tvars: spec
+                .tvars
+                .into_iter()
+                .map(|(k, v)| (k, packs::toml_value_to_string(Some(v))))
+                .collect(),
+            compare: CompareOptions {
+                ignore_ts,
+                ignore_kv: spec.ignore_kv,
+                unordered: spec.unordered,
+            },
+        }))
+    }
+
+    /// `Err` describes the first difference between `out` and `expected.tsv`.
+    pub fn check(&self, out: &str) -> Result<(), String> {
+        let got: Vec<String> = out
+            .lines()
+            .filter(|l| keep(l) && !is_tick(l))
+            .map(str::to_string)
+            .collect();
+        let mut want = self.compare.normalize(&self.expected);
+        let mut got = self.compare.normalize(&got);
+        if self.compare.unordered {
+            want.sort();
+            got.sort();
+        }
+
+        let at = (0..want.len().max(got.len())).find(|&i| want.get(i) != got.get(i));
+        let Some(i) = at else {
+            return Ok(());
+        };
+        let shown = |l: Option<&String>| l.map_or("<none>".to_string(), |l| l.replace('\t', "  "));
+        Err(format!(
+            "output differs at line {} (expected {} lines, got {}):\n    want: {}\n    got:  {}",
+            i + 1,
+            want.len(),
+            got.len(),
+            shown(want.get(i)),
+            shown(got.get(i))
+        ))
+    }
+}
+
+/// Data lines only: blanks and `#` comments are skipped on both sides.
+fn keep(line: &str) -> bool {
+    let t = line.trim();
+    !t.is_empty() && !t.starts_with('#')
+}
+
+/// Ticks echoed back by the script, which the bridge drops too.
+fn is_tick(line: &str) -> bool {
+    line.split('\t').nth(1) == Some(TICK_KIND)
+}
+
+fn read_lines(path: &Path) -> anyhow::Result<Vec<String>> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {:?}", path))?;
+    Ok(raw
+        .lines()
+        .filter(|l| keep(l))
+        .map(|l| l.trim_end_matches('\r').to_string())
+        .collect())
+}
+// :: ∎
+
+// ▛▞// compare :: hawk.fixtures.compare
+// ⫸ [mask.ts.kv]
+impl CompareOptions {
+    fn normalize(&self, lines: &[String]) -> Vec<String> {
+        lines.iter().map(|l| self.normalize_line(l)).collect()
+    }
+
+    fn normalize_line(&self, line: &str) -> String {
+        let mut cols: Vec<String> = line.split('\t').map(str::to_string).collect();
+        if self.ignore_ts {
+            cols[0] = "*".to_string();
+        }
+        if !self.ignore_kv.is_empty() && cols.len() >= 7 {
+            let kept: Vec<&str> = cols[6]
+                .split(';')
+                .filter(|pair| {
+                    let key = pair.split_once('=').map_or(*pair, |(k, _)| k).trim();
+                    !self.ignore_kv.iter().any(|k| k == key)
+                })
+                .collect();
+            cols[6] = kept.join(";");
+        }
+        cols.join("\t")
+    }
+}
+// :: ∎
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_masks_ts_and_kv_and_can_ignore_order() {
+        let fx = |compare| Fixture {
+            input: Vec::new(),
+            expected: vec![
+                "2026-02-16T00:00:00Z\tX\ta\t1\tok\tm\tn=1;epoch=5".to_string(),
+                "2026-02-16T00:00:00Z\tX\tb\t2\tok\tm\tn=2;epoch=5".to_string(),
+            ],
+            tvars: BTreeMap::new(),
+            compare,
+        };
+        let out = "# comment\n\
+                   2026-10-19T07:00:00Z\tTICK\thawk\ttick\tinfo\t\t\n\
+                   2026-10-19T07:00:00Z\tX\tb\t2\tok\tm\tn=2;epoch=9\n\
+                   2026-10-19T07:00:00Z\tX\ta\t1\tok\tm\tn=1;epoch=9\n";
+
+        let err = fx(CompareOptions::default()).check(out).unwrap_err();
+        assert!(err.contains("line 1 (expected 2 lines, got 2)"), "{}", err);
+
+        let loose = CompareOptions {
+            ignore_ts: true,
+            ignore_kv: vec!["epoch".to_string()],
+            unordered: true,
+        };
+        assert_eq!(fx(loose.clone()).check(out), Ok(()));
+        let err = fx(loose).check(&out.replace("n=1", "n=3")).unwrap_err();
+        assert!(err.contains("want: *  X  a  1  ok  m  n=1"), "{}", err);
+    }
+}
+// :: ∎
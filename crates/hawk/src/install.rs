@@ -0,0 +1,523 @@
+// ▛▞// hawk pack install :: hawk.install
+// @ctx ⫸ [stage.doctor.activate]
+// @ctx ⫸ [lockfile.versions]
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::doctor::{self, DoctorOptions};
+use crate::packs::{self, Pack, PackIndex};
+use crate::signing::Signature;
+
+// :: ∎
+
+// ▛▞// lockfile :: hawk.install.lock
+// ⫸ [toml.version.source]
+pub const LOCK_FILE: &str = "hawk.lock";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "pack")]
+    pub packs: Vec<LockedPack>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedPack {
+    pub id: String,
+    pub version: String,
+    /// What `install` was given; `update` reuses it.
+    pub source: String,
+    pub installed_at: String,
+    /// Trusted key that signed the installed manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_by: Option<String>,
+}
+
+impl Lockfile {
+    pub fn load(packs_dir: &Path) -> anyhow::Result<Self> {
+        let path = packs_dir.join(LOCK_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(&path).with_context(|| format!("read lockfile {:?}", path))?;
+        toml::from_str(&raw).with_context(|| format!("parse lockfile {:?}", path))
+    }
+
+    fn save(&self, packs_dir: &Path) -> anyhow::Result<()> {
+        let path = packs_dir.join(LOCK_FILE);
+        let tmp = packs_dir.join(format!(".{}.tmp", LOCK_FILE));
+        let body = toml::to_string(self).context("serialize lockfile")?;
+        fs::write(
+            &tmp,
+            format!("# Written by hawk pack install|update|remove.\n{}", body),
+        )
+        .with_context(|| format!("write lockfile {:?}", tmp))?;
+        fs::rename(&tmp, &path).with_context(|| format!("replace lockfile {:?}", path))
+    }
+
+    pub fn get(&self, id: &str) -> Option<&LockedPack> {
+        self.packs.iter().find(|p| p.id == id)
+    }
+
+    fn put(&mut self, entry: LockedPack) {
+        self.packs.retain(|p| p.id != entry.id);
+        self.packs.push(entry);
+        self.packs.sort_by(|a, b| a.id.cmp(&b.id));
+    }
+}
+// :: ∎
+
+// ▛▞// sources :: hawk.install.source
+// ⫸ [dir.tarball.git]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    Dir(PathBuf),
+    Tarball(PathBuf),
+    /// `file://` URL or path of a local git mirror.
+    Git(String),
+}
+
+impl Source {
+    fn parse(s: &str) -> anyhow::Result<Self> {
+        if s.starts_with("file://") {
+            return Ok(Source::Git(s.to_string()));
+        }
+        if s.contains("://") || s.starts_with("git@") {
+            anyhow::bail!(
+                "only local git mirrors are supported (file:// URL or a repo path), got {}",
+                s
+            );
+        }
+
+        let p = PathBuf::from(s);
+        if p.is_dir() {
+            let is_repo =
+                p.join(".git").exists() || (p.join("HEAD").is_file() && p.join("objects").is_dir());
+            if is_repo && !p.join("pack.toml").exists() {
+                return Ok(Source::Git(s.to_string()));
+            }
+            return Ok(Source::Dir(p));
+        }
+
+        let name = s.to_ascii_lowercase();
+        if p.is_file()
+            && [".tar", ".tar.gz", ".tgz"]
+                .iter()
+                .any(|ext| name.ends_with(ext))
+        {
+            return Ok(Source::Tarball(p));
+        }
+
+        anyhow::bail!(
+            "pack source is not a dir, .tar[.gz] or local git mirror: {}",
+            s
+        )
+    }
+
+    /// Fetch into `into`, which must not exist yet.
+    fn fetch(&self, into: &Path) -> anyhow::Result<()> {
+        match self {
+            Source::Dir(p) => copy_tree(p, into),
+            Source::Tarball(p) => {
+                fs::create_dir_all(into).with_context(|| format!("create {:?}", into))?;
+                let mut cmd = Command::new("tar");
+                cmd.arg("-xf")
+                    .arg(p)
+                    .arg("-C")
+                    .arg(into)
+                    .arg("--no-same-owner")
+                    .arg("--no-same-permissions");
+                run_tool(cmd, "tar")
+            }
+            Source::Git(url) => {
+                // Local transports only, even for submodules or redirects.
+                let mut cmd = Command::new("git");
+                cmd.args([
+                    "-c",
+                    "protocol.allow=never",
+                    "-c",
+                    "protocol.file.allow=always",
+                ])
+                .args(["clone", "--quiet", "--depth", "1"])
+                .arg(url)
+                .arg(into)
+                .env("GIT_TERMINAL_PROMPT", "0");
+                run_tool(cmd, "git clone")
+            }
+        }
+    }
+}
+
+fn run_tool(mut cmd: Command, what: &str) -> anyhow::Result<()> {
+    let out = cmd.output().with_context(|| format!("spawn {}", what))?;
+    if !out.status.success() {
+        anyhow::bail!(
+            "{} failed: {}",
+            what,
+            String::from_utf8_lossy(&out.stderr).trim()
+        );
+    }
+    Ok(())
+}
+// :: ∎
+
+// ▛▞// staging :: hawk.install.stage
+// ⫸ [fetch.copy.parse]
+static STAGE_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+/// Directory removed on drop unless it was moved away.
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn new(parent: &Path, prefix: &str) -> Self {
+        let n = STAGE_SEQ.fetch_add(1, Ordering::Relaxed);
+        Self(parent.join(format!("{}-{}-{}", prefix, std::process::id(), n)))
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A fetched pack copied into a hidden dir of packs_dir, ready to activate.
+struct Staged {
+    dir: ScratchDir,
+    pack: Pack,
+}
+
+fn stage(packs_dir: &Path, source: &Source) -> anyhow::Result<Staged> {
+    let fetched = ScratchDir::new(&std::env::temp_dir(), "hawk-fetch");
+    source.fetch(&fetched.0)?;
+    let root = find_pack_root(&fetched.0)?;
+
+    // Hidden, so load_packs skips it; same filesystem, so activation is a rename.
+    fs::create_dir_all(packs_dir).with_context(|| format!("create packs dir {:?}", packs_dir))?;
+    let dir = ScratchDir::new(packs_dir, ".hawk-install");
+    copy_tree(&root, &dir.0)?;
+
+    let pack = packs::parse_pack_manifest(&dir.0, &dir.0.join("pack.toml"))
+        .context("parse staged pack.toml")?;
+    Ok(Staged { dir, pack })
+}
+
+/// The fetched tree itself, or its single top-level dir (tarballs usually wrap one).
+fn find_pack_root(dir: &Path) -> anyhow::Result<PathBuf> {
+    if dir.join("pack.toml").is_file() {
+        return Ok(dir.to_path_buf());
+    }
+    let subdirs: Vec<PathBuf> = fs::read_dir(dir)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir() && !packs::is_hidden(p))
+        .collect();
+    match subdirs.as_slice() {
+        [one] if one.join("pack.toml").is_file() => Ok(one.clone()),
+        _ => anyhow::bail!("no pack.toml at the top of the pack source"),
+    }
+}
+
+/// Plain files and dirs only; `.git` is left behind.
+fn copy_tree(from: &Path, to: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(to).with_context(|| format!("create {:?}", to))?;
+    for entry in fs::read_dir(from).with_context(|| format!("read {:?}", from))? {
+        let entry = entry?;
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        let ty = entry.file_type()?;
+        let dst = to.join(entry.file_name());
+        if ty.is_dir() {
+            copy_tree(&entry.path(), &dst)?;
+        } else if ty.is_file() {
+            fs::copy(entry.path(), &dst).with_context(|| format!("copy {:?}", entry.path()))?;
+        } else {
+            anyhow::bail!(
+                "pack contains a symlink or special file: {:?}",
+                entry.path()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Thread ids other packs already ship. Both stay reachable as
+/// `pack_id/thread_id`, but the bare id stops resolving.
+fn collisions(idx: &PackIndex, pack: &Pack, replacing: Option<&str>) -> Vec<String> {
+    let mut out = Vec::new();
+    for th in &pack.threads {
+        let mut others: Vec<&str> = idx
+            .threads
+            .values()
+            .filter(|t| t.thread_id == th.id && Some(t.pack_id.as_str()) != replacing)
+            .map(|t| t.pack_id.as_str())
+            .collect();
+        others.sort();
+        for other in others {
+            out.push(format!(
+                "thread {} is also in pack {}; bare '{}' is now ambiguous, use {}/{}",
+                th.id, other, th.id, pack.id, th.id
+            ));
+        }
+    }
+    out
+}
+
+/// Doctor warnings on success; every error otherwise.
+fn run_doctor(staged: &Staged, opts: DoctorOptions) -> anyhow::Result<Vec<String>> {
+    let rep = doctor::run_pack_dir_doctor(&staged.dir.0, opts);
+    if !rep.ok() {
+        anyhow::bail!(
+            "pack doctor failed for {}, nothing installed:\n  {}",
+            staged.pack.id,
+            rep.errors.join("\n  ")
+        );
+    }
+    Ok(rep.warnings)
+}
+// :: ∎
+
+// ▛▞// commands :: hawk.install.cmd
+// ⫸ [install.update.remove]
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub id: String,
+    pub version: String,
+    /// Version replaced by `update`.
+    pub previous: Option<String>,
+    pub dir: PathBuf,
+    pub warnings: Vec<String>,
+}
+
+pub fn install(packs_dir: &Path, source: &str, opts: DoctorOptions) -> anyhow::Result<Outcome> {
+    let src = Source::parse(source)?;
+    let staged = stage(packs_dir, &src)?;
+    let idx = packs::load_packs(packs_dir, &opts.trust)?;
+
+    if idx.packs.iter().any(|p| p.id == staged.pack.id) {
+        anyhow::bail!(
+            "pack {} is already installed; use `hawk pack update {}`",
+            staged.pack.id,
+            staged.pack.id
+        );
+    }
+    let dest = packs_dir.join(&staged.pack.id);
+    if dest.exists() {
+        anyhow::bail!("{:?} already exists", dest);
+    }
+    let clashes = collisions(&idx, &staged.pack, None);
+    packs::check_dependencies(&staged.pack, &idx.packs).map_err(anyhow::Error::msg)?;
+    let signed_by = opts.trust.verify(&staged.pack).ok().and_then(Signature::trusted_key);
+    let mut warnings = run_doctor(&staged, opts)?;
+    warnings.extend(clashes);
+
+    fs::rename(&staged.dir.0, &dest).with_context(|| format!("activate {:?}", dest))?;
+    record(packs_dir, &staged.pack, source, signed_by)?;
+
+    Ok(Outcome {
+        id: staged.pack.id.clone(),
+        version: staged.pack.version.clone(),
+        previous: None,
+        dir: dest,
+        warnings,
+    })
+}
+
+/// Replace pack `id` with a fresh copy of `source`, or of its recorded source.
+pub fn update(
+    packs_dir: &Path,
+    id: &str,
+    source: Option<&str>,
+    opts: DoctorOptions,
+) -> anyhow::Result<Outcome> {
+    let lock = Lockfile::load(packs_dir)?;
+    let idx = packs::load_packs(packs_dir, &opts.trust)?;
+    let current = idx
+        .packs
+        .iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| anyhow::anyhow!("pack {} is not installed", id))?;
+
+    let source = match source {
+        Some(s) => s.to_string(),
+        None => lock
+            .get(id)
+            .map(|l| l.source.clone())
+            .ok_or_else(|| anyhow::anyhow!("no source recorded for pack {}; pass one", id))?,
+    };
+    let staged = stage(packs_dir, &Source::parse(&source)?)?;
+    if staged.pack.id != id {
+        anyhow::bail!("source provides pack {}, not {}", staged.pack.id, id);
+    }
+    let clashes = collisions(&idx, &staged.pack, Some(id));
+
+    // The new version must satisfy its own deps and every dependent's range.
+    let after: Vec<Pack> = idx
+        .packs
+        .iter()
+        .map(|p| if p.id == id { staged.pack.clone() } else { p.clone() })
+        .collect();
+    for p in &after {
+        packs::check_dependencies(p, &after)
+            .map_err(|e| anyhow::anyhow!("pack {}: {}", p.id, e))?;
+    }
+    let signed_by = opts.trust.verify(&staged.pack).ok().and_then(Signature::trusted_key);
+    let mut warnings = run_doctor(&staged, opts)?;
+    warnings.extend(clashes);
+
+    // Swap by renames so a failure leaves the old copy in place.
+    let dest = current.root_dir.clone();
+    let old = ScratchDir::new(packs_dir, ".hawk-old");
+    fs::rename(&dest, &old.0).with_context(|| format!("move aside {:?}", dest))?;
+    if let Err(e) = fs::rename(&staged.dir.0, &dest) {
+        let _ = fs::rename(&old.0, &dest);
+        return Err(e).with_context(|| format!("activate {:?}", dest));
+    }
+    record(packs_dir, &staged.pack, &source, signed_by)?;
+
+    Ok(Outcome {
+        id: id.to_string(),
+        version: staged.pack.version.clone(),
+        previous: Some(current.version.clone()),
+        dir: dest,
+        warnings,
+    })
+}
+
+/// Delete pack `id` and its lock entry. Scans manifests one by one so a
+/// broken tree (e.g. colliding packs) can still be repaired.
+pub fn remove(packs_dir: &Path, id: &str) -> anyhow::Result<Outcome> {
+    let mut found = None;
+    let mut dependents = Vec::new();
+    for entry in
+        fs::read_dir(packs_dir).with_context(|| format!("read packs dir {:?}", packs_dir))?
+    {
+        let dir = entry?.path();
+        let manifest = dir.join("pack.toml");
+        if !dir.is_dir() || packs::is_hidden(&dir) || !manifest.exists() {
+            continue;
+        }
+        if let Ok(pack) = packs::parse_pack_manifest(&dir, &manifest) {
+            if pack.dependencies.contains_key(id) {
+                dependents.push(pack.id.clone());
+            }
+            if pack.id == id {
+                found = Some(pack);
+            }
+        }
+    }
+    let pack = found.ok_or_else(|| anyhow::anyhow!("pack {} is not installed", id))?;
+    if !dependents.is_empty() {
+        anyhow::bail!("pack {} is required by {}", id, dependents.join(", "));
+    }
+
+    fs::remove_dir_all(&pack.root_dir).with_context(|| format!("remove {:?}", pack.root_dir))?;
+    let mut lock = Lockfile::load(packs_dir)?;
+    if lock.get(id).is_some() {
+        lock.packs.retain(|p| p.id != id);
+        lock.save(packs_dir)?;
+    }
+
+    Ok(Outcome {
+        id: pack.id,
+        version: pack.version,
+        previous: None,
+        dir: pack.root_dir,
+        warnings: Vec::new(),
+    })
+}
+
+fn record(
+    packs_dir: &Path,
+    pack: &Pack,
+    source: &str,
+    signed_by: Option<String>,
+) -> anyhow::Result<()> {
+    let mut lock = Lockfile::load(packs_dir)?;
+    lock.put(LockedPack {
+        id: pack.id.clone(),
+        version: pack.version.clone(),
+        source: source.to_string(),
+        installed_at: OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_default(),
+        signed_by,
+    });
+    lock.save(packs_dir)
+}
+// :: ∎
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::awk::AwkBackend;
+    use crate::doctor::SecurityMode;
+    use crate::packs::testkit::Manifest;
+    use crate::signing::TrustPolicy;
+
+    fn write_pack(dir: &Path, id: &str, version: &str, thread: &str) {
+        Manifest::new(id)
+            .version(version)
+            .thread(thread)
+            .script("# ▛▞// t :: test\n{ print }\n# :: ∎\n")
+            .write(dir);
+    }
+
+    #[test]
+    fn install_update_remove_keep_the_lockfile_in_step() {
+        let root = ScratchDir::new(&std::env::temp_dir(), "hawk-install-test");
+        let packs_dir = root.0.join("packs");
+        let opts = DoctorOptions {
+            smoke: true,
+            security: SecurityMode::Strict,
+            backend: AwkBackend::Embedded,
+            trust: TrustPolicy::default(),
+        };
+
+        let src = root.0.join("src-a");
+        write_pack(&src, "demo.a", "0.1.0", "echo");
+        let out = install(&packs_dir, src.to_str().unwrap(), opts.clone()).unwrap();
+        assert_eq!(out.dir, packs_dir.join("demo.a"));
+        assert!(install(&packs_dir, src.to_str().unwrap(), opts.clone())
+            .unwrap_err()
+            .to_string()
+            .contains("already installed"));
+
+        // A second pack may ship the same thread id; it only warns.
+        let clash = root.0.join("src-b");
+        write_pack(&clash, "demo.b", "0.1.0", "echo");
+        let out = install(&packs_dir, clash.to_str().unwrap(), opts.clone()).unwrap();
+        assert!(out.warnings.iter().any(|w| w.contains("use demo.b/echo")), "{:?}", out.warnings);
+        remove(&packs_dir, "demo.b").unwrap();
+
+        write_pack(&src, "demo.a", "0.2.0", "echo");
+        let out = update(&packs_dir, "demo.a", None, opts).unwrap();
+        assert_eq!(out.previous.as_deref(), Some("0.1.0"));
+        assert_eq!(
+            Lockfile::load(&packs_dir)
+                .unwrap()
+                .get("demo.a")
+                .unwrap()
+                .version,
+            "0.2.0"
+        );
+
+        remove(&packs_dir, "demo.a").unwrap();
+        assert!(!packs_dir.join("demo.a").exists());
+        assert!(Lockfile::load(&packs_dir).unwrap().packs.is_empty());
+        let leftovers: Vec<_> = fs::read_dir(&packs_dir)
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name())
+            .collect();
+        assert_eq!(leftovers, [LOCK_FILE]);
+    }
+}
+// :: ∎
@@ -1,103 +1,425 @@
 // ▛▞// hawk io :: hawk.io
-// @ctx ⫸ [ingest.stdin.error]
-use std::io::{self, BufRead};
+// @ctx ⫸ [ingest.sources.error]
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
 
-use hawk_core::{HawkFrame, HawkFrameParseError};
+use anyhow::Context;
+use hawk_core::{HawkFrame, ParseOptions, RejectedLine, RepairedLine, REPLAY_DONE_MARKER};
 
 // ▛▞// ingest types :: hawk.io.types
 // @ctx ⫸ [ingest.msg.error]
 #[derive(Debug)]
 pub enum IngestMsg {
     Frame(HawkFrame),
-    ParseError(HawkFrameParseError),
+    ParseError(RejectedLine),
+    /// A frame was accepted after repairs; the frame itself follows.
+    Repaired(RepairedLine),
     IoError(String),
+    /// Transform output routed to the derived panel (`output = "panel"`).
+    Derived(HawkFrame),
+    /// hawkd finished replaying cached frames; live frames follow.
+    ReplayDone,
+    /// Drop entity and tail state (capture replay seeking backwards).
+    Reset,
 }
 // :: ∎
 
-// ▛▞// stdin source :: hawk.io.stdin
-// @ctx ⫸ [source.readline.parse]
-pub fn spawn_stdin_reader(tx: std::sync::mpsc::Sender<IngestMsg>, strict: bool) {
-    std::thread::spawn(move || {
-        let stdin = io::stdin();
-        let mut locked = stdin.lock();
-
-        let mut line = String::new();
-        loop {
-            line.clear();
-            match locked.read_line(&mut line) {
-                Ok(0) => break,
-                Ok(_) => match HawkFrame::parse_tsv_line(&line) {
-                    Ok(Some(f)) => {
-                        let _ = tx.send(IngestMsg::Frame(f));
-                    }
-                    Ok(None) => {}
-                    Err(e) => {
-                        let _ = tx.send(IngestMsg::ParseError(e.clone()));
-                        if strict {
-                            break;
-                        }
-                    }
-                },
-                Err(e) => {
-                    let _ = tx.send(IngestMsg::IoError(e.to_string()));
-                    break;
-                }
+// ▛▞// source specs :: hawk.io.source
+// @ctx ⫸ [uri.name.parse]
+/// Frames are tagged with the name of the source they arrived on.
+pub const SOURCE_KV: &str = "source";
+
+/// A producer's own `source` kv, kept here when the tag replaces it.
+pub const CLAIMED_SOURCE_KV: &str = "claimed_source";
+
+/// Transform output merged with raw frames carries `origin=transform`.
+pub const ORIGIN_KV: &str = "origin";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceKind {
+    Stdin,
+    /// None means the `--socket-path` default.
+    Unix(Option<String>),
+    File(String),
+    Tcp(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceSpec {
+    pub name: String,
+    pub kind: SourceKind,
+}
+
+impl SourceSpec {
+    /// `[name=]stdin|unix[:path]|file:path|tcp://host:port`
+    pub fn parse(s: &str) -> Result<SourceSpec, String> {
+        let t = s.trim();
+        let (name, uri) = match t.split_once('=') {
+            Some((n, u)) if !n.is_empty() && !n.contains([':', '/']) => (Some(n), u),
+            _ => (None, t),
+        };
+
+        let kind = if uri == "stdin" || uri == "-" {
+            SourceKind::Stdin
+        } else if uri == "unix" {
+            SourceKind::Unix(None)
+        } else if let Some(addr) = uri.strip_prefix("tcp://") {
+            if !addr.contains(':') {
+                return Err(format!("tcp source needs host:port, got {}", s));
             }
+            SourceKind::Tcp(addr.to_string())
+        } else if let Some(path) = uri.strip_prefix("unix:") {
+            SourceKind::Unix(Some(path.to_string()))
+        } else if let Some(path) = uri.strip_prefix("file:") {
+            SourceKind::File(path.to_string())
+        } else {
+            return Err(format!(
+                "unknown source {} (stdin, unix:<path>, file:<path>, tcp://host:port)",
+                s
+            ));
+        };
+
+        if matches!(&kind, SourceKind::Unix(Some(p)) | SourceKind::File(p) if p.is_empty()) {
+            return Err(format!("source {} needs a path", s));
         }
-    });
+
+        let name = match name {
+            Some(n) => n.to_string(),
+            None => default_source_name(&kind),
+        };
+        Ok(SourceSpec { name, kind })
+    }
+}
+
+fn default_source_name(kind: &SourceKind) -> String {
+    let base = |p: &str| {
+        std::path::Path::new(p)
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_else(|| p.to_string())
+    };
+    match kind {
+        SourceKind::Stdin => "stdin".to_string(),
+        SourceKind::Unix(None) => "unix".to_string(),
+        SourceKind::Unix(Some(p)) | SourceKind::File(p) => base(p),
+        SourceKind::Tcp(addr) => addr.clone(),
+    }
 }
 // :: ∎
 
-// ▛▞// unix source :: hawk.io.unix
-// ⫸ [source.connect.readlines]
-pub fn spawn_unix_reader(
-    tx: std::sync::mpsc::Sender<IngestMsg>,
+// ▛▞// source stats :: hawk.io.stats
+// ⫸ [status.counters.shared]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceStatus {
+    Connecting,
+    Connected,
+    /// Reader reached EOF or the peer closed.
+    Closed,
+    Failed(String),
+}
+
+impl SourceStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            SourceStatus::Connecting => "connecting",
+            SourceStatus::Connected => "up",
+            SourceStatus::Closed => "closed",
+            SourceStatus::Failed(_) => "failed",
+        }
+    }
+}
+
+/// Shared between a reader thread and the mirror's top bar.
+#[derive(Debug)]
+pub struct SourceStats {
+    pub name: String,
+    pub status: Mutex<SourceStatus>,
+    pub frames: AtomicU64,
+    pub parse_errors: AtomicU64,
+    pub repaired: AtomicU64,
+}
+
+impl SourceStats {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            status: Mutex::new(SourceStatus::Connecting),
+            frames: AtomicU64::new(0),
+            parse_errors: AtomicU64::new(0),
+            repaired: AtomicU64::new(0),
+        }
+    }
+
+    pub fn set_status(&self, st: SourceStatus) {
+        if let Ok(mut cur) = self.status.lock() {
+            *cur = st;
+        }
+    }
+
+    pub fn status(&self) -> SourceStatus {
+        self.status
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or(SourceStatus::Connecting)
+    }
+}
+// :: ∎
+
+// ▛▞// quarantine :: hawk.io.quarantine
+// ⫸ [rejected.append.refeed]
+/// Append-only file of rejected lines. Each line is preceded by a `#` comment
+/// with its origin and reason, so the file can be fixed up and fed back in.
+#[derive(Debug)]
+pub struct Quarantine {
+    pub path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Quarantine {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("create {:?}", dir))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("open quarantine {:?}", path))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+        })
+    }
+
+    /// `raw` is the full line; the in-memory `RejectedLine` only keeps a clip.
+    fn write(&self, r: &RejectedLine, raw: &str) -> io::Result<()> {
+        let mut f = self
+            .file
+            .lock()
+            .map_err(|_| io::Error::other("quarantine lock poisoned"))?;
+        writeln!(
+            f,
+            "# {}:{} offset={} {}",
+            r.source, r.line_no, r.offset, r.error
+        )?;
+        writeln!(f, "{}", raw.trim_end_matches(['\n', '\r']))
+    }
+}
+// :: ∎
+
+// ▛▞// source readers :: hawk.io.readers
+// @ctx ⫸ [stdin.unix.file.tcp]
+#[derive(Debug, Clone, Default)]
+pub struct ReaderOptions {
+    /// Stop a reader at its first rejected line.
+    pub strict: bool,
+    pub parse: ParseOptions,
+    pub quarantine: Option<Arc<Quarantine>>,
+}
+
+/// Start one reader thread per spec. The channel disconnects once every
+/// reader has finished.
+pub fn spawn_sources(
+    specs: &[SourceSpec],
     socket_path: &str,
-    strict: bool,
+    tx: Sender<IngestMsg>,
+    opts: &ReaderOptions,
+) -> anyhow::Result<Vec<Arc<SourceStats>>> {
+    let mut seen = std::collections::BTreeSet::new();
+    let mut out = Vec::new();
+    for spec in specs {
+        if !seen.insert(spec.name.clone()) {
+            anyhow::bail!("duplicate source name {:?}; use name=uri", spec.name);
+        }
+        let stats = Arc::new(SourceStats::new(&spec.name));
+        spawn_source(
+            spec.kind.clone(),
+            socket_path,
+            Arc::clone(&stats),
+            tx.clone(),
+            opts.clone(),
+        )?;
+        out.push(stats);
+    }
+    Ok(out)
+}
+
+fn spawn_source(
+    kind: SourceKind,
+    socket_path: &str,
+    stats: Arc<SourceStats>,
+    tx: Sender<IngestMsg>,
+    opts: ReaderOptions,
 ) -> anyhow::Result<()> {
     #[cfg(not(unix))]
-    {
+    if let SourceKind::Unix(_) = kind {
         anyhow::bail!("unix socket source requires a unix platform (WSL, Linux, macOS)");
     }
+    let socket_path = socket_path.to_string();
+
+    std::thread::spawn(move || {
+        let opened: io::Result<Box<dyn BufRead>> = match &kind {
+            SourceKind::Stdin => Ok(Box::new(io::stdin().lock())),
+            SourceKind::File(p) => {
+                std::fs::File::open(p).map(|f| Box::new(BufReader::new(f)) as Box<dyn BufRead>)
+            }
+            SourceKind::Tcp(addr) => std::net::TcpStream::connect(addr)
+                .map(|s| Box::new(BufReader::new(s)) as Box<dyn BufRead>),
+            #[cfg(unix)]
+            SourceKind::Unix(p) => {
+                std::os::unix::net::UnixStream::connect(p.as_deref().unwrap_or(&socket_path))
+                    .map(|s| Box::new(BufReader::new(s)) as Box<dyn BufRead>)
+            }
+            #[cfg(not(unix))]
+            SourceKind::Unix(_) => unreachable!(),
+        };
+
+        let reader = match opened {
+            Ok(r) => r,
+            Err(e) => {
+                let _ = tx.send(IngestMsg::IoError(format!("{}: {}", stats.name, e)));
+                stats.set_status(SourceStatus::Failed(e.to_string()));
+                return;
+            }
+        };
+        stats.set_status(SourceStatus::Connected);
+
+        match read_lines(reader, &stats, &tx, &opts) {
+            Ok(()) => stats.set_status(SourceStatus::Closed),
+            Err(e) => {
+                let _ = tx.send(IngestMsg::IoError(format!("{}: {}", stats.name, e)));
+                stats.set_status(SourceStatus::Failed(e.to_string()));
+            }
+        }
+    });
+
+    Ok(())
+}
 
-    #[cfg(unix)]
-    {
-        use std::os::unix::net::UnixStream;
-
-        let path = socket_path.to_string();
-        std::thread::spawn(move || {
-            let stream = match UnixStream::connect(&path) {
-                Ok(s) => s,
-                Err(e) => {
-                    let _ = tx.send(IngestMsg::IoError(format!("connect {} failed: {}", path, e)));
-                    return;
+fn read_lines(
+    mut reader: Box<dyn BufRead>,
+    stats: &SourceStats,
+    tx: &Sender<IngestMsg>,
+    opts: &ReaderOptions,
+) -> io::Result<()> {
+    let mut line = String::new();
+    let mut line_no = 0u64;
+    let mut offset = 0u64;
+    loop {
+        line.clear();
+        let n = reader.read_line(&mut line)?;
+        if n == 0 {
+            return Ok(());
+        }
+        line_no += 1;
+        let start = offset;
+        offset += n as u64;
+
+        if is_replay_marker(&line) {
+            let _ = tx.send(IngestMsg::ReplayDone);
+            continue;
+        }
+        match HawkFrame::parse_tsv_line_with(&line, &opts.parse) {
+            Ok(Some(parsed)) => {
+                let mut f = parsed.frame;
+                if !parsed.repairs.is_empty() {
+                    stats.repaired.fetch_add(1, Ordering::Relaxed);
+                    let note =
+                        RepairedLine::new(&stats.name, line_no, start, &line, parsed.repairs);
+                    let _ = tx.send(IngestMsg::Repaired(note));
                 }
-            };
-
-            let reader = std::io::BufReader::new(stream);
-            for line in reader.lines() {
-                match line {
-                    Ok(line) => match HawkFrame::parse_tsv_line(&line) {
-                        Ok(Some(f)) => {
-                            let _ = tx.send(IngestMsg::Frame(f));
-                        }
-                        Ok(None) => {}
-                        Err(e) => {
-                            let _ = tx.send(IngestMsg::ParseError(e.clone()));
-                            if strict {
-                                break;
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        let _ = tx.send(IngestMsg::IoError(e.to_string()));
-                        break;
+                stats.frames.fetch_add(1, Ordering::Relaxed);
+                // The connection name always wins; a producer cannot pose
+                // as another source.
+                if let Some(own) = f.kv.insert(SOURCE_KV.to_string(), stats.name.clone()) {
+                    if own != stats.name {
+                        f.kv.insert(CLAIMED_SOURCE_KV.to_string(), own);
                     }
                 }
+                let _ = tx.send(IngestMsg::Frame(f));
             }
-        });
+            Ok(None) => {}
+            Err(e) => {
+                stats.parse_errors.fetch_add(1, Ordering::Relaxed);
+                let rejected = RejectedLine::new(&stats.name, line_no, start, &line, e);
+                if let Some(q) = &opts.quarantine {
+                    if let Err(err) = q.write(&rejected, &line) {
+                        let _ = tx.send(IngestMsg::IoError(format!(
+                            "quarantine {:?}: {}",
+                            q.path, err
+                        )));
+                    }
+                }
+                let _ = tx.send(IngestMsg::ParseError(rejected));
+                if opts.strict {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+// :: ∎
+
+// ▛▞// replay marker :: hawk.io.replay
+// ⫸ [hawkd.replay.done]
+fn is_replay_marker(line: &str) -> bool {
+    line.trim_end_matches(['\n', '\r']) == REPLAY_DONE_MARKER
+}
+// :: ∎
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_source_uris() {
+        let p = |s: &str| SourceSpec::parse(s).unwrap();
+        assert_eq!(p("stdin").kind, SourceKind::Stdin);
+        assert_eq!(p("unix").kind, SourceKind::Unix(None));
+        let u = p("unix:/run/hawk/hawk.sock");
+        assert_eq!(u.kind, SourceKind::Unix(Some("/run/hawk/hawk.sock".into())));
+        assert_eq!(u.name, "hawk.sock");
+        let t = p("edge=tcp://10.0.0.5:7400");
+        assert_eq!(
+            (t.name.as_str(), t.kind),
+            ("edge", SourceKind::Tcp("10.0.0.5:7400".into()))
+        );
+        assert_eq!(p("file:/var/log/x.tsv").name, "x.tsv");
+        assert!(SourceSpec::parse("tcp://nohost").is_err());
+        assert!(SourceSpec::parse("http://x").is_err());
+        assert!(SourceSpec::parse("file:").is_err());
+    }
+
+    #[test]
+    fn source_tag_overrides_the_producers_own() {
+        let input = "2026-02-16T00:00:00Z\tHEALTH\ta\t1\tok\tm\t\n\
+                     2026-02-16T00:00:00Z\tHEALTH\tb\t2\tok\tm\tsource=edge-7\n";
+        let (tx, rx) = std::sync::mpsc::channel();
+        let reader: Box<dyn BufRead> = Box::new(io::Cursor::new(input));
+        read_lines(reader, &SourceStats::new("spine"), &tx, &ReaderOptions::default()).unwrap();
+        drop(tx);
 
-        Ok(())
+        let tags: Vec<(String, Option<String>)> = rx
+            .iter()
+            .filter_map(|m| match m {
+                IngestMsg::Frame(f) => Some((
+                    f.kv[SOURCE_KV].clone(),
+                    f.kv.get(CLAIMED_SOURCE_KV).cloned(),
+                )),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            tags,
+            [
+                ("spine".to_string(), None),
+                ("spine".to_string(), Some("edge-7".to_string()))
+            ]
+        );
     }
 }
 // :: ∎
@@ -1,20 +1,31 @@
 // ▛▞// hawk cli main :: hawk.main
 // @ctx ⫸ [pack.transform.tui]
+mod ack;
 mod app;
 mod awk;
+mod capture;
+mod clock;
+mod config;
 mod doctor;
+mod fixtures;
+mod install;
 mod io;
 mod packs;
+mod pipeline;
+mod sandbox;
+mod signing;
+mod status;
 mod ui;
 
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Parser, Subcommand};
 
-use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use app::App;
-use hawk_core::HawkFrame;
-use io::{spawn_stdin_reader, IngestMsg};
+use io::{IngestMsg, SourceSpec, SourceStats};
 
 #[derive(Debug, Clone, Parser)]
 #[command(name = "hawk", version, about = "Hawk TUI: event-driven health mirror for agents and services")]
@@ -22,11 +33,13 @@ struct Cli {
     #[command(subcommand)]
     cmd: Option<Command>,
 
-    /// Source of HawkFrame TSV lines.
-    #[arg(long, value_enum, default_value = "stdin")]
-    source: Source,
+    /// Source of HawkFrame TSV lines, repeatable:
+    /// stdin, unix:<path>, file:<path>, tcp://host:port.
+    /// Prefix name= to set the tag, e.g. spine=unix:/run/hawk/hawk.sock
+    #[arg(long = "source", value_parser = SourceSpec::parse, default_value = "stdin")]
+    sources: Vec<SourceSpec>,
 
-    /// Unix socket path when source=unix.
+    /// Unix socket path for a bare `--source unix`.
     #[arg(long, default_value = "/tmp/hawk.sock")]
     socket_path: String,
 
@@ -34,10 +47,23 @@ struct Cli {
     #[arg(long, default_value_t = false)]
     strict: bool,
 
-    /// Max events kept in the right-side tail.
+    /// Line parsing: strict (exactly 7 columns), lenient (drop extra
+    /// columns), repair (also 6 columns, epoch timestamps, space separated).
+    #[arg(long, default_value = "lenient", value_parser = hawk_core::ParseMode::parse)]
+    parse_mode: hawk_core::ParseMode,
+
+    /// Append rejected lines (with a `#` origin/reason comment) to this file.
+    #[arg(long)]
+    quarantine: Option<PathBuf>,
+
+    /// Max events shown in the right-side tail.
     #[arg(long, default_value_t = 200)]
     tail_size: usize,
 
+    /// Events kept for tail scrollback (at least tail_size).
+    #[arg(long, default_value_t = 5000)]
+    history_size: usize,
+
     /// Default stale TTL in seconds.
     #[arg(long, default_value_t = 10)]
     stale_s: i64,
@@ -46,30 +72,124 @@ struct Cli {
     #[arg(long, default_value_t = 30)]
     dead_s: i64,
 
+    /// Forget entities unseen for this many seconds. 0 keeps them forever.
+    /// Frames can override with kv ttl_forget_s.
+    #[arg(long, default_value_t = 0)]
+    forget_s: i64,
+
+    /// Per-scope forget TTL, repeatable: --forget docker=3600 --forget grpc=86400
+    #[arg(long = "forget", value_parser = parse_scope_ttl)]
+    forget: Vec<(String, i64)>,
+
     /// Packs directory. Community adds packs here.
     #[arg(long, default_value = "./packs")]
     packs_dir: PathBuf,
 
+    /// Dir of trusted ed25519 pack keys (`<name>.pub`, hex).
+    /// Default $XDG_CONFIG_HOME/hawk/trusted_keys.
+    #[arg(long)]
+    trusted_keys: Option<PathBuf>,
+
+    /// Refuse packs without a trusted signature and a sha256 per thread.
+    #[arg(long)]
+    require_signed: bool,
+
     /// Transform selection:
     /// thread:<id> loads from packs
     /// file:<path> loads direct awk file
+    /// Comma separated stages run as a pipeline: thread:systemd_focus,thread:fail_only
     #[arg(long, default_value = "none")]
     transform: String,
 
     /// Transform variables passed as -v key=value to awk.
     /// Repeatable: --tvar scope=grpc --tvar window_s=5
+    /// Prefix with a stage to scope it in a pipeline: --tvar fail_only.scope=grpc
     #[arg(long = "tvar", value_parser = parse_kv)]
     tvars: Vec<(String, String)>,
-}
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
-enum Source {
-    Stdin,
-    Unix,
+    /// Stop restarting a crashing awk transform after this many crashes in
+    /// a row and pass raw frames through instead. Default restarts forever.
+    #[arg(long)]
+    awk_passthrough_after: Option<u32>,
+
+    /// What runs awk transforms: embedded (in-process interpreter for a
+    /// safe awk subset) or external (the system awk, full language, no
+    /// isolation).
+    #[arg(long, value_enum, default_value_t = awk::AwkBackend::Embedded)]
+    awk_backend: awk::AwkBackend,
+
+    /// Where transform output goes, overriding the thread's `output`:
+    /// replace (raw frames hidden), merge (raw frames plus output tagged
+    /// origin=transform), panel (raw frames in the table, output in the
+    /// derived panel).
+    #[arg(long, value_parser = packs::ThreadOutput::parse)]
+    transform_output: Option<packs::ThreadOutput>,
+
+    /// Acks file (ACK frames as TSV). Default $XDG_STATE_HOME/hawk/acks.tsv.
+    /// none disables persistence.
+    #[arg(long)]
+    acks_file: Option<String>,
+
+    /// hawkd ingest socket to publish acks to, so other mirrors see them.
+    #[arg(long)]
+    ack_publish: Option<PathBuf>,
+
+    /// UI config file. Default searches $XDG_CONFIG_HOME/hawk/hawk.toml.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Theme name: default|operator|calm|aggressive or a [themes.<name>] from config.
+    #[arg(long)]
+    theme: Option<String>,
+
+    /// Entity table columns, comma separated.
+    /// Names: scope,id,kind,level,state,age,msg,kv:<key>
+    #[arg(long, value_delimiter = ',')]
+    columns: Option<Vec<String>>,
+
+    /// Entity table width in percent; the event tail gets the rest.
+    #[arg(long)]
+    split: Option<u16>,
+
+    /// UI refresh interval in milliseconds.
+    #[arg(long)]
+    tick_ms: Option<u64>,
 }
 
 #[derive(Debug, Subcommand, Clone)]
 enum Command {
+    /// Run the mirror while recording everything it ingests to a gzip'd capture.
+    Record {
+        /// Capture file to write.
+        out: PathBuf,
+
+        /// Record without the TUI until the source ends.
+        #[arg(long, default_value_t = false)]
+        headless: bool,
+    },
+
+    /// Replay a capture into the mirror on its own timeline.
+    Replay {
+        /// Capture file written by `hawk record`.
+        file: PathBuf,
+
+        /// Playback speed, e.g. 1x, 10x, 0.5x.
+        #[arg(long, default_value = "1x", value_parser = capture::parse_speed)]
+        speed: f64,
+    },
+
+    /// One-shot entity report: collect, print, exit 2 on fail/dead.
+    Status {
+        /// Max seconds to collect. Stops early at source EOF or when the
+        /// hawkd replay completes.
+        #[arg(long, default_value_t = 5)]
+        wait_s: u64,
+
+        /// Output format.
+        #[arg(long, value_enum, default_value = "text")]
+        format: status::StatusFormat,
+    },
+
     /// Pack management commands.
     Pack {
         #[command(subcommand)]
@@ -79,7 +199,7 @@ enum Command {
     /// Validate packs, security scan awk, optional smoke test.
     #[command(name = "pack-doctor")]
     PackDoctor {
-        /// Enable smoke tests (runs awk threads on sample input).
+        /// Enable smoke tests and pack fixtures (runs awk threads on sample input).
         #[arg(long, default_value_t = true)]
         smoke: bool,
 
@@ -99,7 +219,7 @@ enum PackCommand {
 
     /// Validate packs, security scan awk, optional smoke test.
     Doctor {
-        /// Enable smoke tests (runs awk threads on sample input).
+        /// Enable smoke tests and pack fixtures (runs awk threads on sample input).
         #[arg(long, default_value_t = true)]
         smoke: bool,
 
@@ -107,6 +227,51 @@ enum PackCommand {
         #[arg(long, default_value = "strict")]
         security: String,
     },
+
+    /// Install a pack from a dir, a .tar/.tar.gz, or a local git mirror.
+    /// The pack doctor runs before it is activated.
+    Install {
+        source: String,
+
+        /// Security mode for the doctor run: strict|warn|off
+        #[arg(long, default_value = "strict")]
+        security: String,
+    },
+
+    /// Replace an installed pack, from its recorded source unless given one.
+    Update {
+        pack_id: String,
+        source: Option<String>,
+
+        /// Security mode for the doctor run: strict|warn|off
+        #[arg(long, default_value = "strict")]
+        security: String,
+    },
+
+    /// Delete an installed pack and its lockfile entry.
+    Remove { pack_id: String },
+
+    /// Create an ed25519 signing key: <prefix>.key (secret) and <prefix>.pub.
+    Keygen { prefix: PathBuf },
+
+    /// Sign <pack_dir>/pack.toml into pack.toml.sig. Every thread needs a sha256.
+    Sign {
+        pack_dir: PathBuf,
+
+        #[arg(long)]
+        key: PathBuf,
+    },
+}
+
+fn parse_scope_ttl(s: &str) -> Result<(String, i64), String> {
+    let (scope, v) = parse_kv(s)?;
+    let n = v
+        .parse::<i64>()
+        .map_err(|_| format!("expected scope=seconds, got {}", s))?;
+    if n <= 0 {
+        return Err("forget ttl must be > 0".to_string());
+    }
+    Ok((scope, n))
 }
 
 fn parse_kv(s: &str) -> Result<(String, String), String> {
@@ -126,98 +291,187 @@ fn parse_kv(s: &str) -> Result<(String, String), String> {
 fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
-    // Pack subcommands do not run TUI.
+    // Subcommands do not run TUI.
     if let Some(cmd) = cli.cmd.clone() {
-        return run_pack_cmd(&cli, cmd);
+        return match cmd {
+            Command::Status { wait_s, format } => run_status(&cli, wait_s, format),
+            Command::Record { out, headless } => run_record(&cli, &out, headless),
+            Command::Replay { file, speed } => run_replay(&cli, &file, speed),
+            cmd => run_pack_cmd(&cli, cmd),
+        };
     }
 
-    let idx = packs::load_packs(&cli.packs_dir)?;
+    let ui_cfg = load_ui_config(&cli)?;
 
-    let (tx_source, rx_source) = std::sync::mpsc::channel::<IngestMsg>();
-    match cli.source {
-        Source::Stdin => spawn_stdin_reader(tx_source, cli.strict),
-        Source::Unix => {
-            io::spawn_unix_reader(tx_source, &cli.socket_path, cli.strict)?;
-        }
-    }
+    let ingest = spawn_ingest(&cli)?;
+    let mut app = build_app(&cli, true)?;
+    let rx_ui = ingest.attach(&mut app);
+    ui::run_tui(&mut app, rx_ui, &ui_cfg)?;
 
-    let (tx_ui, rx_ui) = std::sync::mpsc::channel::<IngestMsg>();
-    let transform = cli.transform.trim().to_string();
+    Ok(())
+}
+// :: ∎
 
-    if transform == "none" {
-        // No transform: pass source stream directly into UI.
-        std::thread::spawn(move || {
-            for msg in rx_source {
-                let _ = tx_ui.send(msg);
-            }
+// ▛▞// ingest pipeline :: hawk.main.ingest
+// @ctx ⫸ [source.transform.channel]
+/// Running ingest: the UI channel plus the stats the TUI shows about it.
+struct Ingest {
+    rx: Receiver<IngestMsg>,
+    sources: Vec<Arc<SourceStats>>,
+    transforms: Vec<Arc<awk::RunnerStats>>,
+    switch: pipeline::TransformSwitch,
+    packs: packs::PackIndex,
+    output: Option<packs::ThreadOutput>,
+}
+
+impl Ingest {
+    fn attach(self, app: &mut App) -> Receiver<IngestMsg> {
+        app.sources = self.sources;
+        app.transforms = self.transforms;
+        app.switch = Some(app::SwitchHandle {
+            switch: self.switch,
+            packs: self.packs,
+            output: self.output,
         });
-    } else {
-        let mut tvars: BTreeMap<String, String> = BTreeMap::new();
-        for (k, v) in &cli.tvars {
-            tvars.insert(k.clone(), v.clone());
-        }
+        self.rx
+    }
+}
 
-        let (mode, target) = parse_transform(&transform)?;
-        let script_path = match mode.as_str() {
-            "thread" => {
-                let th = packs::resolve_thread(&idx, &target)
-                    .ok_or_else(|| anyhow::anyhow!("thread not found: {}", target))?;
+/// Start the source readers and optional awk transform. The returned channel
+/// disconnects once every source ends and the transform has drained.
+fn spawn_ingest(cli: &Cli) -> anyhow::Result<Ingest> {
+    let idx = load_packs(cli)?;
+    let chain = pipeline::parse_chain(&cli.transform)?;
+    let stages = if chain.is_empty() {
+        Vec::new()
+    } else {
+        pipeline::resolve(&idx, &chain, &cli.tvars)?
+    };
 
-                let mut merged = packs::default_tvars(&th.args);
-                for (k, v) in &tvars {
-                    merged.insert(k.clone(), v.clone());
-                }
+    let (tx_source, rx_source) = std::sync::mpsc::channel::<IngestMsg>();
+    let quarantine = match &cli.quarantine {
+        Some(p) => Some(Arc::new(io::Quarantine::open(p)?)),
+        None => None,
+    };
+    let opts = io::ReaderOptions {
+        strict: cli.strict,
+        parse: hawk_core::ParseOptions {
+            mode: cli.parse_mode,
+        },
+        quarantine,
+    };
+    let sources = io::spawn_sources(&cli.sources, &cli.socket_path, tx_source, &opts)?;
 
-                packs::validate_tvars(&th.args, &merged)
-                    .map_err(anyhow::Error::msg)?;
+    let (tx_ui, rx_ui) = std::sync::mpsc::channel::<IngestMsg>();
+    let supervise = awk::SuperviseOptions {
+        backend: cli.awk_backend,
+        passthrough_after: cli.awk_passthrough_after,
+        ..awk::SuperviseOptions::default()
+    };
+    let switch = pipeline::TransformSwitch::start(rx_source, tx_ui, supervise);
+    let output = cli
+        .transform_output
+        .unwrap_or_else(|| pipeline::output_mode(&stages));
+    let transforms = switch.swap(stages, output)?;
+
+    Ok(Ingest {
+        rx: rx_ui,
+        sources,
+        transforms,
+        switch,
+        packs: idx,
+        output: cli.transform_output,
+    })
+}
 
-                tvars = merged;
-                th.script_path
-            }
-            "file" => PathBuf::from(target),
-            _ => return Err(anyhow::anyhow!("unknown transform mode")),
-        };
+fn load_ui_config(cli: &Cli) -> anyhow::Result<config::UiConfig> {
+    config::load_ui_config(
+        cli.config.as_deref(),
+        &config::UiOverrides {
+            theme: cli.theme.clone(),
+            columns: cli.columns.clone(),
+            split_pct: cli.split,
+            tick_ms: cli.tick_ms,
+        },
+    )
+}
 
-        let (tx_frames_raw, rx_frames_raw) = std::sync::mpsc::channel::<HawkFrame>();
-        let (tx_frames_out, rx_frames_out) = std::sync::mpsc::channel::<HawkFrame>();
-        let (tx_err, rx_err) = std::sync::mpsc::channel::<String>();
+/// `persist_acks` is for the live TUI only; headless and replay runs must
+/// not rewrite the user's acks file.
+fn build_app(cli: &Cli, persist_acks: bool) -> anyhow::Result<App> {
+    let acks_path = match cli.acks_file.as_deref() {
+        Some("none") => None,
+        Some(p) => Some(PathBuf::from(p)),
+        None => ack::default_acks_path(),
+    };
 
-        let tx_ui_ingest = tx_ui.clone();
-        std::thread::spawn(move || {
-            for msg in rx_source {
-                match msg {
-                    IngestMsg::Frame(f) => {
-                        let _ = tx_frames_raw.send(f);
-                    }
-                    other => {
-                        let _ = tx_ui_ingest.send(other);
-                    }
-                }
-            }
-        });
+    let mut app = App::new(cli.tail_size, cli.history_size, cli.stale_s, cli.dead_s);
+    app.forget_s = cli.forget_s;
+    app.forget_by_scope = cli.forget.iter().cloned().collect();
+    app.acks = ack::AckStore::load(acks_path, cli.ack_publish.clone())?;
+    app.acks.persist = persist_acks;
+    Ok(app)
+}
+// :: ∎
 
-        let runner = awk::AwkRunner::spawn(script_path, &tvars)?;
-        runner.start_bridge(rx_frames_raw, tx_frames_out, tx_err);
+// ▛▞// capture commands :: hawk.capture.cmd
+// ⫸ [record.tee.replay.player]
+fn run_record(cli: &Cli, out: &std::path::Path, headless: bool) -> anyhow::Result<()> {
+    let mut ingest = spawn_ingest(cli)?;
+    let rec = Arc::new(Mutex::new(Some(capture::Recorder::create(out)?)));
+    ingest.rx = capture::spawn_tee(ingest.rx, Arc::clone(&rec));
 
-        let tx_ui_frames = tx_ui.clone();
-        std::thread::spawn(move || {
-            for frame in rx_frames_out {
-                let _ = tx_ui_frames.send(IngestMsg::Frame(frame));
-            }
-        });
+    if headless {
+        // Drain until the source ends; the tee writes as it forwards.
+        for _ in ingest.rx {}
+    } else {
+        let ui_cfg = load_ui_config(cli)?;
+        let mut app = build_app(cli, true)?;
+        let rx_ui = ingest.attach(&mut app);
+        ui::run_tui(&mut app, rx_ui, &ui_cfg)?;
+    }
 
-        let tx_ui_err = tx_ui.clone();
-        std::thread::spawn(move || {
-            for err in rx_err {
-                eprintln!("{}", err);
-                let _ = tx_ui_err.send(IngestMsg::IoError(format!("awk: {}", err)));
-            }
-        });
+    let taken = rec.lock().ok().and_then(|mut r| r.take());
+    if let Some(r) = taken {
+        r.finish()?;
     }
+    Ok(())
+}
 
-    let mut app = App::new(cli.tail_size, cli.stale_s, cli.dead_s);
-    ui::run_tui(&mut app, rx_ui)?;
+fn run_replay(cli: &Cli, file: &std::path::Path, speed: f64) -> anyhow::Result<()> {
+    let cap = capture::read_capture(file)?;
+    let ui_cfg = load_ui_config(cli)?;
 
+    let (tx, rx) = std::sync::mpsc::channel::<IngestMsg>();
+    let mut app = build_app(cli, false)?;
+    let handle = capture::spawn_player(cap, speed, tx);
+    app.clock = Arc::new(handle.clone());
+    app.replay = Some(handle);
+    ui::run_tui(&mut app, rx, &ui_cfg)?;
+    Ok(())
+}
+// :: ∎
+
+// ▛▞// status command :: hawk.status.cmd
+// ⫸ [collect.render.exit]
+fn run_status(cli: &Cli, wait_s: u64, format: status::StatusFormat) -> anyhow::Result<()> {
+    let ingest = spawn_ingest(cli)?;
+    let mut app = build_app(cli, false)?;
+    let rx = ingest.attach(&mut app);
+
+    // Each spine connection ends its replay with a marker; wait for all of them.
+    let spines = cli
+        .sources
+        .iter()
+        .filter(|s| matches!(s.kind, io::SourceKind::Unix(_) | io::SourceKind::Tcp(_)))
+        .count();
+    status::collect(&mut app, &rx, Duration::from_secs(wait_s), spines.max(1));
+    print!("{}", status::render(&app, format));
+
+    let code = status::exit_code(&app);
+    if code != 0 {
+        std::process::exit(code);
+    }
     Ok(())
 }
 // :: ∎
@@ -227,18 +481,49 @@ fn main() -> anyhow::Result<()> {
 fn run_pack_cmd(cli: &Cli, cmd: Command) -> anyhow::Result<()> {
     match cmd {
         Command::Pack { cmd } => {
-            let idx = packs::load_packs(&cli.packs_dir)?;
+            if let Some(out) = run_pack_install_cmd(cli, &cmd)? {
+                for w in &out.warnings {
+                    eprintln!("WARN\t{}", w);
+                }
+                let verb = match cmd {
+                    PackCommand::Install { .. } => "installed",
+                    PackCommand::Update { .. } => "updated",
+                    _ => "removed",
+                };
+                match out.previous {
+                    Some(prev) => println!("OK\t{} {} {} -> {} at {:?}", verb, out.id, prev, out.version, out.dir),
+                    None => println!("OK\t{} {} {} at {:?}", verb, out.id, out.version, out.dir),
+                }
+                return Ok(());
+            }
+
+            // Signing works on a pack dir, not on the loaded tree.
+            match &cmd {
+                PackCommand::Keygen { prefix } => {
+                    let (key, public) = signing::generate_key(prefix)?;
+                    println!("OK\tsecret {:?}, public {:?}", key, public);
+                    return Ok(());
+                }
+                PackCommand::Sign { pack_dir, key } => {
+                    let pack = packs::parse_pack_manifest(pack_dir, &pack_dir.join("pack.toml"))?;
+                    let sig = signing::sign_pack(&pack, key)?;
+                    println!("OK\tsigned {} {} at {:?}", pack.id, pack.version, sig);
+                    return Ok(());
+                }
+                _ => {}
+            }
+
+            let idx = load_packs(cli)?;
             match cmd {
                 PackCommand::List => {
-                    let mut rows: Vec<_> = idx.threads.iter().collect();
-                    rows.sort_by(|a, b| a.0.cmp(b.0));
-                    for (id, th) in rows {
-                        println!("{}\t{}\t{}\t{}", id, th.pack_id, th.kind, th.title);
+                    let mut rows: Vec<_> = idx.threads.values().collect();
+                    rows.sort_by(|a, b| (&a.thread_id, &a.pack_id).cmp(&(&b.thread_id, &b.pack_id)));
+                    for th in rows {
+                        println!("{}\t{}\t{}\t{}", th.thread_id, th.pack_id, th.kind, th.title);
                     }
                 }
                 PackCommand::Show { thread_id } => {
-                    let th = packs::resolve_thread(&idx, &thread_id)
-                        .ok_or_else(|| anyhow::anyhow!("thread not found: {}", thread_id))?;
+                    let th = packs::resolve_thread(&idx, &thread_id).map_err(anyhow::Error::msg)?;
 
                     println!("id: {}", th.thread_id);
                     println!("pack: {}", th.pack_id);
@@ -246,29 +531,60 @@ fn run_pack_cmd(cli: &Cli, cmd: Command) -> anyhow::Result<()> {
                     println!("title: {}", th.title);
                     println!("desc: {}", th.description);
                     println!("script: {:?}", th.script_path);
+                    println!("output: {}", th.output.as_str());
+                    println!("sandbox: {}", th.sandbox.describe());
+                    if let Some(pack) = idx.packs.iter().find(|p| p.id == th.pack_id) {
+                        println!("pack name: {} by {}", pack.name, pack.author);
+                        if !pack.description.is_empty() {
+                            println!("pack desc: {}", pack.description);
+                        }
+                        println!("pack version: {} (schema {})", pack.version, pack.schema_version);
+                        if !pack.license.is_empty() {
+                            println!("license: {}", pack.license);
+                        }
+                        if let Some(v) = &pack.min_hawk {
+                            println!("min hawk: {}", v);
+                        }
+                        for (id, req) in &pack.dependencies {
+                            println!("depends: {} {}", id, req);
+                        }
+                        println!("signed: {}", pack.signed_by.as_deref().unwrap_or("no"));
+                    }
+                    if let Some(tick) = th.tick {
+                        println!("tick: every {}s", tick.as_secs());
+                    }
 
                     if th.args.is_empty() {
                         println!("args: none");
                     } else {
                         println!("args:");
                         for a in &th.args {
-                            let ty = match a.ty {
-                                packs::ArgType::String => "string",
-                                packs::ArgType::Int => "int",
-                                packs::ArgType::Bool => "bool",
-                            };
-                            println!("  - {} ({}) default={} :: {}", a.name, ty, a.default, a.help);
+                            if a.required {
+                                println!("  - {} ({}) :: {}", a.name, a.describe(), a.help);
+                            } else {
+                                println!("  - {} ({}) default={} :: {}", a.name, a.describe(), a.default, a.help);
+                            }
                         }
                     }
                 }
                 PackCommand::Doctor { smoke, security } => {
                     run_doctor(cli, smoke, &security)?;
                 }
+                PackCommand::Keygen { .. }
+                | PackCommand::Sign { .. }
+                | PackCommand::Install { .. }
+                | PackCommand::Update { .. }
+                | PackCommand::Remove { .. } => {
+                    unreachable!("handled above")
+                }
             }
         }
         Command::PackDoctor { smoke, security } => {
             run_doctor(cli, smoke, &security)?;
         }
+        Command::Status { .. } | Command::Record { .. } | Command::Replay { .. } => {
+            unreachable!("dispatched in main")
+        }
     }
 
     Ok(())
@@ -278,23 +594,7 @@ fn run_pack_cmd(cli: &Cli, cmd: Command) -> anyhow::Result<()> {
 // ▛▞// doctor entry :: hawk.pack.doctor
 // ⫸ [security.smoke.exit]
 fn run_doctor(cli: &Cli, smoke: bool, security: &str) -> anyhow::Result<()> {
-    let sec_mode = match security {
-        "strict" => doctor::SecurityMode::Strict,
-        "warn" => doctor::SecurityMode::Warn,
-        "off" => doctor::SecurityMode::Off,
-        other => {
-            return Err(anyhow::anyhow!(
-                "unknown security mode '{}', expected strict|warn|off",
-                other
-            ));
-        }
-    };
-
-    let opts = doctor::DoctorOptions {
-        smoke,
-        security: sec_mode,
-    };
-
+    let opts = doctor_options(cli, smoke, security)?;
     let rep = doctor::run_pack_doctor(&cli.packs_dir, opts);
 
     for w in &rep.warnings {
@@ -311,29 +611,56 @@ fn run_doctor(cli: &Cli, smoke: bool, security: &str) -> anyhow::Result<()> {
         std::process::exit(2);
     }
 }
-// :: ∎
 
-// ▛▞// parse_transform :: hawk.transform
-// ⫸ [transform.parse.mode]
-fn parse_transform(s: &str) -> Result<(String, String), anyhow::Error> {
-    if let Some(rest) = s.strip_prefix("thread:") {
-        let t = rest.trim().to_string();
-        if t.is_empty() {
-            return Err(anyhow::anyhow!("empty thread id"));
+fn doctor_options(cli: &Cli, smoke: bool, security: &str) -> anyhow::Result<doctor::DoctorOptions> {
+    let sec_mode = match security {
+        "strict" => doctor::SecurityMode::Strict,
+        "warn" => doctor::SecurityMode::Warn,
+        "off" => doctor::SecurityMode::Off,
+        other => {
+            return Err(anyhow::anyhow!(
+                "unknown security mode '{}', expected strict|warn|off",
+                other
+            ));
         }
-        return Ok(("thread".to_string(), t));
-    }
+    };
 
-    if let Some(rest) = s.strip_prefix("file:") {
-        let p = rest.trim().to_string();
-        if p.is_empty() {
-            return Err(anyhow::anyhow!("empty file path"));
-        }
-        return Ok(("file".to_string(), p));
+    Ok(doctor::DoctorOptions {
+        smoke,
+        security: sec_mode,
+        backend: cli.awk_backend,
+        trust: trust_policy(cli)?,
+    })
+}
+
+fn trust_policy(cli: &Cli) -> anyhow::Result<signing::TrustPolicy> {
+    signing::TrustPolicy::load(cli.trusted_keys.as_deref(), cli.require_signed)
+}
+
+/// Load the packs dir, warning on stderr about packs loaded with caveats.
+fn load_packs(cli: &Cli) -> anyhow::Result<packs::PackIndex> {
+    let idx = packs::load_packs(&cli.packs_dir, &trust_policy(cli)?)?;
+    for w in &idx.warnings {
+        eprintln!("WARN\t{}", w);
     }
+    Ok(idx)
+}
 
-    Err(anyhow::anyhow!(
-        "transform must be none, thread:<id>, or file:<path>"
-    ))
+/// install/update/remove; `None` for the read-only pack commands.
+fn run_pack_install_cmd(cli: &Cli, cmd: &PackCommand) -> anyhow::Result<Option<install::Outcome>> {
+    let out = match cmd {
+        PackCommand::Install { source, security } => {
+            install::install(&cli.packs_dir, source, doctor_options(cli, true, security)?)?
+        }
+        PackCommand::Update { pack_id, source, security } => install::update(
+            &cli.packs_dir,
+            pack_id,
+            source.as_deref(),
+            doctor_options(cli, true, security)?,
+        )?,
+        PackCommand::Remove { pack_id } => install::remove(&cli.packs_dir, pack_id)?,
+        _ => return Ok(None),
+    };
+    Ok(Some(out))
 }
 // :: ∎
@@ -3,10 +3,15 @@
 use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::Context;
+use semver::{Version, VersionReq};
 use serde::Deserialize;
 
+use crate::sandbox::{SandboxMode, SandboxPolicy};
+use crate::signing::{self, Signature, TrustPolicy};
+
 // :: ∎
 
 // ▛▞// public model :: hawk.packs.model
@@ -14,18 +19,35 @@ use serde::Deserialize;
 #[derive(Debug, Clone)]
 pub struct PackIndex {
     pub packs: Vec<Pack>,
+    /// Keyed by `pack_id/thread_id`; see `resolve_thread` for bare ids.
     pub threads: HashMap<String, ThreadResolved>,
+    /// Loaded anyway, but worth telling the user about.
+    pub warnings: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Pack {
+    /// Manifest schema; 1 when `schema_version` is absent.
+    pub schema_version: u32,
     pub id: String,
     pub name: String,
     pub version: String,
     pub author: String,
+    pub license: String,
     pub description: String,
+    /// Oldest hawk that can run this pack.
+    pub min_hawk: Option<Version>,
+    /// Other packs this one needs, by pack id.
+    pub dependencies: BTreeMap<String, VersionReq>,
     pub root_dir: PathBuf,
     pub threads: Vec<Thread>,
+    /// How the external awk backend jails this pack's threads.
+    pub sandbox: SandboxPolicy,
+    /// Trusted key that signed `pack.toml`, set by `load_packs`.
+    pub signed_by: Option<String>,
+    /// The `pack.toml` text this was parsed from; the signature is checked
+    /// against it, not a second read of the file.
+    pub manifest: String,
 }
 
 #[derive(Debug, Clone)]
@@ -36,21 +58,209 @@ pub struct Thread {
     pub file: String,
     pub description: String,
     pub args: Vec<ArgSpec>,
+    pub output: ThreadOutput,
+    /// Interval for synthetic TICK frames, when the thread declares `needs_tick`.
+    pub tick: Option<Duration>,
+    /// Declared hex sha256 of `file`; checked on parse.
+    pub sha256: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+/// Where a thread's output goes relative to the raw stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThreadOutput {
+    /// Output replaces the raw stream.
+    #[default]
+    Replace,
+    /// Raw frames reach the table too; output joins them tagged `origin=transform`.
+    Merge,
+    /// Raw frames feed the table; output goes to the derived panel.
+    Panel,
+}
+
+impl ThreadOutput {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "replace" => Ok(ThreadOutput::Replace),
+            "merge" => Ok(ThreadOutput::Merge),
+            "panel" => Ok(ThreadOutput::Panel),
+            _ => Err(format!("unknown output '{}', expected replace|merge|panel", s)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThreadOutput::Replace => "replace",
+            ThreadOutput::Merge => "merge",
+            ThreadOutput::Panel => "panel",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct ArgSpec {
     pub name: String,
     pub ty: ArgType,
     pub default: String,
     pub help: String,
+    /// Allowed values of an `enum`, or of each `list` item.
+    pub choices: Vec<String>,
+    /// Bounds for int, float and duration (in seconds).
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// No default; every run has to set it.
+    pub required: bool,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum ArgType {
+    #[default]
     String,
     Int,
+    Float,
     Bool,
+    /// `10s`, `5m`, `2h`, `1d` or bare seconds; awk gets whole seconds.
+    Duration,
+    /// Checked by compiling it as the embedded interpreter will. The
+    /// external backend's system awk has its own ERE dialect, which may
+    /// still reject it.
+    Regex,
+    /// One of `choices`.
+    Enum,
+    /// Comma separated; awk gets it as is, for `split(v, a, ",")`.
+    List,
+}
+
+impl ArgType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArgType::String => "string",
+            ArgType::Int => "int",
+            ArgType::Float => "float",
+            ArgType::Bool => "bool",
+            ArgType::Duration => "duration",
+            ArgType::Regex => "regex",
+            ArgType::Enum => "enum",
+            ArgType::List => "list",
+        }
+    }
+
+    fn numeric(&self) -> bool {
+        matches!(self, ArgType::Int | ArgType::Float | ArgType::Duration)
+    }
+}
+
+impl ArgSpec {
+    /// Check one value against the type and constraints. Empty means unset.
+    pub fn check(&self, v: &str) -> Result<(), String> {
+        let v = v.trim();
+        if v.is_empty() {
+            return if self.required {
+                Err("is required".to_string())
+            } else {
+                Ok(())
+            };
+        }
+
+        let n = match self.ty {
+            ArgType::String => None,
+            ArgType::Int => Some(
+                v.parse::<i64>()
+                    .map_err(|_| format!("expects int, got {}", v))? as f64,
+            ),
+            ArgType::Float => Some(
+                v.parse::<f64>()
+                    .ok()
+                    .filter(|f| f.is_finite())
+                    .ok_or_else(|| format!("expects float, got {}", v))?,
+            ),
+            ArgType::Duration => Some(
+                duration_secs(v)
+                    .ok_or_else(|| format!("expects a duration like 30s, 5m or 1h, got {}", v))?
+                    as f64,
+            ),
+            ArgType::Bool => {
+                let vv = v.to_ascii_lowercase();
+                if vv != "true" && vv != "false" && vv != "1" && vv != "0" {
+                    return Err(format!("expects bool, got {}", v));
+                }
+                None
+            }
+            ArgType::Regex => {
+                crate::awk::lang::compile_regex(v).map_err(|e| format!("expects a regex: {}", e))?;
+                None
+            }
+            ArgType::Enum => {
+                if !self.choices.iter().any(|c| c == v) {
+                    return Err(format!("expects one of {}, got {}", self.choices.join("|"), v));
+                }
+                None
+            }
+            ArgType::List => {
+                for item in v.split(',').map(str::trim) {
+                    if item.is_empty() {
+                        return Err(format!("has an empty list item in {}", v));
+                    }
+                    if !self.choices.is_empty() && !self.choices.iter().any(|c| c == item) {
+                        return Err(format!(
+                            "items must be among {}, got {}",
+                            self.choices.join("|"),
+                            item
+                        ));
+                    }
+                }
+                None
+            }
+        };
+
+        if let Some(n) = n {
+            if let Some(min) = self.min.filter(|m| n < *m) {
+                return Err(format!("must be >= {}, got {}", self.bound(min), v));
+            }
+            if let Some(max) = self.max.filter(|m| n > *m) {
+                return Err(format!("must be <= {}, got {}", self.bound(max), v));
+            }
+        }
+        Ok(())
+    }
+
+    /// Type plus constraints, as `pack show` prints them: `enum ok|warn`,
+    /// `duration 1s..3600s`, `list of a|b, required`.
+    pub fn describe(&self) -> String {
+        let mut out = self.ty.as_str().to_string();
+        if !self.choices.is_empty() {
+            let sep = if self.ty == ArgType::List { " of " } else { " " };
+            out.push_str(sep);
+            out.push_str(&self.choices.join("|"));
+        }
+        if self.min.is_some() || self.max.is_some() {
+            let lo = self.min.map(|m| self.bound(m)).unwrap_or_default();
+            let hi = self.max.map(|m| self.bound(m)).unwrap_or_default();
+            out.push_str(&format!(" {}..{}", lo, hi));
+        }
+        if self.required {
+            out.push_str(", required");
+        }
+        out
+    }
+
+    fn bound(&self, n: f64) -> String {
+        match self.ty {
+            ArgType::Duration => format!("{}s", n),
+            _ => n.to_string(),
+        }
+    }
+
+    /// What awk sees for a checked value: durations become whole seconds.
+    fn awk_value(&self, v: &str) -> String {
+        match self.ty {
+            ArgType::Duration => duration_secs(v).map_or_else(|| v.to_string(), |s| s.to_string()),
+            _ => v.to_string(),
+        }
+    }
+}
+
+fn duration_secs(v: &str) -> Option<i64> {
+    crate::ack::parse_duration(v).map(|d| d.whole_seconds())
 }
 
 #[derive(Debug, Clone)]
@@ -62,26 +272,68 @@ pub struct ThreadResolved {
     pub description: String,
     pub script_path: PathBuf,
     pub args: Vec<ArgSpec>,
+    pub output: ThreadOutput,
+    pub tick: Option<Duration>,
+    pub sandbox: SandboxPolicy,
+    /// Declared digest of the script; the runner checks what it runs.
+    pub sha256: Option<String>,
+}
+
+impl ThreadResolved {
+    /// `pack_id/thread_id`, unique across packs.
+    pub fn qualified_id(&self) -> String {
+        format!("{}/{}", self.pack_id, self.thread_id)
+    }
 }
 // :: ∎
 
 // ▛▞// manifest types :: hawk.packs.manifest
 // @ctx ⫸ [path.safety.rules]
+/// Highest `schema_version` this hawk understands.
+pub const MANIFEST_SCHEMA: u32 = 2;
+
 #[derive(Debug, Deserialize)]
 struct Manifest {
     pack: ManifestPack,
     #[serde(default)]
     thread: Vec<ManifestThread>,
+    #[serde(default)]
+    sandbox: Option<ManifestSandbox>,
+    /// pack id -> semver range, e.g. `"hawk.core" = "^0.1"`.
+    #[serde(default)]
+    dependencies: BTreeMap<String, String>,
+    /// Keys serde does not know; schema 1 ignores them, schema 2 refuses.
+    #[serde(flatten)]
+    extra: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestSandbox {
+    /// off | best_effort | required
+    mode: String,
+    #[serde(default)]
+    cpu_s: Option<u64>,
+    #[serde(default)]
+    mem_mb: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ManifestPack {
+    #[serde(default)]
+    schema_version: Option<u32>,
     id: String,
     name: String,
     version: String,
     author: String,
     #[serde(default)]
+    license: String,
+    #[serde(default)]
     description: String,
+    /// Minimum hawk version, e.g. "0.1.0".
+    #[serde(default)]
+    min_hawk: Option<String>,
+    #[serde(flatten)]
+    extra: BTreeMap<String, toml::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -94,6 +346,17 @@ struct ManifestThread {
     description: String,
     #[serde(default)]
     arg: Vec<ManifestArg>,
+    /// replace (default) | merge | panel
+    #[serde(default)]
+    output: Option<String>,
+    /// Feed the script a TICK frame every `tick_s` seconds (default 1).
+    #[serde(default)]
+    needs_tick: bool,
+    #[serde(default)]
+    tick_s: Option<u64>,
+    /// Hex sha256 of `file`.
+    #[serde(default)]
+    sha256: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -105,24 +368,38 @@ struct ManifestArg {
     default: Option<toml::Value>,
     #[serde(default)]
     help: String,
+    #[serde(default)]
+    choices: Vec<String>,
+    /// Number, or a duration string for `duration` args.
+    #[serde(default)]
+    min: Option<toml::Value>,
+    #[serde(default)]
+    max: Option<toml::Value>,
+    #[serde(default)]
+    required: bool,
 }
 // :: ∎
 
 // ▛▞// load_packs :: hawk.packs.load
 // ⫸ [discover.parse.index]
-pub fn load_packs(packs_dir: &Path) -> anyhow::Result<PackIndex> {
+pub fn load_packs(packs_dir: &Path, trust: &TrustPolicy) -> anyhow::Result<PackIndex> {
     let mut packs: Vec<Pack> = Vec::new();
     let mut threads: HashMap<String, ThreadResolved> = HashMap::new();
+    let mut warnings = Vec::new();
 
     if !packs_dir.exists() {
-        return Ok(PackIndex { packs, threads });
+        return Ok(PackIndex {
+            packs,
+            threads,
+            warnings,
+        });
     }
 
     let mut dirs = Vec::new();
     for entry in fs::read_dir(packs_dir).with_context(|| format!("read packs dir {:?}", packs_dir))? {
         let entry = entry?;
         let path = entry.path();
-        if path.is_dir() {
+        if path.is_dir() && !is_hidden(&path) {
             dirs.push(path);
         }
     }
@@ -134,8 +411,21 @@ pub fn load_packs(packs_dir: &Path) -> anyhow::Result<PackIndex> {
             continue;
         }
 
-        let pack = parse_pack_manifest(&dir, &manifest_path)
+        let mut pack = parse_pack_manifest(&dir, &manifest_path)
             .with_context(|| format!("parse pack {:?}", manifest_path))?;
+        if let Some(other) = packs.iter().find(|p| p.id == pack.id) {
+            anyhow::bail!("pack id {} used by both {:?} and {:?}", pack.id, other.root_dir, dir);
+        }
+        pack.signed_by = match trust
+            .verify(&pack)
+            .map_err(|e| anyhow::anyhow!("refusing pack {:?}: {}", dir, e))?
+        {
+            Signature::Untrusted(why) => {
+                warnings.push(format!("{}; loading it as unsigned", why));
+                None
+            }
+            sig => sig.trusted_key(),
+        };
 
         for th in &pack.threads {
             let resolved = ThreadResolved {
@@ -146,26 +436,93 @@ pub fn load_packs(packs_dir: &Path) -> anyhow::Result<PackIndex> {
                 description: th.description.clone(),
                 script_path: pack.root_dir.join(&th.file),
                 args: th.args.clone(),
+                output: th.output,
+                tick: th.tick,
+                sandbox: pack.sandbox.clone(),
+                sha256: th.sha256.clone(),
             };
 
-            if threads.contains_key(&th.id) {
-                anyhow::bail!("thread id collision: {}", th.id);
+            // Bare ids may repeat across packs; resolve_thread sorts that out.
+            let key = resolved.qualified_id();
+            if threads.contains_key(&key) {
+                anyhow::bail!("thread id {} declared twice", key);
             }
 
-            threads.insert(th.id.clone(), resolved);
+            threads.insert(key, resolved);
         }
 
         packs.push(pack);
     }
 
-    Ok(PackIndex { packs, threads })
+    for pack in &packs {
+        check_dependencies(pack, &packs).map_err(|e| anyhow::anyhow!("pack {}: {}", pack.id, e))?;
+    }
+
+    Ok(PackIndex {
+        packs,
+        threads,
+        warnings,
+    })
+}
+
+/// Every `[dependencies]` entry must be present among `installed` at a
+/// matching version.
+pub fn check_dependencies(pack: &Pack, installed: &[Pack]) -> Result<(), String> {
+    for (id, req) in &pack.dependencies {
+        let dep = installed
+            .iter()
+            .find(|p| &p.id == id)
+            .ok_or_else(|| format!("depends on {} {}, which is not installed", id, req))?;
+        let ok = Version::parse(&dep.version).is_ok_and(|v| req.matches(&v));
+        if !ok {
+            return Err(format!(
+                "depends on {} {}, but {} is installed",
+                id, req, dep.version
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Dot dirs in packs_dir are install staging, never packs.
+pub fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.starts_with('.'))
 }
 // :: ∎
 
 // ▛▞// resolve_thread :: hawk.packs.resolve
 // ⫸ [thread.id.lookup]
-pub fn resolve_thread(idx: &PackIndex, thread_id: &str) -> Option<ThreadResolved> {
-    idx.threads.get(thread_id).cloned()
+/// `pack_id/thread_id` resolves exactly; a bare `thread_id` resolves when
+/// only one pack ships it.
+pub fn resolve_thread(idx: &PackIndex, thread_id: &str) -> Result<ThreadResolved, String> {
+    if thread_id.contains('/') {
+        return idx
+            .threads
+            .get(thread_id)
+            .cloned()
+            .ok_or_else(|| format!("thread not found: {}", thread_id));
+    }
+
+    let mut found: Vec<&ThreadResolved> = idx
+        .threads
+        .values()
+        .filter(|t| t.thread_id == thread_id)
+        .collect();
+    match found.len() {
+        0 => Err(format!("thread not found: {}", thread_id)),
+        1 => Ok(found[0].clone()),
+        _ => {
+            found.sort_by(|a, b| a.pack_id.cmp(&b.pack_id));
+            let names: Vec<String> = found.iter().map(|t| t.qualified_id()).collect();
+            Err(format!(
+                "thread id {} is ambiguous, use one of: {}",
+                thread_id,
+                names.join(", ")
+            ))
+        }
+    }
 }
 // :: ∎
 
@@ -179,24 +536,32 @@ pub fn validate_tvars(args: &[ArgSpec], tvars: &BTreeMap<String, String>) -> Res
 
     for (k, v) in tvars {
         let spec = allowed.get(k).ok_or_else(|| format!("unknown tvar: {}", k))?;
-        match spec.ty {
-            ArgType::String => {}
-            ArgType::Int => {
-                v.parse::<i64>()
-                    .map_err(|_| format!("tvar {} expects int, got {}", k, v))?;
-            }
-            ArgType::Bool => {
-                let vv = v.to_ascii_lowercase();
-                if vv != "true" && vv != "false" && vv != "1" && vv != "0" {
-                    return Err(format!("tvar {} expects bool, got {}", k, v));
-                }
-            }
+        spec.check(v).map_err(|e| format!("tvar {} {}", k, e))?;
+    }
+
+    for a in args.iter().filter(|a| a.required) {
+        if !tvars.contains_key(&a.name) {
+            return Err(format!("tvar {} is required", a.name));
         }
     }
 
     Ok(())
 }
 
+/// Values as handed to awk, after `validate_tvars`.
+pub fn awk_tvars(args: &[ArgSpec], tvars: BTreeMap<String, String>) -> BTreeMap<String, String> {
+    tvars
+        .into_iter()
+        .map(|(k, v)| {
+            let v = match args.iter().find(|a| a.name == k) {
+                Some(spec) => spec.awk_value(&v),
+                None => v,
+            };
+            (k, v)
+        })
+        .collect()
+}
+
 pub fn default_tvars(args: &[ArgSpec]) -> BTreeMap<String, String> {
     let mut out = BTreeMap::new();
     for a in args {
@@ -214,6 +579,54 @@ pub fn parse_pack_manifest(pack_dir: &Path, manifest_path: &Path) -> anyhow::Res
 
     let man: Manifest = toml::from_str(&raw)
         .with_context(|| "toml deserialize")?;
+    check_pack_id(&man.pack.id)?;
+
+    let schema_version = man.pack.schema_version.unwrap_or(1);
+    if schema_version == 0 || schema_version > MANIFEST_SCHEMA {
+        anyhow::bail!(
+            "pack {} uses manifest schema_version {}; this hawk reads 1..={}",
+            man.pack.id,
+            schema_version,
+            MANIFEST_SCHEMA
+        );
+    }
+    let v2 = schema_version >= 2;
+
+    if v2 {
+        let unknown: Vec<&String> = man.extra.keys().chain(man.pack.extra.keys()).collect();
+        if !unknown.is_empty() {
+            anyhow::bail!("unknown manifest keys: {:?}", unknown);
+        }
+        Version::parse(&man.pack.version)
+            .map_err(|e| anyhow::anyhow!("pack version {:?} is not semver: {}", man.pack.version, e))?;
+        if man.pack.license.trim().is_empty() {
+            anyhow::bail!("schema_version 2 requires a license (SPDX id, e.g. \"MIT\")");
+        }
+    } else if man.pack.min_hawk.is_some() || !man.dependencies.is_empty() {
+        anyhow::bail!("min_hawk and [dependencies] need schema_version = 2");
+    }
+
+    let min_hawk = match &man.pack.min_hawk {
+        Some(s) => {
+            let want = Version::parse(s).map_err(|e| anyhow::anyhow!("min_hawk {:?}: {}", s, e))?;
+            let have = hawk_version();
+            if have < want {
+                anyhow::bail!("pack {} needs hawk >= {}, this is {}", man.pack.id, want, have);
+            }
+            Some(want)
+        }
+        None => None,
+    };
+
+    let mut dependencies = BTreeMap::new();
+    for (id, req) in &man.dependencies {
+        if id == &man.pack.id {
+            anyhow::bail!("pack {} depends on itself", id);
+        }
+        let req = VersionReq::parse(req)
+            .map_err(|e| anyhow::anyhow!("dependency {} range {:?}: {}", id, req, e))?;
+        dependencies.insert(id.clone(), req);
+    }
 
     let mut threads: Vec<Thread> = Vec::new();
 
@@ -224,6 +637,10 @@ pub fn parse_pack_manifest(pack_dir: &Path, manifest_path: &Path) -> anyhow::Res
         if t.id.trim().is_empty() {
             anyhow::bail!("thread id cannot be empty");
         }
+        // `/` separates the pack in a qualified id.
+        if t.id.contains('/') {
+            anyhow::bail!("thread id {} cannot contain '/'", t.id);
+        }
 
         if t.kind.trim().is_empty() {
             anyhow::bail!("thread {} kind cannot be empty", t.id);
@@ -234,16 +651,39 @@ pub fn parse_pack_manifest(pack_dir: &Path, manifest_path: &Path) -> anyhow::Res
             anyhow::bail!("thread {} references missing file {:?}", t.id, script_path);
         }
 
+        if let Some(want) = &t.sha256 {
+            let raw = fs::read(&script_path).with_context(|| format!("read {:?}", script_path))?;
+            let got = signing::sha256_hex(&raw);
+            if !got.eq_ignore_ascii_case(want.trim()) {
+                anyhow::bail!(
+                    "thread {} file {} sha256 mismatch: manifest {}, file {}",
+                    t.id,
+                    t.file,
+                    want,
+                    got
+                );
+            }
+        }
+
         let mut args = Vec::new();
         for a in t.arg {
             let ty = parse_arg_type(&a.ty)
                 .map_err(|e| anyhow::anyhow!("thread {} arg {}: {}", t.id, a.name, e))?;
 
+            let min = arg_bound(ty, a.min)
+                .map_err(|e| anyhow::anyhow!("thread {} arg {} min: {}", t.id, a.name, e))?;
+            let max = arg_bound(ty, a.max)
+                .map_err(|e| anyhow::anyhow!("thread {} arg {} max: {}", t.id, a.name, e))?;
+
             let spec = ArgSpec {
                 name: a.name,
                 ty,
                 default: toml_value_to_string(a.default),
                 help: a.help,
+                choices: a.choices,
+                min,
+                max,
+                required: a.required,
             };
 
             validate_arg_default(&spec)
@@ -252,6 +692,18 @@ pub fn parse_pack_manifest(pack_dir: &Path, manifest_path: &Path) -> anyhow::Res
             args.push(spec);
         }
 
+        let output = match &t.output {
+            Some(o) => ThreadOutput::parse(o).map_err(|e| anyhow::anyhow!("thread {}: {}", t.id, e))?,
+            None => ThreadOutput::default(),
+        };
+
+        let tick = match (t.needs_tick, t.tick_s) {
+            (false, None) => None,
+            (false, Some(_)) => anyhow::bail!("thread {} sets tick_s without needs_tick", t.id),
+            (true, Some(0)) => anyhow::bail!("thread {} tick_s must be at least 1", t.id),
+            (true, s) => Some(Duration::from_secs(s.unwrap_or(1))),
+        };
+
         threads.push(Thread {
             id: t.id,
             title: t.title,
@@ -259,55 +711,107 @@ pub fn parse_pack_manifest(pack_dir: &Path, manifest_path: &Path) -> anyhow::Res
             file: t.file,
             description: t.description,
             args,
+            output,
+            tick,
+            sha256: t.sha256,
         });
     }
 
+    let sandbox = match man.sandbox {
+        Some(sb) => {
+            let defaults = SandboxPolicy::default();
+            SandboxPolicy {
+                mode: SandboxMode::parse(&sb.mode).map_err(|e| anyhow::anyhow!("sandbox: {}", e))?,
+                cpu_s: sb.cpu_s.unwrap_or(defaults.cpu_s),
+                mem_mb: sb.mem_mb.unwrap_or(defaults.mem_mb),
+            }
+        }
+        None => SandboxPolicy::default(),
+    };
+
     Ok(Pack {
+        schema_version,
         id: man.pack.id,
         name: man.pack.name,
         version: man.pack.version,
         author: man.pack.author,
+        license: man.pack.license,
         description: man.pack.description,
+        min_hawk,
+        dependencies,
         root_dir: pack_dir.to_path_buf(),
         threads,
+        sandbox,
+        signed_by: None,
+        manifest: raw,
     })
 }
+
+pub fn hawk_version() -> Version {
+    Version::parse(env!("CARGO_PKG_VERSION")).expect("crate version is semver")
+}
 // :: ∎
 
 fn parse_arg_type(s: &str) -> Result<ArgType, String> {
     match s.trim().to_ascii_lowercase().as_str() {
         "string" => Ok(ArgType::String),
         "int" => Ok(ArgType::Int),
+        "float" => Ok(ArgType::Float),
         "bool" => Ok(ArgType::Bool),
-        _ => Err(format!("unknown arg type: {}", s)),
+        "duration" => Ok(ArgType::Duration),
+        "regex" => Ok(ArgType::Regex),
+        "enum" => Ok(ArgType::Enum),
+        "list" => Ok(ArgType::List),
+        _ => Err(format!(
+            "unknown arg type: {} (string|int|float|bool|duration|regex|enum|list)",
+            s
+        )),
     }
 }
 
+fn arg_bound(ty: ArgType, v: Option<toml::Value>) -> Result<Option<f64>, String> {
+    let n = match v {
+        None => return Ok(None),
+        Some(_) if !ty.numeric() => {
+            return Err(format!("{} args take no bounds", ty.as_str()));
+        }
+        Some(toml::Value::Integer(i)) => i as f64,
+        Some(toml::Value::Float(f)) => f,
+        Some(toml::Value::String(s)) if ty == ArgType::Duration => {
+            duration_secs(&s).ok_or_else(|| format!("bad duration {}", s))? as f64
+        }
+        Some(other) => return Err(format!("expects a number, got {}", other)),
+    };
+    Ok(Some(n))
+}
+
 fn validate_arg_default(spec: &ArgSpec) -> Result<(), String> {
+    if spec.required && !spec.default.is_empty() {
+        return Err("required args take no default".to_string());
+    }
     match spec.ty {
-        ArgType::String => Ok(()),
-        ArgType::Int => {
-            if spec.default.trim().is_empty() {
-                Ok(())
-            } else {
-                spec.default
-                    .parse::<i64>()
-                    .map(|_| ())
-                    .map_err(|_| format!("default expects int, got {}", spec.default))
-            }
+        ArgType::Enum if spec.choices.is_empty() => {
+            return Err("enum args need choices".to_string());
         }
-        ArgType::Bool => {
-            let vv = spec.default.to_ascii_lowercase();
-            if vv.is_empty() || vv == "true" || vv == "false" || vv == "1" || vv == "0" {
-                Ok(())
-            } else {
-                Err(format!("default expects bool, got {}", spec.default))
-            }
+        ArgType::Enum | ArgType::List => {}
+        _ if !spec.choices.is_empty() => {
+            return Err(format!("{} args take no choices; use enum", spec.ty.as_str()));
+        }
+        _ => {}
+    }
+    if let (Some(min), Some(max)) = (spec.min, spec.max) {
+        if min > max {
+            return Err(format!("min {} is above max {}", min, max));
         }
     }
+    if spec.default.is_empty() {
+        return Ok(());
+    }
+    spec.check(&spec.default)
+        .map_err(|e| format!("default {}", e))
 }
 
-fn toml_value_to_string(v: Option<toml::Value>) -> String {
+pub fn toml_value_to_string(v: Option<toml::Value>) -> String {
     match v {
         Some(toml::Value::String(s)) => s,
         Some(toml::Value::Integer(i)) => i.to_string(),
@@ -318,6 +822,22 @@ fn toml_value_to_string(v: Option<toml::Value>) -> String {
     }
 }
 
+/// Pack ids become directory names and prefix `pack_id/thread_id`.
+fn check_pack_id(id: &str) -> anyhow::Result<()> {
+    let ok = !id.is_empty()
+        && !id.starts_with('.')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-".contains(c));
+    if !ok {
+        anyhow::bail!(
+            "pack id '{}' must be [A-Za-z0-9._-] and not start with '.'",
+            id
+        );
+    }
+    Ok(())
+}
+
 fn ensure_safe_rel_path(file: &str) -> Result<(), String> {
     let p = Path::new(file);
 
@@ -333,3 +853,227 @@ fn ensure_safe_rel_path(file: &str) -> Result<(), String> {
 
     Ok(())
 }
+
+/// `pack.toml` fixtures for tests across the crate.
+#[cfg(test)]
+pub mod testkit {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// A minimal valid manifest: every thread is a filter over `t.awk`.
+    pub struct Manifest {
+        id: String,
+        version: String,
+        pack_lines: Vec<String>,
+        threads: Vec<(String, Vec<String>)>,
+        tail: String,
+        script: String,
+    }
+
+    impl Manifest {
+        pub fn new(id: &str) -> Self {
+            Self {
+                id: id.to_string(),
+                version: "0.1.0".to_string(),
+                pack_lines: Vec::new(),
+                threads: Vec::new(),
+                tail: String::new(),
+                script: "{ print }\n".to_string(),
+            }
+        }
+
+        pub fn version(mut self, v: &str) -> Self {
+            self.version = v.to_string();
+            self
+        }
+
+        /// Extra lines in the `[pack]` table.
+        pub fn pack_line(mut self, line: &str) -> Self {
+            self.pack_lines.push(line.to_string());
+            self
+        }
+
+        pub fn thread(mut self, id: &str) -> Self {
+            self.threads.push((id.to_string(), Vec::new()));
+            self
+        }
+
+        /// Extra lines (or `[[thread.arg]]` tables) for the last thread.
+        pub fn thread_line(mut self, line: &str) -> Self {
+            let (_, lines) = self.threads.last_mut().expect("thread_line before thread");
+            lines.push(line.to_string());
+            self
+        }
+
+        /// Tables after the threads, e.g. `[dependencies]`.
+        pub fn tail(mut self, text: &str) -> Self {
+            self.tail = text.to_string();
+            self
+        }
+
+        /// Contents of `t.awk`.
+        pub fn script(mut self, src: &str) -> Self {
+            self.script = src.to_string();
+            self
+        }
+
+        pub fn render(&self) -> String {
+            let mut out = format!(
+                "[pack]\nid = \"{0}\"\nname = \"{0}\"\nversion = \"{1}\"\nauthor = \"t\"\n",
+                self.id, self.version
+            );
+            for line in &self.pack_lines {
+                out.push_str(line);
+                out.push('\n');
+            }
+            for (id, lines) in &self.threads {
+                out.push_str(&format!(
+                    "\n[[thread]]\nid = \"{}\"\ntitle = \"t\"\nkind = \"filter\"\nfile = \"t.awk\"\n",
+                    id
+                ));
+                for line in lines {
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+            if !self.tail.is_empty() {
+                out.push('\n');
+                out.push_str(&self.tail);
+                out.push('\n');
+            }
+            out
+        }
+
+        /// Write `pack.toml` and `t.awk` into `dir`; returns the manifest path.
+        pub fn write(&self, dir: &Path) -> PathBuf {
+            fs::create_dir_all(dir).unwrap();
+            fs::write(dir.join("t.awk"), &self.script).unwrap();
+            let manifest = dir.join("pack.toml");
+            fs::write(&manifest, self.render()).unwrap();
+            manifest
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testkit::Manifest;
+    use super::*;
+
+    fn parse(id: &str, pack_extra: &str, tail: &str) -> anyhow::Result<Pack> {
+        let dir = std::env::temp_dir().join(format!("hawk-manifest-{}-{}", std::process::id(), id));
+        let manifest = Manifest::new(id)
+            .version("0.2.1")
+            .pack_line(pack_extra)
+            .tail(tail)
+            .write(&dir);
+        let out = parse_pack_manifest(&dir, &manifest);
+        let _ = fs::remove_dir_all(dir);
+        out
+    }
+
+    #[test]
+    fn schema_v2_is_strict_and_dependencies_resolve_by_semver() {
+        // Schema 1 keeps ignoring unknown keys but cannot declare v2 fields.
+        assert!(parse("v1", "homepage = \"x\"", "").is_ok());
+        let err = parse("v1dep", "", "[dependencies]\nbase = \"^0.2\"").unwrap_err();
+        assert!(err.to_string().contains("schema_version = 2"));
+
+        let v2 = "schema_version = 2\nlicense = \"MIT\"";
+        let err = parse("typo", &format!("{v2}\nlicence = \"MIT\""), "").unwrap_err();
+        assert!(err.to_string().contains("licence"), "{}", err);
+        let err = parse("future", "schema_version = 3", "").unwrap_err();
+        assert!(err.to_string().contains("reads 1..=2"), "{}", err);
+        let err = parse("newer", &format!("{v2}\nmin_hawk = \"999.0.0\""), "").unwrap_err();
+        assert!(err.to_string().contains("needs hawk >= 999.0.0"), "{}", err);
+
+        let base = parse("base", v2, "").unwrap();
+        let app = parse("app", v2, "[dependencies]\nbase = \">=0.2, <0.3\"").unwrap();
+        assert_eq!(check_dependencies(&app, std::slice::from_ref(&base)), Ok(()));
+        assert!(check_dependencies(&app, &[]).unwrap_err().contains("not installed"));
+        let old = Pack {
+            version: "0.1.9".to_string(),
+            ..base
+        };
+        assert!(check_dependencies(&app, &[old]).unwrap_err().contains("0.1.9 is installed"));
+    }
+
+    #[test]
+    fn arg_types_enforce_constraints_and_awk_gets_seconds() {
+        let with_arg = |id: &str, arg: &str| {
+            let dir = std::env::temp_dir().join(format!("hawk-args-{}-{}", std::process::id(), id));
+            let manifest = Manifest::new("p")
+                .thread("t")
+                .thread_line(&format!("[[thread.arg]]\n{arg}"))
+                .write(&dir);
+            let out = parse_pack_manifest(&dir, &manifest).map(|p| p.threads[0].args.clone());
+            let _ = fs::remove_dir_all(dir);
+            out
+        };
+
+        // Bad defaults and contradictory specs fail at load.
+        for (id, arg) in [
+            ("re", "name = \"m\"\ntype = \"regex\"\ndefault = \"(\""),
+            ("enum", "name = \"m\"\ntype = \"enum\""),
+            ("req", "name = \"m\"\ntype = \"int\"\nrequired = true\ndefault = 1"),
+            ("bound", "name = \"m\"\ntype = \"string\"\nmin = 1"),
+            ("range", "name = \"m\"\ntype = \"float\"\ndefault = 2.5\nmax = 1.0"),
+        ] {
+            assert!(with_arg(id, arg).is_err(), "{} should not load", id);
+        }
+
+        let args = [
+            with_arg("lvl", "name = \"lvl\"\ntype = \"enum\"\nchoices = [\"warn\", \"fail\"]\nrequired = true").unwrap(),
+            with_arg("win", "name = \"win\"\ntype = \"duration\"\ndefault = \"5m\"\nmin = \"1s\"\nmax = \"1h\"").unwrap(),
+            with_arg("tags", "name = \"tags\"\ntype = \"list\"\nchoices = [\"a\", \"b\"]").unwrap(),
+        ]
+        .concat();
+        assert_eq!(args[0].describe(), "enum warn|fail, required");
+        assert_eq!(args[1].describe(), "duration 1s..3600s");
+
+        let run = |pairs: &[(&str, &str)]| {
+            let mut tv = default_tvars(&args);
+            tv.extend(pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+            validate_tvars(&args, &tv).map(|_| awk_tvars(&args, tv))
+        };
+        assert_eq!(run(&[]).unwrap_err(), "tvar lvl is required");
+        assert!(run(&[("lvl", "info")]).unwrap_err().contains("one of warn|fail"));
+        assert!(run(&[("lvl", "warn"), ("win", "2h")]).unwrap_err().contains("<= 3600s"));
+        assert!(run(&[("lvl", "warn"), ("tags", "a,c")]).unwrap_err().contains("got c"));
+        let ok = run(&[("lvl", "fail"), ("tags", "b,a")]).unwrap();
+        assert_eq!(ok["win"], "300");
+        assert_eq!(ok["tags"], "b,a");
+    }
+
+    #[test]
+    fn bare_thread_ids_resolve_until_two_packs_share_them() {
+        let root = std::env::temp_dir().join(format!("hawk-resolve-{}", std::process::id()));
+        for (pack, threads) in [("a.pack", ["echo", "only_a"]), ("b.pack", ["echo", "only_b"])] {
+            threads
+                .into_iter()
+                .fold(Manifest::new(pack), Manifest::thread)
+                .write(&root.join(pack));
+        }
+
+        let idx = load_packs(&root, &TrustPolicy::default()).unwrap();
+        assert_eq!(resolve_thread(&idx, "only_b").unwrap().pack_id, "b.pack");
+        assert_eq!(resolve_thread(&idx, "b.pack/echo").unwrap().pack_id, "b.pack");
+        assert_eq!(
+            resolve_thread(&idx, "echo").unwrap_err(),
+            "thread id echo is ambiguous, use one of: a.pack/echo, b.pack/echo"
+        );
+        assert!(resolve_thread(&idx, "a.pack/only_b").is_err());
+
+        Manifest::new("c.pack").thread("x/echo").write(&root.join("c.pack"));
+        let err = load_packs(&root, &TrustPolicy::default()).unwrap_err();
+        assert!(format!("{:#}", err).contains("cannot contain '/'"), "{:#}", err);
+        fs::remove_dir_all(root.join("c.pack")).unwrap();
+        for id in ["c/pack", ""] {
+            Manifest::new(id).thread("x").write(&root.join("c.pack"));
+            let err = load_packs(&root, &TrustPolicy::default()).unwrap_err();
+            assert!(format!("{:#}", err).contains("must be [A-Za-z0-9._-]"), "{:#}", err);
+        }
+        let _ = fs::remove_dir_all(root);
+    }
+}
+// :: ∎
@@ -0,0 +1,549 @@
+// ▛▞// hawk transform pipeline :: hawk.pipeline
+// @ctx ⫸ [chain.stage.tvars]
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use hawk_core::HawkFrame;
+
+use crate::awk::{AwkRunner, RunnerStats, SuperviseOptions};
+use crate::io::{IngestMsg, ORIGIN_KV};
+use crate::packs::{self, PackIndex, ThreadOutput};
+use crate::sandbox::SandboxPolicy;
+
+// :: ∎
+
+// ▛▞// stage refs :: hawk.pipeline.parse
+// ⫸ [thread.file.chain]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StageRef {
+    Thread(String),
+    File(PathBuf),
+}
+
+impl StageRef {
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        if let Some(rest) = s.strip_prefix("thread:") {
+            let t = rest.trim();
+            if t.is_empty() {
+                anyhow::bail!("empty thread id");
+            }
+            return Ok(StageRef::Thread(t.to_string()));
+        }
+
+        if let Some(rest) = s.strip_prefix("file:") {
+            let p = rest.trim();
+            if p.is_empty() {
+                anyhow::bail!("empty file path");
+            }
+            return Ok(StageRef::File(PathBuf::from(p)));
+        }
+
+        anyhow::bail!(
+            "transform must be none, thread:<id>, or file:<path>, got '{}'",
+            s
+        )
+    }
+
+    /// Namespace for `--tvar <key>.<var>=value`: the thread id without its
+    /// pack, or the file stem.
+    pub fn key(&self) -> String {
+        match self {
+            StageRef::Thread(id) => id.rsplit('/').next().unwrap_or(id).to_string(),
+            StageRef::File(p) => p
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            StageRef::Thread(id) => format!("thread:{}", id),
+            StageRef::File(p) => format!("file:{}", p.display()),
+        }
+    }
+}
+
+/// `none`, or comma separated stages run left to right:
+/// `thread:systemd_focus,thread:fail_only`.
+pub fn parse_chain(s: &str) -> anyhow::Result<Vec<StageRef>> {
+    let s = s.trim();
+    if s.is_empty() || s == "none" {
+        return Ok(Vec::new());
+    }
+    s.split(',').map(StageRef::parse).collect()
+}
+// :: ∎
+
+// ▛▞// resolve :: hawk.pipeline.resolve
+// @ctx ⫸ [defaults.namespace.validate]
+#[derive(Debug, Clone)]
+pub struct Stage {
+    pub name: String,
+    pub script_path: PathBuf,
+    pub tvars: BTreeMap<String, String>,
+    /// From the thread's `output`; file stages replace.
+    pub output: ThreadOutput,
+    /// From the thread's `needs_tick`; file stages get no ticks.
+    pub tick: Option<Duration>,
+    /// From the thread's pack; file stages run unjailed.
+    pub sandbox: SandboxPolicy,
+    /// From the thread's manifest; file stages have none.
+    pub sha256: Option<String>,
+}
+
+/// Resolve scripts and route tvars. `stage.var=value` goes to that stage only;
+/// `stage` is its `key` or, for threads, `pack_id/thread_id`. A bare
+/// `var=value` goes to the only stage, or in a chain to every stage that
+/// declares it (file stages take all bare vars).
+pub fn resolve(
+    idx: &PackIndex,
+    chain: &[StageRef],
+    tvars: &[(String, String)],
+) -> anyhow::Result<Vec<Stage>> {
+    let names: Vec<[String; 2]> = chain
+        .iter()
+        .map(|stage| {
+            let qualified = match stage {
+                StageRef::Thread(id) => packs::resolve_thread(idx, id)
+                    .map(|th| th.qualified_id())
+                    .unwrap_or_else(|_| id.clone()),
+                StageRef::File(_) => stage.key(),
+            };
+            [stage.key(), qualified]
+        })
+        .collect();
+
+    let mut scoped: Vec<BTreeMap<String, String>> = vec![BTreeMap::new(); chain.len()];
+    let mut bare: Vec<(&str, &str)> = Vec::new();
+    for (k, v) in tvars {
+        match k.rsplit_once('.') {
+            Some((ns, var)) => {
+                let hits: Vec<usize> = (0..chain.len())
+                    .filter(|&i| names[i].iter().any(|n| n == ns))
+                    .collect();
+                let i = match hits[..] {
+                    [i] => i,
+                    [] => anyhow::bail!("tvar {}: no stage named {}", k, ns),
+                    _ => anyhow::bail!(
+                        "tvar {}: {} stages are named {}; ambiguous, qualify it as <pack_id>/<thread_id>.{}",
+                        k,
+                        hits.len(),
+                        ns,
+                        var
+                    ),
+                };
+                scoped[i].insert(var.to_string(), v.clone());
+            }
+            None => bare.push((k, v)),
+        }
+    }
+
+    let mut stages = Vec::new();
+    let mut taken = vec![false; bare.len()];
+    for (i, stage) in chain.iter().enumerate() {
+        let (script_path, args, output, tick, sandbox, sha256) = match stage {
+            StageRef::Thread(id) => {
+                let th = packs::resolve_thread(idx, id).map_err(anyhow::Error::msg)?;
+                (th.script_path, Some(th.args), th.output, th.tick, th.sandbox, th.sha256)
+            }
+            StageRef::File(p) => (
+                p.clone(),
+                None,
+                ThreadOutput::Replace,
+                None,
+                SandboxPolicy::default(),
+                None,
+            ),
+        };
+
+        let mut merged = args
+            .as_deref()
+            .map(packs::default_tvars)
+            .unwrap_or_default();
+        for (j, (k, v)) in bare.iter().enumerate() {
+            let declared = args
+                .as_deref()
+                .is_none_or(|a| a.iter().any(|spec| spec.name == *k));
+            if chain.len() == 1 || declared {
+                merged.insert(k.to_string(), v.to_string());
+                taken[j] = true;
+            }
+        }
+        merged.extend(std::mem::take(&mut scoped[i]));
+
+        if let Some(args) = &args {
+            packs::validate_tvars(args, &merged)
+                .map_err(|e| anyhow::anyhow!("{}: {}", stage.label(), e))?;
+            merged = packs::awk_tvars(args, merged);
+        }
+
+        stages.push(Stage {
+            name: stage.label(),
+            script_path,
+            tvars: merged,
+            output,
+            tick,
+            sandbox,
+            sha256,
+        });
+    }
+
+    if let Some(j) = taken.iter().position(|t| !t) {
+        anyhow::bail!(
+            "tvar {} matches no stage; use <stage>.{}=value",
+            bare[j].0,
+            bare[j].0
+        );
+    }
+
+    Ok(stages)
+}
+// :: ∎
+
+/// The chain's output routing is the last stage's.
+pub fn output_mode(stages: &[Stage]) -> ThreadOutput {
+    stages.last().map(|s| s.output).unwrap_or_default()
+}
+
+// ▛▞// spawn :: hawk.pipeline.spawn
+// ⫸ [runner.chain.channel]
+/// Start one supervised runner per stage, each feeding the next. Returns
+/// per-stage stats in chain order.
+pub fn spawn(
+    stages: Vec<Stage>,
+    in_rx: Receiver<HawkFrame>,
+    out_tx: Sender<HawkFrame>,
+    err_tx: Sender<String>,
+    opts: &SuperviseOptions,
+) -> anyhow::Result<Vec<Arc<RunnerStats>>> {
+    let mut runners = Vec::new();
+    for st in stages {
+        runners.push(AwkRunner::spawn(
+            st.script_path,
+            &st.tvars,
+            &st.name,
+            SuperviseOptions {
+                tick: st.tick,
+                sandbox: st.sandbox,
+                sha256: st.sha256,
+                ..opts.clone()
+            },
+        )?);
+    }
+
+    let stats: Vec<Arc<RunnerStats>> = runners.iter().map(AwkRunner::stats).collect();
+    let last = runners.len().saturating_sub(1);
+    let mut rx = in_rx;
+    for (i, runner) in runners.into_iter().enumerate() {
+        if i == last {
+            runner.start_bridge(rx, out_tx, err_tx);
+            break;
+        }
+        let (tx_next, rx_next) = std::sync::mpsc::channel::<HawkFrame>();
+        runner.start_bridge(rx, tx_next, err_tx.clone());
+        rx = rx_next;
+    }
+    Ok(stats)
+}
+// :: ∎
+
+// ▛▞// switch :: hawk.pipeline.switch
+// @ctx ⫸ [route.swap.drain]
+/// Routes source frames through the current chain. The chain can be swapped
+/// at runtime; sources and the UI channel stay up across swaps.
+#[derive(Clone)]
+pub struct TransformSwitch {
+    inner: Arc<Mutex<Route>>,
+    opts: SuperviseOptions,
+}
+
+impl std::fmt::Debug for TransformSwitch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransformSwitch")
+            .field("label", &self.label())
+            .finish()
+    }
+}
+
+struct Route {
+    /// None once every source has ended; no further swaps.
+    tx_ui: Option<Sender<IngestMsg>>,
+    err_tx: Option<Sender<String>>,
+    /// Head of the running chain. None passes frames straight through.
+    input: Option<Sender<HawkFrame>>,
+    output: ThreadOutput,
+    stats: Vec<Arc<RunnerStats>>,
+    label: String,
+}
+
+impl TransformSwitch {
+    /// Start the router between `rx_source` and `tx_ui` with no chain.
+    pub fn start(
+        rx_source: Receiver<IngestMsg>,
+        tx_ui: Sender<IngestMsg>,
+        opts: SuperviseOptions,
+    ) -> Self {
+        let (err_tx, err_rx) = std::sync::mpsc::channel::<String>();
+        let tx_ui_err = tx_ui.clone();
+        std::thread::spawn(move || {
+            for err in err_rx {
+                let _ = tx_ui_err.send(IngestMsg::IoError(format!("awk: {}", err)));
+            }
+        });
+
+        let switch = TransformSwitch {
+            inner: Arc::new(Mutex::new(Route {
+                tx_ui: Some(tx_ui),
+                err_tx: Some(err_tx),
+                input: None,
+                output: ThreadOutput::Replace,
+                stats: Vec::new(),
+                label: "none".to_string(),
+            })),
+            opts,
+        };
+
+        let inner = switch.inner.clone();
+        std::thread::spawn(move || {
+            for msg in rx_source {
+                let route = inner.lock().unwrap_or_else(|e| e.into_inner());
+                let Some(tx_ui) = &route.tx_ui else { break };
+                match (msg, &route.input) {
+                    (IngestMsg::Frame(f), Some(input)) => {
+                        if route.output != ThreadOutput::Replace {
+                            let _ = tx_ui.send(IngestMsg::Frame(f.clone()));
+                        }
+                        let _ = input.send(f);
+                    }
+                    (other, _) => {
+                        let _ = tx_ui.send(other);
+                    }
+                }
+            }
+            // Sources ended: drop our ends so the chain drains and the UI
+            // channel disconnects.
+            let mut route = inner.lock().unwrap_or_else(|e| e.into_inner());
+            route.input = None;
+            route.tx_ui = None;
+            route.err_tx = None;
+        });
+
+        switch
+    }
+
+    /// Replace the running chain. The old chain sees its input close and
+    /// drains. An empty chain passes frames through. On error the old chain
+    /// keeps running.
+    pub fn swap(
+        &self,
+        stages: Vec<Stage>,
+        output: ThreadOutput,
+    ) -> anyhow::Result<Vec<Arc<RunnerStats>>> {
+        let mut route = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let (Some(tx_ui), Some(err_tx)) = (route.tx_ui.clone(), route.err_tx.clone()) else {
+            anyhow::bail!("sources have ended");
+        };
+
+        let label = if stages.is_empty() {
+            "none".to_string()
+        } else {
+            stages
+                .iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        if stages.is_empty() {
+            route.input = None;
+            route.output = ThreadOutput::Replace;
+            route.stats = Vec::new();
+            route.label = label;
+            return Ok(Vec::new());
+        }
+
+        let (tx_in, rx_in) = std::sync::mpsc::channel::<HawkFrame>();
+        let (tx_out, rx_out) = std::sync::mpsc::channel::<HawkFrame>();
+        let stats = spawn(stages, rx_in, tx_out, err_tx, &self.opts)?;
+
+        std::thread::spawn(move || {
+            for mut frame in rx_out {
+                let msg = match output {
+                    ThreadOutput::Replace => IngestMsg::Frame(frame),
+                    ThreadOutput::Merge => {
+                        frame
+                            .kv
+                            .insert(ORIGIN_KV.to_string(), "transform".to_string());
+                        IngestMsg::Frame(frame)
+                    }
+                    ThreadOutput::Panel => IngestMsg::Derived(frame),
+                };
+                if tx_ui.send(msg).is_err() {
+                    break;
+                }
+            }
+        });
+
+        route.input = Some(tx_in);
+        route.output = output;
+        route.stats = stats.clone();
+        route.label = label;
+        Ok(stats)
+    }
+
+    /// Label of the running chain, `none` when passing through.
+    pub fn label(&self) -> String {
+        let route = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        route.label.clone()
+    }
+}
+// :: ∎
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::{ArgSpec, ArgType, ThreadResolved};
+    use std::collections::HashMap;
+
+    fn index() -> PackIndex {
+        let thread = |id: &str, args: &[&str], output| ThreadResolved {
+            pack_id: "hawk.core".to_string(),
+            thread_id: id.to_string(),
+            title: id.to_string(),
+            kind: "filter".to_string(),
+            description: String::new(),
+            script_path: PathBuf::from(format!("{}.awk", id)),
+            args: args
+                .iter()
+                .map(|a| ArgSpec {
+                    name: a.to_string(),
+                    ty: ArgType::String,
+                    ..ArgSpec::default()
+                })
+                .collect(),
+            output,
+            tick: None,
+            sandbox: Default::default(),
+            sha256: None,
+        };
+        let mut threads = HashMap::new();
+        threads.insert(
+            "focus".to_string(),
+            thread("focus", &["unit"], ThreadOutput::Replace),
+        );
+        threads.insert(
+            "fail_only".to_string(),
+            thread("fail_only", &["scope"], ThreadOutput::Panel),
+        );
+        PackIndex {
+            packs: Vec::new(),
+            threads,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn kv(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_chains() {
+        assert!(parse_chain("none").unwrap().is_empty());
+        let chain = parse_chain("thread:focus, file:./x/lift.awk").unwrap();
+        assert_eq!(chain[0], StageRef::Thread("focus".to_string()));
+        assert_eq!(chain[1].key(), "lift");
+        assert!(parse_chain("thread:focus,bogus").is_err());
+    }
+
+    #[test]
+    fn routes_tvars_per_stage() {
+        let idx = index();
+        let chain = parse_chain("thread:focus,thread:fail_only").unwrap();
+
+        let stages = resolve(
+            &idx,
+            &chain,
+            &kv(&[("scope", "grpc"), ("focus.unit", "hawkd.service")]),
+        )
+        .unwrap();
+        assert_eq!(stages[0].tvars["unit"], "hawkd.service");
+        assert!(!stages[0].tvars.contains_key("scope"));
+        assert_eq!(stages[1].tvars["scope"], "grpc");
+        assert_eq!(output_mode(&stages), ThreadOutput::Panel);
+
+        assert!(resolve(&idx, &chain, &kv(&[("window_s", "5")])).is_err());
+        assert!(resolve(&idx, &chain, &kv(&[("nope.scope", "x")])).is_err());
+        assert!(resolve(&idx, &chain, &kv(&[("fail_only.unit", "x")])).is_err());
+
+        let stages = resolve(&idx, &chain, &kv(&[("hawk.core/fail_only.scope", "x")])).unwrap();
+        assert_eq!(stages[1].tvars["scope"], "x");
+        let twice = parse_chain("thread:focus,thread:hawk.core/focus").unwrap();
+        let err = resolve(&idx, &twice, &kv(&[("focus.unit", "x")])).unwrap_err();
+        assert!(err.to_string().contains("ambiguous, qualify it"), "{}", err);
+    }
+
+    #[test]
+    fn swap_keeps_sources_flowing() {
+        use std::time::Duration;
+
+        let path = std::env::temp_dir().join(format!("hawk-swap-{}.awk", std::process::id()));
+        std::fs::write(&path, "{ print }\n").unwrap();
+        let frame = |id: &str| HawkFrame {
+            ts: None,
+            kind: "HEALTH".to_string(),
+            scope: "svc".to_string(),
+            id: id.to_string(),
+            level: hawk_core::Level::Ok,
+            level_raw: None,
+            msg: String::new(),
+            kv: BTreeMap::new(),
+        };
+
+        let (tx_source, rx_source) = std::sync::mpsc::channel();
+        let (tx_ui, rx_ui) = std::sync::mpsc::channel();
+        let switch = TransformSwitch::start(rx_source, tx_ui, SuperviseOptions::default());
+        let recv = || match rx_ui.recv_timeout(Duration::from_secs(5)).unwrap() {
+            IngestMsg::Frame(f) => (f.id, f.kv.contains_key(ORIGIN_KV)),
+            _ => panic!("expected a frame"),
+        };
+
+        tx_source.send(IngestMsg::Frame(frame("a"))).unwrap();
+        assert_eq!(recv(), ("a".to_string(), false));
+
+        let stage = Stage {
+            name: "file:echo".to_string(),
+            script_path: path.clone(),
+            tvars: BTreeMap::new(),
+            output: ThreadOutput::Merge,
+            tick: None,
+            sandbox: SandboxPolicy::default(),
+            sha256: None,
+        };
+        assert_eq!(switch.swap(vec![stage], ThreadOutput::Merge).unwrap().len(), 1);
+        tx_source.send(IngestMsg::Frame(frame("b"))).unwrap();
+        let mut got = vec![recv(), recv()];
+        got.sort();
+        assert_eq!(got, [("b".to_string(), false), ("b".to_string(), true)]);
+
+        switch.swap(Vec::new(), ThreadOutput::Replace).unwrap();
+        assert_eq!(switch.label(), "none");
+        tx_source.send(IngestMsg::Frame(frame("c"))).unwrap();
+        assert_eq!(recv(), ("c".to_string(), false));
+
+        drop(tx_source);
+        // Sources ended and the old chain drained: the UI channel closes.
+        assert!(matches!(
+            rx_ui.recv_timeout(Duration::from_secs(5)),
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected)
+        ));
+        let _ = std::fs::remove_file(&path);
+    }
+}
+// :: ∎
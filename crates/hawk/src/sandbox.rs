@@ -0,0 +1,615 @@
+// ▛▞// awk sandbox :: hawk.sandbox
+// @ctx ⫸ [namespace.seccomp.rlimit]
+//! Jail for the external awk backend. Inside it awk sees an empty read-only
+//! root holding only its binary, the shared library dirs and the script, has
+//! no network and no environment, cannot fork or open sockets (seccomp), and
+//! runs under CPU, memory and file-size rlimits.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+// :: ∎
+
+// ▛▞// policy :: hawk.sandbox.policy
+// ⫸ [mode.limits]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SandboxMode {
+    /// Plain child process.
+    #[default]
+    Off,
+    /// Full jail when the kernel allows user namespaces, else seccomp and
+    /// rlimits only.
+    BestEffort,
+    /// Refuse to start the thread without the full jail.
+    Required,
+}
+
+impl SandboxMode {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "off" => Ok(SandboxMode::Off),
+            "best_effort" => Ok(SandboxMode::BestEffort),
+            "required" => Ok(SandboxMode::Required),
+            _ => Err(format!(
+                "unknown sandbox mode '{}', expected off|best_effort|required",
+                s
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SandboxMode::Off => "off",
+            SandboxMode::BestEffort => "best_effort",
+            SandboxMode::Required => "required",
+        }
+    }
+}
+
+/// A pack's `[sandbox]` table. Limits of 0 mean unlimited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxPolicy {
+    pub mode: SandboxMode,
+    /// RLIMIT_CPU, cumulative over the child's life; the supervisor restarts
+    /// a child killed for it.
+    pub cpu_s: u64,
+    /// RLIMIT_AS.
+    pub mem_mb: u64,
+}
+
+impl Default for SandboxPolicy {
+    fn default() -> Self {
+        Self {
+            mode: SandboxMode::Off,
+            cpu_s: 60,
+            mem_mb: 256,
+        }
+    }
+}
+
+impl SandboxPolicy {
+    pub fn describe(&self) -> String {
+        match self.mode {
+            SandboxMode::Off => "off".to_string(),
+            mode => format!(
+                "{} cpu_s={} mem_mb={}",
+                mode.as_str(),
+                self.cpu_s,
+                self.mem_mb
+            ),
+        }
+    }
+}
+// :: ∎
+
+// ▛▞// jail :: hawk.sandbox.jail
+// ⫸ [prepare.command.degrade]
+/// Prepared once per runner; every (re)spawn reuses it.
+pub struct Jail {
+    program: PathBuf,
+    script: PathBuf,
+    note: Option<String>,
+    #[cfg(target_os = "linux")]
+    plan: linux::Plan,
+}
+
+impl std::fmt::Debug for Jail {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Jail")
+            .field("program", &self.program)
+            .field("script", &self.script)
+            .field("note", &self.note)
+            .finish()
+    }
+}
+
+impl Jail {
+    /// None when the policy is off. Fails when the policy is required and
+    /// the jail cannot be built here.
+    pub fn prepare(policy: &SandboxPolicy, script: &Path) -> anyhow::Result<Option<Arc<Jail>>> {
+        if policy.mode == SandboxMode::Off {
+            return Ok(None);
+        }
+        let awk = find_awk()?;
+        prepare_for(policy, &awk, script).map(|j| Some(Arc::new(j)))
+    }
+
+    /// `awk` with the jail entered between fork and exec.
+    pub fn command(self: &Arc<Self>) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.env_clear();
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::unix::process::CommandExt;
+            let jail = Arc::clone(self);
+            // SAFETY: enter() only makes raw syscalls on data prepared before the fork.
+            unsafe {
+                cmd.pre_exec(move || jail.plan.enter());
+            }
+        }
+        cmd
+    }
+
+    /// Script path as awk sees it.
+    pub fn script_arg(&self) -> &Path {
+        &self.script
+    }
+
+    /// Set when best effort could not build the full jail.
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+}
+
+fn find_awk() -> anyhow::Result<PathBuf> {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&path)
+        .map(|d| d.join("awk"))
+        .find(|p| p.is_file())
+        .and_then(|p| p.canonicalize().ok())
+        .ok_or_else(|| anyhow::anyhow!("awk not found in PATH"))
+}
+
+#[cfg(target_os = "linux")]
+fn prepare_for(policy: &SandboxPolicy, awk: &Path, script: &Path) -> anyhow::Result<Jail> {
+    let full = linux::Plan::new(policy, awk, script, true).and_then(|plan| {
+        plan.probe()?;
+        Ok(plan)
+    });
+    match full {
+        Ok(plan) => Ok(Jail {
+            program: PathBuf::from(linux::JAIL_AWK),
+            script: PathBuf::from(linux::JAIL_SCRIPT),
+            note: None,
+            plan,
+        }),
+        Err(e) if policy.mode == SandboxMode::Required => {
+            anyhow::bail!("sandbox required but unavailable: {}", e)
+        }
+        Err(e) => Ok(Jail {
+            program: awk.to_path_buf(),
+            script: script.to_path_buf(),
+            note: Some(format!("sandbox degraded to seccomp and rlimits: {}", e)),
+            plan: linux::Plan::new(policy, awk, script, false)
+                .map_err(|e| anyhow::anyhow!("sandbox: {}", e))?,
+        }),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn prepare_for(policy: &SandboxPolicy, awk: &Path, script: &Path) -> anyhow::Result<Jail> {
+    if policy.mode == SandboxMode::Required {
+        anyhow::bail!("sandbox required but only available on Linux");
+    }
+    Ok(Jail {
+        program: awk.to_path_buf(),
+        script: script.to_path_buf(),
+        note: Some("sandbox degraded to a cleared environment (not Linux)".to_string()),
+    })
+}
+// :: ∎
+
+// ▛▞// linux jail :: hawk.sandbox.linux
+// ⫸ [unshare.bind.chroot.seccomp]
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::collections::BTreeMap;
+    use std::ffi::CString;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+    use std::process::{Command, Stdio};
+
+    use seccompiler::{BpfProgram, SeccompAction, SeccompFilter, TargetArch};
+
+    use super::SandboxPolicy;
+
+    pub const JAIL_AWK: &str = "/hawk/awk";
+    pub const JAIL_SCRIPT: &str = "/hawk/script.awk";
+
+    /// Dirs the dynamic loader may need. Bound read-only where present.
+    const LIB_DIRS: [&str; 5] = ["/lib", "/lib64", "/lib32", "/usr/lib", "/usr/lib64"];
+
+    struct Bind {
+        src: CString,
+        dst: CString,
+        file: bool,
+        /// Flags of the source mount, which a read-only remount must keep.
+        keep: libc::c_ulong,
+    }
+
+    /// Everything the child needs, built before the fork so `enter` never
+    /// allocates.
+    pub struct Plan {
+        namespaces: bool,
+        root_dir: PathBuf,
+        root: CString,
+        uid_map: Vec<u8>,
+        gid_map: Vec<u8>,
+        dirs: Vec<CString>,
+        binds: Vec<Bind>,
+        cpu_s: u64,
+        mem_bytes: u64,
+        filter: BpfProgram,
+    }
+
+    fn cstr(p: &Path) -> io::Result<CString> {
+        CString::new(p.as_os_str().as_bytes())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contains NUL"))
+    }
+
+    fn cvt(rc: libc::c_int) -> io::Result<()> {
+        if rc == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn mount_flags(p: &CString) -> libc::c_ulong {
+        // SAFETY: statvfs writes into a zeroed struct we own.
+        let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(p.as_ptr(), &mut st) } != 0 {
+            return 0;
+        }
+        [
+            (libc::ST_NOSUID, libc::MS_NOSUID),
+            (libc::ST_NODEV, libc::MS_NODEV),
+            (libc::ST_NOEXEC, libc::MS_NOEXEC),
+            (libc::ST_NOATIME, libc::MS_NOATIME),
+            (libc::ST_NODIRATIME, libc::MS_NODIRATIME),
+            (libc::ST_RELATIME, libc::MS_RELATIME),
+        ]
+        .iter()
+        .filter(|(st_flag, _)| st.f_flag & st_flag != 0)
+        .fold(0, |acc, (_, ms)| acc | ms)
+    }
+
+    fn seccomp_filter() -> io::Result<BpfProgram> {
+        let arch = TargetArch::try_from(std::env::consts::ARCH)
+            .map_err(|e| io::Error::new(io::ErrorKind::Unsupported, e.to_string()))?;
+        // fork and vfork only exist as syscalls on x86_64.
+        #[cfg(target_arch = "x86_64")]
+        let legacy = [libc::SYS_fork, libc::SYS_vfork];
+        #[cfg(not(target_arch = "x86_64"))]
+        let legacy: [libc::c_long; 0] = [];
+        let deny = [
+            libc::SYS_socket,
+            libc::SYS_socketpair,
+            libc::SYS_connect,
+            libc::SYS_bind,
+            libc::SYS_listen,
+            libc::SYS_accept,
+            libc::SYS_accept4,
+            libc::SYS_clone,
+            libc::SYS_clone3,
+            libc::SYS_execveat,
+            libc::SYS_ptrace,
+            libc::SYS_process_vm_readv,
+            libc::SYS_process_vm_writev,
+            libc::SYS_mount,
+            libc::SYS_umount2,
+            libc::SYS_pivot_root,
+            libc::SYS_chroot,
+            libc::SYS_unshare,
+            libc::SYS_setns,
+            libc::SYS_bpf,
+            libc::SYS_perf_event_open,
+            libc::SYS_userfaultfd,
+            libc::SYS_io_uring_setup,
+            libc::SYS_keyctl,
+            libc::SYS_add_key,
+            libc::SYS_request_key,
+            libc::SYS_init_module,
+            libc::SYS_finit_module,
+            libc::SYS_delete_module,
+            libc::SYS_kexec_load,
+            libc::SYS_reboot,
+        ];
+
+        let rules: BTreeMap<i64, Vec<seccompiler::SeccompRule>> = deny
+            .into_iter()
+            .chain(legacy)
+            .map(|nr| (nr, Vec::new()))
+            .collect();
+        let filter = SeccompFilter::new(
+            rules,
+            SeccompAction::Allow,
+            SeccompAction::Errno(libc::EPERM as u32),
+            arch,
+        )
+        .map_err(|e| io::Error::other(e.to_string()))?;
+        filter
+            .try_into()
+            .map_err(|e: seccompiler::BackendError| io::Error::other(e.to_string()))
+    }
+
+    impl Plan {
+        /// `namespaces = false` keeps only the cleared env, seccomp and rlimits.
+        pub fn new(
+            policy: &SandboxPolicy,
+            awk: &Path,
+            script: &Path,
+            namespaces: bool,
+        ) -> io::Result<Self> {
+            let mut plan = Plan {
+                namespaces,
+                root_dir: PathBuf::new(),
+                root: CString::default(),
+                uid_map: Vec::new(),
+                gid_map: Vec::new(),
+                dirs: Vec::new(),
+                binds: Vec::new(),
+                cpu_s: policy.cpu_s,
+                mem_bytes: policy.mem_mb.saturating_mul(1024 * 1024),
+                filter: seccomp_filter()?,
+            };
+            if !namespaces {
+                return Ok(plan);
+            }
+
+            // The tmpfs lands on this empty dir inside the child's mount
+            // namespace only; the host keeps seeing an empty dir.
+            let root_dir = std::env::temp_dir().join(format!(
+                "hawk-jail-{}-{}",
+                std::process::id(),
+                super::next_id()
+            ));
+            std::fs::create_dir_all(&root_dir)?;
+            plan.root = cstr(&root_dir)?;
+            plan.root_dir = root_dir.clone();
+
+            // SAFETY: getuid/getgid cannot fail.
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            plan.uid_map = format!("{} {} 1", uid, uid).into_bytes();
+            plan.gid_map = format!("{} {} 1", gid, gid).into_bytes();
+
+            let mut dirs: Vec<PathBuf> = vec![root_dir.join("hawk")];
+            for lib in LIB_DIRS {
+                let src = Path::new(lib);
+                if !src.is_dir() {
+                    continue;
+                }
+                let dst = root_dir.join(lib.trim_start_matches('/'));
+                for a in dst.ancestors().collect::<Vec<_>>().into_iter().rev() {
+                    if a.starts_with(&root_dir) && a != root_dir && !dirs.iter().any(|d| d == a) {
+                        dirs.push(a.to_path_buf());
+                    }
+                }
+                let src = cstr(src)?;
+                plan.binds.push(Bind {
+                    keep: mount_flags(&src),
+                    src,
+                    dst: cstr(&dst)?,
+                    file: false,
+                });
+            }
+            for (src, dst) in [(awk, JAIL_AWK), (script, JAIL_SCRIPT)] {
+                let src = cstr(&src.canonicalize()?)?;
+                plan.binds.push(Bind {
+                    keep: mount_flags(&src),
+                    src,
+                    dst: cstr(&root_dir.join(dst.trim_start_matches('/')))?,
+                    file: true,
+                });
+            }
+            plan.dirs = dirs.iter().map(|d| cstr(d)).collect::<io::Result<_>>()?;
+            Ok(plan)
+        }
+
+        /// Run `awk 'BEGIN { }'` in the jail once, so a kernel without user
+        /// namespaces shows up before the first real spawn.
+        pub fn probe(&self) -> io::Result<()> {
+            use std::os::unix::process::CommandExt;
+            let plan: *const Plan = self;
+            let mut cmd = Command::new(JAIL_AWK);
+            cmd.arg("BEGIN { }")
+                .env_clear()
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            // SAFETY: `self` outlives the child, which is waited for below;
+            // enter() only makes raw syscalls.
+            unsafe {
+                let plan = plan as usize;
+                cmd.pre_exec(move || (*(plan as *const Plan)).enter());
+            }
+            let status = cmd.status()?;
+            if !status.success() {
+                return Err(io::Error::other(format!("probe exited with {}", status)));
+            }
+            Ok(())
+        }
+
+        /// Runs in the child between fork and exec.
+        pub fn enter(&self) -> io::Result<()> {
+            // SAFETY: raw syscalls on CStrings and buffers owned by `self`.
+            unsafe {
+                if self.namespaces {
+                    self.enter_namespaces()?;
+                }
+
+                let limit = |res, cur: u64, max: u64| {
+                    let rl = libc::rlimit {
+                        rlim_cur: cur as libc::rlim_t,
+                        rlim_max: max as libc::rlim_t,
+                    };
+                    cvt(libc::setrlimit(res, &rl))
+                };
+                if self.cpu_s > 0 {
+                    // SIGXCPU at the soft limit, SIGKILL a second later.
+                    limit(libc::RLIMIT_CPU, self.cpu_s, self.cpu_s + 1)?;
+                }
+                if self.mem_bytes > 0 {
+                    limit(libc::RLIMIT_AS, self.mem_bytes, self.mem_bytes)?;
+                }
+                // Pipes are not regular files, so stdout and stderr still work.
+                limit(libc::RLIMIT_FSIZE, 0, 0)?;
+                limit(libc::RLIMIT_CORE, 0, 0)?;
+                limit(libc::RLIMIT_NOFILE, 32, 32)?;
+            }
+            seccompiler::apply_filter(&self.filter).map_err(|_| io::Error::last_os_error())
+        }
+
+        unsafe fn enter_namespaces(&self) -> io::Result<()> {
+            cvt(libc::unshare(
+                libc::CLONE_NEWUSER
+                    | libc::CLONE_NEWNS
+                    | libc::CLONE_NEWNET
+                    | libc::CLONE_NEWIPC
+                    | libc::CLONE_NEWUTS,
+            ))?;
+            write_file(c"/proc/self/setgroups", b"deny")?;
+            write_file(c"/proc/self/uid_map", &self.uid_map)?;
+            write_file(c"/proc/self/gid_map", &self.gid_map)?;
+
+            let null = std::ptr::null::<libc::c_char>();
+            cvt(libc::mount(
+                null,
+                c"/".as_ptr(),
+                null,
+                libc::MS_REC | libc::MS_PRIVATE,
+                std::ptr::null(),
+            ))?;
+            cvt(libc::mount(
+                c"tmpfs".as_ptr(),
+                self.root.as_ptr(),
+                c"tmpfs".as_ptr(),
+                libc::MS_NOSUID | libc::MS_NODEV,
+                c"size=64k,mode=0755".as_ptr().cast(),
+            ))?;
+            for d in &self.dirs {
+                cvt(libc::mkdir(d.as_ptr(), 0o755))?;
+            }
+            for b in &self.binds {
+                if b.file {
+                    let fd = libc::open(
+                        b.dst.as_ptr(),
+                        libc::O_CREAT | libc::O_WRONLY | libc::O_CLOEXEC,
+                        0o644,
+                    );
+                    if fd == -1 {
+                        return Err(io::Error::last_os_error());
+                    }
+                    libc::close(fd);
+                }
+                cvt(libc::mount(
+                    b.src.as_ptr(),
+                    b.dst.as_ptr(),
+                    null,
+                    libc::MS_BIND | libc::MS_REC,
+                    std::ptr::null(),
+                ))?;
+                cvt(libc::mount(
+                    null,
+                    b.dst.as_ptr(),
+                    null,
+                    libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY | b.keep,
+                    std::ptr::null(),
+                ))?;
+            }
+            cvt(libc::mount(
+                null,
+                self.root.as_ptr(),
+                null,
+                libc::MS_REMOUNT | libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NODEV,
+                std::ptr::null(),
+            ))?;
+            cvt(libc::chdir(self.root.as_ptr()))?;
+            cvt(libc::chroot(c".".as_ptr()))?;
+            cvt(libc::chdir(c"/".as_ptr()))
+        }
+    }
+
+    impl Drop for Plan {
+        fn drop(&mut self) {
+            if self.namespaces {
+                let _ = std::fs::remove_dir(&self.root_dir);
+            }
+        }
+    }
+
+    unsafe fn write_file(path: &std::ffi::CStr, data: &[u8]) -> io::Result<()> {
+        let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+        if fd == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        let n = libc::write(fd, data.as_ptr().cast(), data.len());
+        libc::close(fd);
+        if n != data.len() as isize {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn next_id() -> u64 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+// :: ∎
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    const PROBE: &str = "BEGIN { r = (getline line < \"/etc/hostname\"); s = system(\"true\"); \
+                         print r, (s != 0), length(ENVIRON[\"HOME\"] ENVIRON[\"PATH\"]) }\n";
+
+    fn probe_script(tag: &str) -> PathBuf {
+        let script = std::env::temp_dir().join(format!(
+            "hawk-jail-{}-{}.awk",
+            tag,
+            std::process::id()
+        ));
+        std::fs::write(&script, PROBE).unwrap();
+        script
+    }
+
+    #[test]
+    #[ignore = "needs unprivileged user namespaces; run with --ignored"]
+    fn jailed_awk_cannot_reach_files_env_or_commands() {
+        let script = probe_script("full");
+        let policy = SandboxPolicy {
+            mode: SandboxMode::Required,
+            ..SandboxPolicy::default()
+        };
+        let jail = Jail::prepare(&policy, &script).unwrap().unwrap();
+
+        let out = jail
+            .command()
+            .arg("-f")
+            .arg(jail.script_arg())
+            .output()
+            .unwrap();
+        let _ = std::fs::remove_file(&script);
+        assert_eq!(String::from_utf8_lossy(&out.stdout), "-1 1 0\n");
+    }
+
+    /// The fallback best effort takes without namespaces: files stay
+    /// readable, but commands and the environment do not.
+    #[test]
+    fn degraded_jail_still_blocks_commands_and_env() {
+        let script = probe_script("degraded");
+        let awk = find_awk().unwrap();
+        let jail = Arc::new(Jail {
+            program: awk.clone(),
+            script: script.clone(),
+            note: None,
+            plan: linux::Plan::new(&SandboxPolicy::default(), &awk, &script, false).unwrap(),
+        });
+
+        let out = jail
+            .command()
+            .arg("-f")
+            .arg(jail.script_arg())
+            .output()
+            .unwrap();
+        let _ = std::fs::remove_file(&script);
+        let out = String::from_utf8_lossy(&out.stdout);
+        assert!(out.ends_with(" 1 0\n"), "{}", out);
+    }
+}
+// :: ∎
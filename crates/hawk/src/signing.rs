@@ -0,0 +1,304 @@
+// ▛▞// hawk pack signing :: hawk.signing
+// @ctx ⫸ [sha256.thread.files]
+// @ctx ⫸ [ed25519.manifest.sig]
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+
+use crate::packs::Pack;
+
+// :: ∎
+
+// ▛▞// trust policy :: hawk.signing.trust
+// ⫸ [keys.dir.require]
+/// Detached signature over the exact bytes of `pack.toml`, hex encoded.
+pub const SIG_FILE: &str = "pack.toml.sig";
+
+#[derive(Debug, Clone, Default)]
+pub struct TrustPolicy {
+    pub keys: Vec<TrustedKey>,
+    /// Refuse packs without a valid signature and a sha256 on every thread.
+    pub require_signed: bool,
+}
+
+/// What `verify` found. Untrusted only comes back when the policy lets
+/// such packs load; with `require_signed` it is an error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Signature {
+    /// Name of the trusted key that signed it.
+    Trusted(String),
+    Unsigned,
+    /// Signed, but not verifiably by a trusted key; why.
+    Untrusted(String),
+}
+
+impl Signature {
+    pub fn trusted_key(self) -> Option<String> {
+        match self {
+            Signature::Trusted(k) => Some(k),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrustedKey {
+    /// File stem of the `.pub` file.
+    pub name: String,
+    pub public: Vec<u8>,
+}
+
+impl TrustPolicy {
+    /// Read every `<name>.pub` (hex ed25519 public key) in `dir`, or in the
+    /// default dir when `None`. A missing dir trusts nobody.
+    pub fn load(dir: Option<&Path>, require_signed: bool) -> anyhow::Result<Self> {
+        let dir = match dir {
+            Some(d) => Some(d.to_path_buf()),
+            None => default_keys_dir(),
+        };
+        let mut keys = Vec::new();
+        if let Some(dir) = dir.filter(|d| d.is_dir()) {
+            let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+                .with_context(|| format!("read trusted keys dir {:?}", dir))?
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|e| e == "pub"))
+                .collect();
+            paths.sort();
+            for path in paths {
+                let raw = fs::read_to_string(&path)
+                    .with_context(|| format!("read trusted key {:?}", path))?;
+                let public = hex::decode(raw.trim())
+                    .ok()
+                    .filter(|k| k.len() == 32)
+                    .ok_or_else(|| anyhow::anyhow!("trusted key {:?} is not 64 hex chars", path))?;
+                let name = path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                keys.push(TrustedKey { name, public });
+            }
+        }
+        Ok(Self {
+            keys,
+            require_signed,
+        })
+    }
+
+    /// Check a parsed pack's signature against the manifest text it was
+    /// parsed from. Errors only under `require_signed`; otherwise a bad
+    /// signature, or a thread the signature cannot cover for lack of a
+    /// sha256, is reported as `Untrusted` for the caller to warn about.
+    /// Thread file hashes are checked when the manifest is parsed.
+    pub fn verify(&self, pack: &Pack) -> Result<Signature, String> {
+        let sig_path = pack.root_dir.join(SIG_FILE);
+
+        if !sig_path.exists() {
+            if self.require_signed {
+                return Err(format!(
+                    "pack {} is unsigned and --require-signed is set",
+                    pack.id
+                ));
+            }
+            return Ok(Signature::Unsigned);
+        }
+
+        let unhashed = pack.threads.iter().find(|t| t.sha256.is_none()).map(|th| {
+            format!(
+                "pack {} thread {} has no sha256, so the signature does not cover it",
+                pack.id, th.id
+            )
+        });
+        if let (Some(e), true) = (&unhashed, self.require_signed) {
+            return Err(e.clone());
+        }
+
+        let body = pack.manifest.as_bytes();
+        let checked = fs::read_to_string(&sig_path)
+            .ok()
+            .and_then(|s| hex::decode(s.trim()).ok())
+            .ok_or_else(|| format!("{:?} is not a hex signature", sig_path))
+            .and_then(|sig| {
+                self.keys
+                    .iter()
+                    .find(|k| {
+                        UnparsedPublicKey::new(&ED25519, &k.public)
+                            .verify(body, &sig)
+                            .is_ok()
+                    })
+                    .map(|k| k.name.clone())
+                    .ok_or_else(|| {
+                        format!(
+                            "pack {} signature does not verify against any trusted key ({} loaded); tampered or untrusted",
+                            pack.id,
+                            self.keys.len()
+                        )
+                    })
+            });
+
+        match (checked, unhashed) {
+            (Ok(key), None) => Ok(Signature::Trusted(key)),
+            (Err(e), _) if self.require_signed => Err(e),
+            (Err(e), _) | (Ok(_), Some(e)) => Ok(Signature::Untrusted(e)),
+        }
+    }
+}
+
+/// $XDG_CONFIG_HOME/hawk/trusted_keys or ~/.config/hawk/trusted_keys.
+pub fn default_keys_dir() -> Option<PathBuf> {
+    match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(v) if !v.is_empty() => Some(PathBuf::from(v).join("hawk").join("trusted_keys")),
+        _ => std::env::var_os("HOME").map(|h| {
+            PathBuf::from(h)
+                .join(".config")
+                .join("hawk")
+                .join("trusted_keys")
+        }),
+    }
+}
+// :: ∎
+
+// ▛▞// hashing and signing :: hawk.signing.sign
+// ⫸ [digest.keygen.sign]
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(ring::digest::digest(&ring::digest::SHA256, bytes))
+}
+
+/// Write `<prefix>.key` (hex PKCS#8, owner-only) and `<prefix>.pub`.
+pub fn generate_key(prefix: &Path) -> anyhow::Result<(PathBuf, PathBuf)> {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+        .map_err(|_| anyhow::anyhow!("ed25519 key generation failed"))?;
+    let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+        .map_err(|_| anyhow::anyhow!("ed25519 key generation failed"))?;
+
+    let key_path = prefix.with_extension("key");
+    let pub_path = prefix.with_extension("pub");
+    for p in [&key_path, &pub_path] {
+        if p.exists() {
+            anyhow::bail!("{:?} already exists", p);
+        }
+    }
+
+    let mut opts = fs::OpenOptions::new();
+    opts.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut opts, 0o600);
+    let mut f = opts
+        .open(&key_path)
+        .with_context(|| format!("create {:?}", key_path))?;
+    std::io::Write::write_all(
+        &mut f,
+        format!("{}\n", hex::encode(pkcs8.as_ref())).as_bytes(),
+    )?;
+
+    fs::write(
+        &pub_path,
+        format!("{}\n", hex::encode(pair.public_key().as_ref())),
+    )
+    .with_context(|| format!("write {:?}", pub_path))?;
+    Ok((key_path, pub_path))
+}
+
+/// Sign `pack_dir/pack.toml` into `pack_dir/pack.toml.sig`. Refuses while a
+/// thread lacks a matching sha256, listing the lines to paste.
+pub fn sign_pack(pack: &Pack, key_path: &Path) -> anyhow::Result<PathBuf> {
+    let mut missing = Vec::new();
+    for th in &pack.threads {
+        let raw = fs::read(pack.root_dir.join(&th.file))
+            .with_context(|| format!("read thread {} file", th.id))?;
+        let want = sha256_hex(&raw);
+        if th.sha256.as_deref() != Some(want.as_str()) {
+            missing.push(format!("thread {}: sha256 = \"{}\"", th.id, want));
+        }
+    }
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "add a sha256 to every [[thread]] before signing:\n  {}",
+            missing.join("\n  ")
+        );
+    }
+
+    let raw = fs::read_to_string(key_path).with_context(|| format!("read key {:?}", key_path))?;
+    let pkcs8 = hex::decode(raw.trim()).context("signing key is not hex")?;
+    let pair = Ed25519KeyPair::from_pkcs8(&pkcs8)
+        .map_err(|_| anyhow::anyhow!("{:?} is not an ed25519 PKCS#8 key", key_path))?;
+
+    let sig_path = pack.root_dir.join(SIG_FILE);
+    fs::write(
+        &sig_path,
+        format!("{}\n", hex::encode(pair.sign(pack.manifest.as_bytes()).as_ref())),
+    )
+    .with_context(|| format!("write {:?}", sig_path))?;
+    Ok(sig_path)
+}
+// :: ∎
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packs::{self, testkit::Manifest};
+
+    #[test]
+    fn signature_covers_manifest_and_hashes_cover_scripts() {
+        let root = std::env::temp_dir().join(format!("hawk-sign-{}", std::process::id()));
+        let pack_dir = root.join("demo");
+        let script = "{ print }\n";
+        let manifest = Manifest::new("demo")
+            .thread("echo")
+            .thread_line(&format!("sha256 = \"{}\"", sha256_hex(script.as_bytes())))
+            .script(script);
+        manifest.write(&pack_dir);
+        let load = || packs::parse_pack_manifest(&pack_dir, &pack_dir.join("pack.toml"));
+
+        let keys = root.join("keys");
+        fs::create_dir_all(&keys).unwrap();
+        generate_key(&keys.join("ci")).unwrap();
+        let strict = TrustPolicy::load(Some(&keys), true).unwrap();
+        assert!(strict
+            .verify(&load().unwrap())
+            .unwrap_err()
+            .contains("unsigned"));
+
+        sign_pack(&load().unwrap(), &keys.join("ci.key")).unwrap();
+        assert_eq!(
+            strict.verify(&load().unwrap()),
+            Ok(Signature::Trusted("ci".to_string()))
+        );
+
+        // Without --require-signed a key we do not trust is a warning.
+        let lax = TrustPolicy::default();
+        assert!(matches!(
+            lax.verify(&load().unwrap()),
+            Ok(Signature::Untrusted(e)) if e.contains("untrusted")
+        ));
+        let idx = packs::load_packs(&root, &lax).unwrap();
+        assert!(idx.packs.iter().any(|p| p.id == "demo" && p.signed_by.is_none()));
+        assert_eq!(idx.warnings.len(), 1, "{:?}", idx.warnings);
+
+        // A valid signature over a thread without a hash covers only part
+        // of what runs.
+        let trusting = TrustPolicy::load(Some(&keys), false).unwrap();
+        let mut partial = load().unwrap();
+        partial.threads[0].sha256 = None;
+        assert!(matches!(
+            trusting.verify(&partial),
+            Ok(Signature::Untrusted(e)) if e.contains("thread echo has no sha256")
+        ));
+        assert!(strict.verify(&partial).unwrap_err().contains("no sha256"));
+
+        // A script edit breaks its hash, a manifest edit breaks the signature.
+        fs::write(pack_dir.join("t.awk"), "{ system(\"id\") }\n").unwrap();
+        assert!(load().unwrap_err().to_string().contains("sha256 mismatch"));
+        manifest.version("0.1.1").write(&pack_dir);
+        assert!(strict
+            .verify(&load().unwrap())
+            .unwrap_err()
+            .contains("tampered"));
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
+// :: ∎
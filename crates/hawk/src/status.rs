@@ -0,0 +1,286 @@
+// ▛▞// hawk status :: hawk.status
+// @ctx ⫸ [headless.snapshot.report]
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
+use time::OffsetDateTime;
+
+use crate::app::App;
+use crate::io::IngestMsg;
+
+// :: ∎
+
+// ▛▞// status types :: hawk.status.types
+// ⫸ [format.text.tsv.json]
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum StatusFormat {
+    Text,
+    Tsv,
+    Json,
+}
+
+/// Exit code when any unacked entity is fail or dead.
+pub const EXIT_UNHEALTHY: i32 = 2;
+// :: ∎
+
+// ▛▞// collect :: hawk.status.collect
+// ⫸ [deadline.replay.eof]
+/// Feed `app` until `replays` hawkd replay markers, source EOF, or `wait` elapses.
+pub fn collect(app: &mut App, rx: &Receiver<IngestMsg>, wait: Duration, replays: usize) {
+    let deadline = Instant::now() + wait;
+    let mut done = 0usize;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match rx.recv_timeout(left) {
+            Ok(IngestMsg::ReplayDone) => {
+                done += 1;
+                if done >= replays {
+                    break;
+                }
+            }
+            Ok(msg) => app.ingest(msg),
+            Err(RecvTimeoutError::Timeout) => break,
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    // Drain what already arrived so a fast source is reported in full.
+    while let Ok(msg) = rx.try_recv() {
+        if !matches!(msg, IngestMsg::ReplayDone) {
+            app.ingest(msg);
+        }
+    }
+    app.expire_entities();
+}
+// :: ∎
+
+// ▛▞// render :: hawk.status.render
+// ⫸ [table.sort.liveness]
+struct StatusRow {
+    scope: String,
+    id: String,
+    kind: String,
+    level: String,
+    state: String,
+    age_s: i64,
+    acked: bool,
+    msg: String,
+}
+
+fn rows(app: &App, now: OffsetDateTime) -> Vec<StatusRow> {
+    app.sorted_entities()
+        .into_iter()
+        .map(|st| StatusRow {
+            state: app.compute_entity_liveness(&st, now).as_str().to_string(),
+            age_s: (now - st.last_seen).whole_seconds().max(0),
+            acked: app.ack_for(&st, now).is_some(),
+            level: st.last_level.as_str().to_string(),
+            scope: st.scope,
+            id: st.id,
+            kind: st.kind,
+            msg: st.last_msg,
+        })
+        .collect()
+}
+
+pub fn render(app: &App, format: StatusFormat) -> String {
+    let now = app.now();
+    let c = app.counts_by_state();
+    let rows = rows(app, now);
+
+    match format {
+        StatusFormat::Json => {
+            let entities: Vec<serde_json::Value> = rows
+                .iter()
+                .map(|r| {
+                    serde_json::json!({
+                        "scope": r.scope,
+                        "id": r.id,
+                        "kind": r.kind,
+                        "level": r.level,
+                        "state": r.state,
+                        "age_s": r.age_s,
+                        "acked": r.acked,
+                        "msg": r.msg,
+                    })
+                })
+                .collect();
+
+            let pipeline: Vec<serde_json::Value> = app
+                .transforms
+                .iter()
+                .map(|t| {
+                    serde_json::json!({
+                        "name": t.name,
+                        "state": t.state().as_str(),
+                        "frames_in": t.frames_in.load(Ordering::Relaxed),
+                        "frames_out": t.frames_out.load(Ordering::Relaxed),
+                        "invalid": t.invalid.load(Ordering::Relaxed),
+                        "restarts": t.restarts.load(Ordering::Relaxed),
+                        "crashes": t.crashes.load(Ordering::Relaxed),
+                        "stderr": t.stderr_tail(),
+                    })
+                })
+                .collect();
+
+            let derived: Vec<serde_json::Value> = app
+                .sorted_derived()
+                .into_iter()
+                .map(|st| {
+                    serde_json::json!({
+                        "scope": st.scope,
+                        "id": st.id,
+                        "level": st.last_level.as_str(),
+                        "msg": st.last_msg,
+                    })
+                })
+                .collect();
+
+            let doc = serde_json::json!({
+                "app": "hawk",
+                "entities": c.total,
+                "ok": c.ok,
+                "warn": c.warn,
+                "fail": c.fail,
+                "unknown": c.unknown,
+                "stale": c.stale,
+                "dead": c.dead,
+                "acked": c.acked,
+                "frames": app.frames_seen,
+                "parse_errors": app.parse_errors,
+                "repaired": app.repaired_lines,
+                "io_errors": app.io_errors,
+                "pipeline": pipeline,
+                "rows": entities,
+                "derived": derived,
+            });
+            serde_json::to_string_pretty(&doc).unwrap_or_default() + "\n"
+        }
+        StatusFormat::Tsv => {
+            let mut out = String::from("scope\tid\tkind\tlevel\tstate\tage_s\tacked\tmsg\n");
+            for r in &rows {
+                out.push_str(&format!(
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                    r.scope, r.id, r.kind, r.level, r.state, r.age_s, r.acked, r.msg
+                ));
+            }
+            out
+        }
+        StatusFormat::Text => {
+            let mut out = format!(
+                "entities={}  ok={}  warn={}  fail={}  unknown={}  stale={}  dead={}  acked={}\n",
+                c.total, c.ok, c.warn, c.fail, c.unknown, c.stale, c.dead, c.acked
+            );
+            if rows.is_empty() {
+                return out;
+            }
+
+            let header = ["scope", "id", "kind", "level", "state", "age", "msg"];
+            let table: Vec<[String; 7]> = rows
+                .iter()
+                .map(|r| {
+                    let msg = if r.acked {
+                        format!("[ack] {}", r.msg)
+                    } else {
+                        r.msg.clone()
+                    };
+                    [
+                        r.scope.clone(),
+                        r.id.clone(),
+                        r.kind.clone(),
+                        r.level.clone(),
+                        r.state.clone(),
+                        format!("{}s", r.age_s),
+                        msg,
+                    ]
+                })
+                .collect();
+
+            let mut widths = header.map(str::len);
+            for row in &table {
+                for (w, cell) in widths.iter_mut().zip(row.iter()) {
+                    *w = (*w).max(cell.chars().count());
+                }
+            }
+
+            let fmt_row = |cells: &[&str]| -> String {
+                let mut line = String::new();
+                for (i, cell) in cells.iter().enumerate() {
+                    if i + 1 == cells.len() {
+                        line.push_str(cell);
+                    } else {
+                        line.push_str(&format!("{:<w$}  ", cell, w = widths[i]));
+                    }
+                }
+                line.trim_end().to_string() + "\n"
+            };
+
+            out.push_str(&fmt_row(&header));
+            for row in &table {
+                let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+                out.push_str(&fmt_row(&cells));
+            }
+            out
+        }
+    }
+}
+
+/// Non-zero when any unacked entity is fail or dead.
+pub fn exit_code(app: &App) -> i32 {
+    let c = app.counts_by_state();
+    if c.fail > 0 || c.dead > 0 {
+        EXIT_UNHEALTHY
+    } else {
+        0
+    }
+}
+// :: ∎
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::sync::Arc;
+
+    use hawk_core::HawkFrame;
+    use time::macros::datetime;
+
+    use super::*;
+    use crate::ack::{Ack, AckOp};
+    use crate::clock::ManualClock;
+
+    fn frame(line: &str) -> IngestMsg {
+        IngestMsg::Frame(HawkFrame::parse_tsv_line(line).unwrap().unwrap())
+    }
+
+    #[test]
+    fn collect_stops_at_replay_marker_and_acks_clear_the_exit_code() {
+        let now = datetime!(2026-02-16 12:00:05 UTC);
+        let mut app = App::new(10, 10, 60, 120);
+        app.clock = Arc::new(ManualClock::new(now));
+
+        let (tx, rx) = mpsc::channel();
+        tx.send(frame("2026-02-16T12:00:00Z\tHEALTH\tgrpc\talpha\tfail\tdown\t"))
+            .unwrap();
+        tx.send(IngestMsg::ReplayDone).unwrap();
+        tx.send(frame("2026-02-16T12:00:01Z\tHEALTH\tsystemd\tdb\tok\tup\t"))
+            .unwrap();
+        // Kept open: only the marker ends the wait.
+        collect(&mut app, &rx, Duration::from_secs(30), 1);
+
+        assert_eq!(
+            render(&app, StatusFormat::Tsv),
+            "scope\tid\tkind\tlevel\tstate\tage_s\tacked\tmsg\n\
+             grpc\talpha\tHEALTH\tfail\tactive\t5\tfalse\tdown\n\
+             systemd\tdb\tHEALTH\tok\tactive\t4\tfalse\tup\n"
+        );
+        assert_eq!(exit_code(&app), EXIT_UNHEALTHY);
+
+        app.acks
+            .apply(Ack::new(AckOp::Ack, "grpc", "alpha", "", None, now));
+        assert_eq!(exit_code(&app), 0);
+        drop(tx);
+    }
+}
+// :: ∎
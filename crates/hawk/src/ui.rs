@@ -11,19 +11,23 @@ use crossterm::{
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 
-use crate::{app::App, io::IngestMsg};
+use crate::{app::App, capture::ReplayCtl, config::UiConfig, io::IngestMsg};
 use crate::ui::draw::draw_app;
 
 pub mod draw;
 
-pub fn run_tui(app: &mut App, rx: Receiver<IngestMsg>) -> anyhow::Result<()> {
+const TAIL_PAGE: isize = 10;
+const SEEK_SHORT_MS: i64 = 10_000;
+const SEEK_LONG_MS: i64 = 60_000;
+
+pub fn run_tui(app: &mut App, rx: Receiver<IngestMsg>, cfg: &UiConfig) -> anyhow::Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let tick_rate = Duration::from_millis(200);
+    let tick_rate = cfg.tick_rate;
     let mut last_tick = Instant::now();
 
     loop {
@@ -31,8 +35,9 @@ pub fn run_tui(app: &mut App, rx: Receiver<IngestMsg>) -> anyhow::Result<()> {
         while let Ok(msg) = rx.try_recv() {
             app.ingest(msg);
         }
+        app.expire_entities();
 
-        terminal.draw(|f| draw_app(f, app))?;
+        terminal.draw(|f| draw_app(f, app, cfg))?;
 
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
@@ -40,9 +45,46 @@ pub fn run_tui(app: &mut App, rx: Receiver<IngestMsg>) -> anyhow::Result<()> {
 
         if event::poll(timeout)? {
             if let Event::Key(k) = event::read()? {
+                if app.prompt.is_some() {
+                    handle_prompt_key(app, k.code);
+                    continue;
+                }
+                if app.picker.is_some() {
+                    handle_picker_key(app, k.code);
+                    continue;
+                }
+                if let Some(r) = &app.replay {
+                    let ctl = match k.code {
+                        KeyCode::Char(' ') => Some(ReplayCtl::TogglePause),
+                        KeyCode::Left => Some(ReplayCtl::Seek(-SEEK_SHORT_MS)),
+                        KeyCode::Right => Some(ReplayCtl::Seek(SEEK_SHORT_MS)),
+                        KeyCode::Char('[') => Some(ReplayCtl::Seek(-SEEK_LONG_MS)),
+                        KeyCode::Char(']') => Some(ReplayCtl::Seek(SEEK_LONG_MS)),
+                        KeyCode::Char('+') => Some(ReplayCtl::Speed(2.0)),
+                        KeyCode::Char('-') => Some(ReplayCtl::Speed(0.5)),
+                        _ => None,
+                    };
+                    if let Some(c) = ctl {
+                        r.send(c);
+                        continue;
+                    }
+                }
                 match k.code {
                     KeyCode::Char('q') => break,
                     KeyCode::Esc => break,
+                    KeyCode::Char('j') | KeyCode::Down => app.select_next(),
+                    KeyCode::Char('k') | KeyCode::Up => app.select_prev(),
+                    KeyCode::Char('p') => app.toggle_pause(),
+                    KeyCode::Char('e') => app.toggle_errors(),
+                    KeyCode::Char('t') => app.toggle_pipeline(),
+                    KeyCode::Char('T') => app.open_picker(),
+                    KeyCode::PageUp => app.scroll_tail(TAIL_PAGE),
+                    KeyCode::PageDown => app.scroll_tail(-TAIL_PAGE),
+                    KeyCode::End => app.resume_tail(),
+                    KeyCode::Char('a') => app.open_ack_prompt(),
+                    KeyCode::Char('s') => app.open_silence_prompt(),
+                    KeyCode::Char('u') => app.unack_selected(),
+                    KeyCode::Char('x') | KeyCode::Delete => app.delete_selected(),
                     _ => {}
                 }
             }
@@ -58,4 +100,34 @@ pub fn run_tui(app: &mut App, rx: Receiver<IngestMsg>) -> anyhow::Result<()> {
     terminal.show_cursor()?;
     Ok(())
 }
+
+fn handle_prompt_key(app: &mut App, code: KeyCode) {
+    let Some(p) = app.prompt.as_mut() else {
+        return;
+    };
+    match code {
+        KeyCode::Enter => app.submit_prompt(),
+        KeyCode::Esc => app.prompt = None,
+        KeyCode::Backspace => {
+            p.input.pop();
+        }
+        KeyCode::Char(c) => p.input.push(c),
+        _ => {}
+    }
+}
+
+fn handle_picker_key(app: &mut App, code: KeyCode) {
+    let in_form = app.picker.as_ref().is_some_and(|p| p.form.is_some());
+    match code {
+        KeyCode::Enter => app.picker_submit(),
+        KeyCode::Esc => app.picker_back(),
+        KeyCode::Up | KeyCode::BackTab => app.picker_move(-1),
+        KeyCode::Down | KeyCode::Tab => app.picker_move(1),
+        KeyCode::Char('k') if !in_form => app.picker_move(-1),
+        KeyCode::Char('j') if !in_form => app.picker_move(1),
+        KeyCode::Backspace => app.picker_input(None),
+        KeyCode::Char(c) => app.picker_input(Some(c)),
+        _ => {}
+    }
+}
 // :: ∎
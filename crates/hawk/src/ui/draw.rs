@@ -1,148 +1,1002 @@
 // ▛▞// hawk draw layer :: hawk.ui.draw
 // @ctx ⫸ [topbar.table.footer]
+use std::sync::atomic::Ordering;
+
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState, Wrap},
     Frame,
 };
 
+use hawk_core::Level;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
 
-use crate::app::{App, Liveness};
+use crate::ack::Ack;
+use crate::app::{
+    entity_key, App, ArgsForm, EntityState, LineIssue, Liveness, Picker, PromptKind,
+};
+use crate::awk::RunnerState;
+use crate::config::{Column, UiConfig};
+use crate::io::SourceStatus;
 
-pub fn draw_app(f: &mut Frame, app: &App) {
+pub fn draw_app(f: &mut Frame, app: &App, cfg: &UiConfig) {
     let size = f.size();
 
     let outer = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // top bar
-            Constraint::Min(0),    // body
-            Constraint::Length(1), // footer
+            Constraint::Length(top_bar_height(app)), // top bar
+            Constraint::Min(0),                      // body
+            Constraint::Length(2),                   // footer: rule + one line
         ])
         .split(size);
 
-    draw_top_bar(f, outer[0], app);
-    draw_body(f, outer[1], app);
-    draw_footer(f, outer[2]);
+    draw_top_bar(f, outer[0], app, cfg);
+    draw_body(f, outer[1], app, cfg);
+    draw_footer(f, outer[2], app);
+    draw_picker(f, size, app, cfg);
+}
+
+/// Counts line, plus a sources line when the mirror has live readers or a transform.
+fn top_bar_height(app: &App) -> u16 {
+    if app.sources.is_empty() && app.transforms.is_empty() {
+        3
+    } else {
+        4
+    }
 }
 
-fn draw_top_bar(f: &mut Frame, area: Rect, app: &App) {
-    let (total, ok, warn, fail, stale, dead) = app.counts_by_state();
-    let now = OffsetDateTime::now_utc()
-        .format(&Rfc3339)
-        .unwrap_or_default();
+fn draw_top_bar(f: &mut Frame, area: Rect, app: &App, cfg: &UiConfig) {
+    let c = app.counts_by_state();
+    let now = app.now().format(&Rfc3339).unwrap_or_default();
+
+    let mut counters = format!(
+        "frames={}  parse_err={}  io_err={}  forgot={}",
+        app.frames_seen, app.parse_errors, app.io_errors, app.entities_forgotten
+    );
+    if app.repaired_lines > 0 {
+        counters.push_str(&format!("  repaired={}", app.repaired_lines));
+    }
+
+    // Unknown only shows up once a producer sends a level hawk can't place.
+    let unknown = if c.unknown > 0 {
+        format!("  unknown={}", c.unknown)
+    } else {
+        String::new()
+    };
 
-    let text = Line::from(vec![
-        Span::styled("HAWK", Style::default().add_modifier(Modifier::BOLD)),
+    let mut text = Line::from(vec![
+        Span::styled(
+            "HAWK",
+            Style::default()
+                .fg(cfg.theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ),
         Span::raw("  "),
         Span::raw(format!(
-            "entities={}  ok={}  warn={}  fail={}  stale={}  dead={}",
-            total, ok, warn, fail, stale, dead
+            "entities={}  ok={}  warn={}  fail={}{}  stale={}  dead={}  acked={}",
+            c.total, c.ok, c.warn, c.fail, unknown, c.stale, c.dead, c.acked
         )),
         Span::raw("  "),
-        Span::raw(format!(
-            "frames={}  parse_err={}  io_err={}",
-            app.frames_seen, app.parse_errors, app.io_errors
-        )),
+        Span::raw(counters),
         Span::raw("  "),
         Span::raw(now),
     ]);
 
-    let p = Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL).title("health at a glance"))
-        .wrap(Wrap { trim: true });
+    if let Some(r) = app.replay.as_ref().and_then(|r| r.snapshot()) {
+        let pos = r.position_time().format(&Rfc3339).unwrap_or_default();
+        text.spans.push(Span::raw("  "));
+        text.spans.push(Span::styled(
+            format!(
+                "replay {} {}/{}s x{}{}",
+                pos,
+                r.position_ms() / 1000,
+                r.total_ms / 1000,
+                r.speed,
+                if r.paused { " paused" } else { "" }
+            ),
+            Style::default().add_modifier(Modifier::BOLD),
+        ));
+    }
 
-    f.render_widget(p, area);
+    let block = cfg.theme.block("health at a glance");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(top_bar_height(app) - 3),
+        ])
+        .split(inner);
+
+    let p = Paragraph::new(text).wrap(Wrap { trim: true });
+    f.render_widget(p, rows[0]);
+
+    if top_bar_height(app) > 3 {
+        f.render_widget(Paragraph::new(sources_line(app, cfg)), rows[1]);
+    }
+}
+
+fn sources_line(app: &App, cfg: &UiConfig) -> Line<'static> {
+    let mut spans = Vec::new();
+    if !app.sources.is_empty() {
+        spans.push(Span::raw("sources"));
+    }
+    for src in &app.sources {
+        let status = src.status();
+        let color = match status {
+            SourceStatus::Connected => cfg.theme.level_ok,
+            SourceStatus::Connecting => cfg.theme.level_warn,
+            SourceStatus::Closed => cfg.theme.level_unknown,
+            SourceStatus::Failed(_) => cfg.theme.level_fail,
+        };
+        spans.push(Span::raw(format!("  {} ", src.name)));
+        spans.push(Span::styled(
+            status.as_str().to_string(),
+            Style::default().fg(color),
+        ));
+        spans.push(Span::raw(format!(
+            " frames={} parse_err={}",
+            src.frames.load(Ordering::Relaxed),
+            src.parse_errors.load(Ordering::Relaxed)
+        )));
+    }
+
+    if !app.transforms.is_empty() {
+        if !spans.is_empty() {
+            spans.push(Span::raw("  |"));
+        }
+        spans.push(Span::raw("transform"));
+        let mut restarts = 0;
+        for (i, t) in app.transforms.iter().enumerate() {
+            let state = t.state();
+            spans.push(Span::raw(format!(
+                "{} {} ",
+                if i == 0 { "" } else { " \u{2192}" },
+                t.name
+            )));
+            spans.push(Span::styled(
+                state.as_str(),
+                Style::default().fg(runner_color(&state, cfg)),
+            ));
+            restarts += t.restarts.load(Ordering::Relaxed);
+        }
+        spans.push(Span::raw(format!(" restarts={}", restarts)));
+    }
+    Line::from(spans)
+}
+
+fn runner_color(state: &RunnerState, cfg: &UiConfig) -> Color {
+    match state {
+        RunnerState::Running => cfg.theme.level_ok,
+        RunnerState::Restarting => cfg.theme.level_warn,
+        RunnerState::Passthrough => cfg.theme.level_fail,
+        RunnerState::Stopped => cfg.theme.level_unknown,
+    }
 }
 
-fn draw_body(f: &mut Frame, area: Rect, app: &App) {
+fn draw_body(f: &mut Frame, area: Rect, app: &App, cfg: &UiConfig) {
+    let area = if app.show_pipeline && !app.transforms.is_empty() {
+        // Borders and header around one row per stage.
+        let height = app.transforms.len() as u16 + 3;
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(height)])
+            .split(area);
+        draw_pipeline(f, rows[1], app, cfg);
+        rows[0]
+    } else {
+        area
+    };
+
+    let area = if app.show_errors {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(ERROR_PANEL_HEIGHT)])
+            .split(area);
+        draw_errors(f, rows[1], app, cfg);
+        rows[0]
+    } else {
+        area
+    };
+
     let cols = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(68), Constraint::Percentage(32)])
+        .constraints([
+            Constraint::Percentage(cfg.split_pct),
+            Constraint::Percentage(100 - cfg.split_pct),
+        ])
         .split(area);
 
-    draw_entity_table(f, cols[0], app);
-    draw_tail(f, cols[1], app);
+    let left = if app.derived.is_empty() {
+        cols[0]
+    } else {
+        // Up to a third of the column, one row per derived entity.
+        let want = app.derived.len() as u16 + 3;
+        let height = want.min((cols[0].height / 3).max(4));
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(height)])
+            .split(cols[0]);
+        draw_derived(f, rows[1], app, cfg);
+        rows[0]
+    };
+
+    draw_entity_table(f, left, app, cfg);
+    draw_tail(f, cols[1], app, cfg);
 }
 
-fn draw_footer(f: &mut Frame, area: Rect) {
-    let text = Line::from(vec![
-        Span::raw("q or esc: quit"),
-        Span::raw("  |  "),
-        Span::raw("source: stdin or unix socket (TSV HawkFrames)"),
-    ]);
-    let p = Paragraph::new(text).block(Block::default().borders(Borders::TOP));
-    f.render_widget(p, area);
+/// Transform output kept out of the entity table (`output = "panel"`).
+fn draw_derived(f: &mut Frame, area: Rect, app: &App, cfg: &UiConfig) {
+    let now = app.now();
+    let rows: Vec<Row> = app
+        .sorted_derived()
+        .into_iter()
+        .map(|st| {
+            Row::new(vec![
+                Cell::from(st.scope.clone()),
+                Cell::from(st.id.clone()),
+                Cell::from(st.last_level.as_str())
+                    .style(Style::default().fg(cfg.theme.level_color(st.last_level))),
+                Cell::from(format!("{}s", (now - st.last_seen).whole_seconds().max(0))),
+                Cell::from(st.last_msg.clone()),
+            ])
+        })
+        .collect();
+
+    let header = Row::new(vec!["scope", "id", "level", "age", "msg"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Length(14),
+        Constraint::Length(20),
+        Constraint::Length(8),
+        Constraint::Length(6),
+        Constraint::Fill(1),
+    ];
+    let title = format!("derived [{}]", app.derived.len());
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(cfg.theme.block(&title))
+        .column_spacing(1);
+    f.render_widget(table, area);
 }
 
-fn draw_entity_table(f: &mut Frame, area: Rect, app: &App) {
-    let now = OffsetDateTime::now_utc();
-    let rows = app.sorted_entities().into_iter().take(200).map(|st| {
-        let live = app.compute_entity_liveness(&st, now);
-        let age = (now - st.last_seen).whole_seconds();
+/// Thread picker popup (`T`): thread list, then the args form.
+fn draw_picker(f: &mut Frame, area: Rect, app: &App, cfg: &UiConfig) {
+    let Some(p) = &app.picker else {
+        return;
+    };
+    let (title, body) = match &p.form {
+        Some(form) => (
+            format!("transform thread:{}", form.thread.thread_id),
+            form_lines(form),
+        ),
+        None => ("transform".to_string(), picker_lines(p)),
+    };
+    let mut lines = body;
+    lines.push(Line::from(""));
+    match &p.error {
+        Some(e) => lines.push(Line::styled(
+            e.clone(),
+            Style::default().fg(cfg.theme.level_color(Level::Fail)),
+        )),
+        None if p.form.is_some() => lines.push(Line::from(
+            "tab: next field  enter: apply  esc: back",
+        )),
+        None => lines.push(Line::from("j/k: select  enter: choose  esc: close")),
+    }
+
+    let w = area.width.saturating_sub(8).min(96);
+    let h = area.height.saturating_sub(2).min(lines.len() as u16 + 2);
+    let popup = Rect {
+        x: area.x + (area.width - w) / 2,
+        y: area.y + (area.height - h) / 2,
+        width: w,
+        height: h,
+    };
+    f.render_widget(Clear, popup);
 
-        let live_str = match live {
-            Liveness::Active => "active",
-            Liveness::Dream => "dream",
-            Liveness::Stale => "stale",
-            Liveness::Dead => "dead",
+    // Scroll a long thread list so the selected row stays inside the box.
+    let top = match p.form {
+        Some(_) => 0,
+        None => (p.selected as u16 + 1).saturating_sub(h.saturating_sub(2)),
+    };
+    let para = Paragraph::new(lines)
+        .block(cfg.theme.block(&title))
+        .scroll((top, 0));
+    f.render_widget(para, popup);
+}
+
+fn picker_lines(p: &Picker) -> Vec<Line<'static>> {
+    let id_w = p
+        .threads
+        .iter()
+        .map(|t| t.qualified_id().chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let row = |i: usize, text: String| {
+        let style = if i == p.selected {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
         };
+        Line::styled(text, style)
+    };
+
+    let mut lines = vec![row(0, format!("{:<id_w$}  raw frames, no transform", "none"))];
+    for (i, t) in p.threads.iter().enumerate() {
+        let args: Vec<&str> = t.args.iter().map(|a| a.name.as_str()).collect();
+        let text = format!(
+            "{:<id_w$}  {:<8} {}  [{}]",
+            t.qualified_id(),
+            t.kind,
+            t.title,
+            args.join(" ")
+        );
+        lines.push(row(i + 1, text));
+    }
+    lines
+}
+
+fn form_lines(form: &ArgsForm) -> Vec<Line<'static>> {
+    let name_w = form
+        .thread
+        .args
+        .iter()
+        .map(|a| a.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    form.thread
+        .args
+        .iter()
+        .zip(&form.values)
+        .enumerate()
+        .map(|(i, (a, v))| {
+            let focused = i == form.focus;
+            let cursor = if focused { "_" } else { "" };
+            let mut spans = vec![
+                Span::raw(format!("{:<name_w$} = ", a.name)),
+                Span::styled(
+                    format!("{}{}", v, cursor),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+            ];
+            if !a.help.is_empty() {
+                spans.push(Span::raw(format!("  ({})", a.help)));
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
 
-        Row::new(vec![
-            Cell::from(st.scope),
-            Cell::from(st.id),
-            Cell::from(st.kind),
-            Cell::from(st.last_level.as_str().to_string()),
-            Cell::from(live_str.to_string()),
-            Cell::from(format!("{}s", age.max(0))),
-            Cell::from(st.last_msg),
+fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
+    let text = if let Some(p) = &app.prompt {
+        let label = match &p.kind {
+            PromptKind::Ack { scope, id } => format!("ack {}:{} [ttl] note> ", scope, id),
+            PromptKind::Silence => "silence scope:id-glob [ttl] note> ".to_string(),
+        };
+        Line::from(vec![
+            Span::styled(label, Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(p.input.clone()),
+            Span::raw("_"),
+            Span::raw("  (enter: submit  esc: cancel)"),
         ])
+    } else {
+        let mut spans = vec![
+            Span::raw("q or esc: quit"),
+            Span::raw("  |  "),
+            Span::raw("j/k: select  p: pause  pgup/pgdn: scroll  end: live  e: errors  t: pipeline  T: transform"),
+            Span::raw("  |  "),
+            Span::raw("a: ack  s: silence  u: unack  x: delete"),
+        ];
+        if app.replay.is_some() {
+            spans.push(Span::raw("  |  "));
+            spans.push(Span::raw(
+                "space: play/pause  left/right: 10s  [/]: 60s  +/-: speed",
+            ));
+        }
+        if let Some(s) = &app.status {
+            spans.push(Span::raw("  |  "));
+            spans.push(Span::raw(s.clone()));
+        }
+        Line::from(spans)
+    };
+    let p = Paragraph::new(text).block(Block::default().borders(Borders::TOP));
+    f.render_widget(p, area);
+}
+
+fn draw_entity_table(f: &mut Frame, area: Rect, app: &App, cfg: &UiConfig) {
+    let now = app.now();
+    let sorted: Vec<EntityState> = app.sorted_entities().into_iter().take(200).collect();
+
+    let mut state = TableState::default();
+    if let Some(sel) = &app.selected {
+        state.select(
+            sorted
+                .iter()
+                .position(|st| &entity_key(&st.scope, &st.id) == sel),
+        );
+    }
+
+    let rows = sorted.into_iter().map(|st| {
+        let live = app.compute_entity_liveness(&st, now);
+        let ack = app.ack_for(&st, now);
+        let cells: Vec<Cell> = cfg
+            .columns
+            .iter()
+            .map(|c| entity_cell(&c.column, &st, live, ack, now, cfg))
+            .collect();
+
+        let row = Row::new(cells);
+        if ack.is_some() {
+            row.style(Style::default().add_modifier(Modifier::DIM))
+        } else {
+            row
+        }
     });
 
-    let header = Row::new(vec![
-        Cell::from("scope"),
-        Cell::from("id"),
-        Cell::from("kind"),
-        Cell::from("level"),
-        Cell::from("state"),
-        Cell::from("age"),
-        Cell::from("last msg"),
-    ])
+    let header = Row::new(
+        cfg.columns
+            .iter()
+            .map(|c| Cell::from(c.column.header()))
+            .collect::<Vec<_>>(),
+    )
     .style(Style::default().add_modifier(Modifier::BOLD));
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(10),
-            Constraint::Length(20),
-            Constraint::Length(14),
-            Constraint::Length(8),
-            Constraint::Length(8),
-            Constraint::Length(6),
-            Constraint::Min(10),
-        ],
-    )
-    .header(header)
-    .block(Block::default().borders(Borders::ALL).title("entities"))
-    .column_spacing(1);
+    // The message column soaks up remaining width wherever it sits.
+    let widths: Vec<Constraint> = cfg
+        .columns
+        .iter()
+        .map(|c| match c.column {
+            Column::Msg => Constraint::Fill(1),
+            _ => Constraint::Length(c.width),
+        })
+        .collect();
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(cfg.theme.block("entities"))
+        .column_spacing(1)
+        .highlight_style(Style::default().bg(cfg.theme.highlight));
 
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+fn entity_cell(
+    column: &Column,
+    st: &EntityState,
+    live: Liveness,
+    ack: Option<&Ack>,
+    now: OffsetDateTime,
+    cfg: &UiConfig,
+) -> Cell<'static> {
+    match column {
+        Column::Scope => Cell::from(st.scope.clone()),
+        Column::Id => Cell::from(st.id.clone()),
+        Column::Kind => Cell::from(st.kind.clone()),
+        Column::Level => Cell::from(st.last_level.as_str().to_string())
+            .style(Style::default().fg(cfg.theme.level_color(st.last_level))),
+        Column::State => Cell::from(live.as_str().to_string())
+            .style(Style::default().fg(cfg.theme.liveness_color(live))),
+        Column::Age => {
+            let age = (now - st.last_seen).whole_seconds();
+            Cell::from(format!("{}s", age.max(0)))
+        }
+        Column::Msg => match ack {
+            Some(a) if a.note.is_empty() => {
+                Cell::from(format!("[{}] {}", a.op.as_str(), st.last_msg))
+            }
+            Some(a) => Cell::from(format!("[{}: {}] {}", a.op.as_str(), a.note, st.last_msg)),
+            None => Cell::from(st.last_msg.clone()),
+        },
+        Column::Kv(k) => Cell::from(st.kv.get(k).cloned().unwrap_or_default()),
+    }
+}
+
+/// Rows for the parse error panel, borders included.
+const ERROR_PANEL_HEIGHT: u16 = 8;
+
+fn draw_errors(f: &mut Frame, area: Rect, app: &App, cfg: &UiConfig) {
+    // Transform stderr shares the panel once a child has said anything.
+    let area = if app.transforms.iter().any(|t| !t.stderr_tail().is_empty()) {
+        let cols = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(area);
+        draw_transform_stderr(f, cols[1], app, cfg);
+        cols[0]
+    } else {
+        area
+    };
+
+    let lines: Vec<Line> = app
+        .line_issues
+        .iter()
+        .map(|issue| {
+            let (source, line_no, offset, raw, reason, color) = match issue {
+                LineIssue::Rejected(r) => (
+                    &r.source,
+                    r.line_no,
+                    r.offset,
+                    &r.raw,
+                    r.error.to_string(),
+                    cfg.theme.level_fail,
+                ),
+                LineIssue::Repaired(r) => (
+                    &r.source,
+                    r.line_no,
+                    r.offset,
+                    &r.raw,
+                    format!(
+                        "repaired: {}",
+                        r.repairs
+                            .iter()
+                            .map(|x| x.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    cfg.theme.level_warn,
+                ),
+            };
+            Line::from(vec![
+                Span::raw(format!("{}:{} @{} ", source, line_no, offset)),
+                Span::styled(reason, Style::default().fg(color)),
+                Span::styled(
+                    format!("  {}", raw.replace('\t', " \u{2192} ")),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+            ])
+        })
+        .collect();
+
+    let title = format!(
+        "parse errors [{} rejected, {} repaired, newest first]",
+        app.parse_errors, app.repaired_lines
+    );
+    let p = Paragraph::new(lines).block(cfg.theme.block(&title));
+    f.render_widget(p, area);
+}
+
+fn draw_transform_stderr(f: &mut Frame, area: Rect, app: &App, cfg: &UiConfig) {
+    // Stage order, each stage's tail oldest first; name the stage in a chain.
+    let chained = app.transforms.len() > 1;
+    let tail: Vec<String> = app
+        .transforms
+        .iter()
+        .flat_map(|t| {
+            t.stderr_tail().into_iter().map(move |l| {
+                if chained {
+                    format!("[{}] {}", t.name, l)
+                } else {
+                    l
+                }
+            })
+        })
+        .collect();
+    let fit = area.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = tail[tail.len().saturating_sub(fit)..]
+        .iter()
+        .map(|l| Line::styled(l.clone(), Style::default().fg(cfg.theme.level_warn)))
+        .collect();
+
+    let crashes: u64 = app
+        .transforms
+        .iter()
+        .map(|t| t.crashes.load(Ordering::Relaxed))
+        .sum();
+    let title = format!("awk stderr [{} crashes]", crashes);
+    let p = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(cfg.theme.block(&title));
+    f.render_widget(p, area);
+}
+
+fn draw_pipeline(f: &mut Frame, area: Rect, app: &App, cfg: &UiConfig) {
+    let rows: Vec<Row> = app
+        .transforms
+        .iter()
+        .enumerate()
+        .map(|(i, t)| {
+            let state = t.state();
+            Row::new(vec![
+                Cell::from(format!("{}", i + 1)),
+                Cell::from(t.name.clone()),
+                Cell::from(state.as_str()).style(Style::default().fg(runner_color(&state, cfg))),
+                Cell::from(t.frames_in.load(Ordering::Relaxed).to_string()),
+                Cell::from(t.frames_out.load(Ordering::Relaxed).to_string()),
+                Cell::from(t.invalid.load(Ordering::Relaxed).to_string()),
+                Cell::from(t.restarts.load(Ordering::Relaxed).to_string()),
+            ])
+        })
+        .collect();
+
+    let header = Row::new(vec!["#", "stage", "state", "in", "out", "invalid", "restarts"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Length(2),
+        Constraint::Fill(1),
+        Constraint::Length(11),
+        Constraint::Length(9),
+        Constraint::Length(9),
+        Constraint::Length(8),
+        Constraint::Length(8),
+    ];
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(cfg.theme.block("pipeline"))
+        .column_spacing(1);
     f.render_widget(table, area);
 }
 
-fn draw_tail(f: &mut Frame, area: Rect, app: &App) {
+fn draw_tail(f: &mut Frame, area: Rect, app: &App, cfg: &UiConfig) {
+    let selected = app.selected_entity();
+
     let mut lines: Vec<Line> = Vec::new();
-    for s in app.tail.iter() {
-        lines.push(Line::from(Span::raw(s.clone())));
+    for fr in app.tail_view() {
+        let ts = fr
+            .ts
+            .map(|t| t.format(&Rfc3339).unwrap_or_default())
+            .unwrap_or_default();
+
+        let mut line = Line::from(vec![
+            Span::raw(format!("{} {} {} {} ", ts, fr.kind, fr.scope, fr.id)),
+            Span::styled(
+                fr.level.as_str(),
+                Style::default().fg(cfg.theme.level_color(fr.level)),
+            ),
+            Span::raw(format!(" {}", fr.msg)),
+        ]);
+
+        let is_selected = selected.is_some_and(|st| st.scope == fr.scope && st.id == fr.id);
+        if is_selected {
+            line = line.style(
+                Style::default()
+                    .bg(cfg.theme.highlight)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+        lines.push(line);
     }
 
+    let title = if app.tail_paused {
+        format!("event tail [paused, {} newer]", app.tail_scroll)
+    } else {
+        "event tail".to_string()
+    };
+
     let p = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title("event tail"))
+        .block(cfg.theme.block(&title))
         .wrap(Wrap { trim: false });
 
     f.render_widget(p, area);
 }
 // :: ∎
+
+#[cfg(test)]
+mod tests {
+    //! Golden buffers for `draw_app`. Regenerate after an intentional layout
+    //! change with `HAWK_UPDATE_SNAPSHOTS=1 cargo test -p hawk draw`.
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use hawk_core::{HawkFrame, Level};
+    use ratatui::{backend::TestBackend, Terminal};
+    use time::macros::datetime;
+    use time::Duration;
+
+    use super::*;
+    use crate::ack::{Ack, AckOp};
+    use crate::clock::ManualClock;
+    use crate::io::IngestMsg;
+
+    const T0: OffsetDateTime = datetime!(2026-02-16 12:00:00 UTC);
+
+    fn frame(secs_ago: i64, scope: &str, id: &str, level: Level, msg: &str) -> HawkFrame {
+        HawkFrame {
+            ts: Some(T0 - Duration::seconds(secs_ago)),
+            kind: "HEALTH".to_string(),
+            scope: scope.to_string(),
+            id: id.to_string(),
+            level,
+            level_raw: None,
+            msg: msg.to_string(),
+            kv: Default::default(),
+        }
+    }
+
+    fn fixture_app() -> App {
+        let mut app = App::new(50, 50, 10, 30);
+        app.clock = Arc::new(ManualClock::new(T0));
+        for f in [
+            frame(45, "systemd", "db-primary.service", Level::Ok, "active"),
+            frame(12, "docker", "3f2a9c", Level::Warn, "restarting"),
+            frame(8, "grpc", "proto.beta", Level::Info, "serving"),
+            frame(3, "grpc", "proto.alpha", Level::Fail, "deadline exceeded"),
+            frame(1, "agent", "hawk-agent", Level::Ok, "alive"),
+        ] {
+            app.ingest(IngestMsg::Frame(f));
+        }
+        app.acks.apply(Ack::new(
+            AckOp::Ack,
+            "docker",
+            "3f2a9c",
+            "known flake",
+            None,
+            T0,
+        ));
+        app
+    }
+
+    fn render(app: &App, cfg: &UiConfig, w: u16, h: u16) -> String {
+        let mut term = Terminal::new(TestBackend::new(w, h)).unwrap();
+        term.draw(|f| draw_app(f, app, cfg)).unwrap();
+
+        let buf = term.backend().buffer();
+        let mut out = String::new();
+        for y in 0..buf.area.height {
+            let line: String = (0..buf.area.width)
+                .map(|x| buf.get(x, y).symbol())
+                .collect();
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    fn assert_golden(name: &str, got: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/ui/snapshots")
+            .join(format!("{}.txt", name));
+
+        if std::env::var_os("HAWK_UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, got).unwrap();
+            return;
+        }
+
+        let want = std::fs::read_to_string(&path)
+            .unwrap_or_else(|_| {
+                panic!(
+                    "missing golden {:?}; run with HAWK_UPDATE_SNAPSHOTS=1 to create it",
+                    path
+                )
+            })
+            // A CRLF checkout must not fail every golden.
+            .replace("\r\n", "\n");
+        assert!(
+            want == got,
+            "{} differs from golden {:?}\n--- want\n{}--- got\n{}",
+            name,
+            path,
+            want,
+            got
+        );
+    }
+
+    #[test]
+    fn draw_app_at_terminal_sizes() {
+        let app = fixture_app();
+        let cfg = UiConfig::default();
+        for (w, h) in [(80, 24), (120, 30), (160, 12)] {
+            assert_golden(&format!("app_{}x{}", w, h), &render(&app, &cfg, w, h));
+        }
+    }
+
+    #[test]
+    fn draw_app_selected_and_paused() {
+        let mut app = fixture_app();
+        app.select_next();
+        app.select_next();
+        app.toggle_pause();
+        let f = frame(0, "grpc", "proto.alpha", Level::Ok, "recovered");
+        app.ingest(IngestMsg::Frame(f));
+        app.status = Some("ack docker:3f2a9c".to_string());
+        let got = render(&app, &UiConfig::default(), 120, 30);
+        assert_golden("app_selected_paused", &got);
+    }
+
+    #[test]
+    fn draw_app_with_sources() {
+        let mut app = fixture_app();
+        for (name, status, frames) in [
+            ("stdin", SourceStatus::Closed, 3),
+            ("spine", SourceStatus::Connected, 2),
+            ("edge", SourceStatus::Failed("refused".into()), 0),
+        ] {
+            let src = crate::io::SourceStats::new(name);
+            src.set_status(status);
+            src.frames.store(frames, Ordering::Relaxed);
+            app.sources.push(Arc::new(src));
+        }
+        assert_golden("app_sources", &render(&app, &UiConfig::default(), 120, 20));
+    }
+
+    #[test]
+    fn draw_app_error_panel() {
+        use hawk_core::{HawkFrameParseError, RejectedLine};
+
+        let mut app = fixture_app();
+        app.ingest(IngestMsg::ParseError(RejectedLine::new(
+            "stdin",
+            7,
+            512,
+            "2026-02-16T12:00:00Z\tHEALTH\tgrpc",
+            HawkFrameParseError::TooFewColumns { found: 3 },
+        )));
+        app.ingest(IngestMsg::ParseError(RejectedLine::new(
+            "spine",
+            42,
+            9001,
+            "yesterday\tHEALTH\tgrpc\tx\tok\tmsg\t",
+            HawkFrameParseError::BadTimestamp {
+                raw: "yesterday".to_string(),
+            },
+        )));
+        app.ingest(IngestMsg::Repaired(hawk_core::RepairedLine::new(
+            "stdin",
+            9,
+            600,
+            "1771243200\tHEALTH\tgrpc\tproto.gamma\tok\tup",
+            vec![
+                hawk_core::Repair::MissingKv,
+                hawk_core::Repair::EpochSeconds,
+            ],
+        )));
+        app.toggle_errors();
+        assert_golden("app_errors", &render(&app, &UiConfig::default(), 120, 30));
+    }
+
+    #[test]
+    fn draw_app_transform_restarting() {
+        use crate::awk::{RunnerState, RunnerStats};
+
+        let mut app = fixture_app();
+        let focus = RunnerStats::new("thread:systemd_focus");
+        focus.frames_in.store(120, Ordering::Relaxed);
+        focus.frames_out.store(40, Ordering::Relaxed);
+        let t = RunnerStats::new("thread:scope_counts");
+        t.set_state(RunnerState::Restarting);
+        t.frames_in.store(40, Ordering::Relaxed);
+        t.invalid.store(1, Ordering::Relaxed);
+        t.crashes.store(3, Ordering::Relaxed);
+        t.restarts.store(2, Ordering::Relaxed);
+        t.push_stderr("awk: cmd. line:4: (FILENAME=- FNR=12) fatal: division by zero attempted".into());
+        app.transforms = vec![Arc::new(focus), Arc::new(t)];
+        app.toggle_errors();
+        app.toggle_pipeline();
+        assert_golden("app_transform", &render(&app, &UiConfig::default(), 120, 28));
+    }
+
+    #[test]
+    fn draw_app_derived_panel() {
+        // Rows come from the real hawk.core scope_counts thread, flushed by
+        // two ticks a window apart.
+        let script = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../../packs/hawk.core/scope_counts.awk");
+        let prog = Arc::new(crate::awk::compile_script(&script).unwrap());
+        let tvars = [("window_s".to_string(), "10".to_string())].into();
+        let mut it = crate::awk::interp::Interp::new(prog, &tvars);
+        it.begin().unwrap();
+
+        let mut app = fixture_app();
+        let mut input: Vec<String> = app.tail.iter().map(|f| f.to_tsv_line(T0)).collect();
+        for secs_ago in [12, 2] {
+            let ts = T0 - Duration::seconds(secs_ago);
+            input.push(format!(
+                "{}\tTICK\thawk\ttick\tinfo\ttick\tepoch={}",
+                ts.format(&time::format_description::well_known::Rfc3339).unwrap(),
+                ts.unix_timestamp()
+            ));
+        }
+        for line in &input {
+            it.record(line).unwrap();
+        }
+        it.end().unwrap();
+
+        for line in it.out.lines() {
+            let f = HawkFrame::parse_tsv_line(line).unwrap().unwrap();
+            app.ingest(IngestMsg::Derived(f));
+        }
+        assert_eq!(app.derived.len(), 5);
+        assert_golden("app_derived", &render(&app, &UiConfig::default(), 120, 30));
+    }
+
+    #[test]
+    fn draw_app_prompt_and_columns() {
+        let mut app = fixture_app();
+        app.select_next();
+        app.open_ack_prompt();
+        if let Some(p) = app.prompt.as_mut() {
+            p.input = "2h maintenance".to_string();
+        }
+        let cfg = UiConfig {
+            columns: ["id", "state", "age", "kind", "msg"]
+                .iter()
+                .map(|c| {
+                    let column = Column::parse(c).unwrap();
+                    crate::config::ColumnSpec {
+                        width: column.default_width(),
+                        column,
+                    }
+                })
+                .collect(),
+            split_pct: 50,
+            ..UiConfig::default()
+        };
+        assert_golden("app_prompt_columns", &render(&app, &cfg, 120, 24));
+    }
+
+    #[test]
+    fn draw_app_transform_picker() {
+        use crate::app::SwitchHandle;
+        use crate::packs::{ArgSpec, ArgType, PackIndex, ThreadOutput, ThreadResolved};
+        use crate::pipeline::TransformSwitch;
+
+        let thread = |id: &str, kind: &str, title: &str, args: &[(&str, &str, &str)]| {
+            ThreadResolved {
+                pack_id: "hawk.core".to_string(),
+                thread_id: id.to_string(),
+                title: title.to_string(),
+                kind: kind.to_string(),
+                description: String::new(),
+                script_path: format!("{}.awk", id).into(),
+                args: args
+                    .iter()
+                    .map(|(name, default, help)| ArgSpec {
+                        name: name.to_string(),
+                        ty: ArgType::String,
+                        default: default.to_string(),
+                        help: help.to_string(),
+                        ..ArgSpec::default()
+                    })
+                    .collect(),
+                output: ThreadOutput::Replace,
+                tick: None,
+                sandbox: Default::default(),
+                sha256: None,
+            }
+        };
+        let mut focus = thread(
+            "systemd_focus",
+            "filter",
+            "Focus one unit",
+            &[("unit", "", "systemd unit"), ("min_level", "warn", "")],
+        );
+        focus.args[1].ty = ArgType::Enum;
+        focus.args[1].choices = vec!["info".to_string(), "warn".to_string(), "fail".to_string()];
+        let threads = [thread("fail_only", "filter", "Failures only", &[]), focus];
+
+        let mut app = fixture_app();
+        let (_tx_source, rx_source) = std::sync::mpsc::channel();
+        let (tx_ui, _rx_ui) = std::sync::mpsc::channel();
+        app.switch = Some(SwitchHandle {
+            switch: TransformSwitch::start(rx_source, tx_ui, Default::default()),
+            packs: PackIndex {
+                packs: Vec::new(),
+                threads: threads.into_iter().map(|t| (t.qualified_id(), t)).collect(),
+                warnings: Vec::new(),
+            },
+            output: None,
+        });
+        app.open_picker();
+        app.picker_move(2);
+        assert_golden("app_picker", &render(&app, &UiConfig::default(), 100, 20));
+
+        // Through the real submit, so the error is what validation says.
+        let typed = |app: &mut App, s: &str| s.chars().for_each(|c| app.picker_input(Some(c)));
+        app.picker_submit();
+        typed(&mut app, "hawkd.service");
+        app.picker_move(1);
+        (0..4).for_each(|_| app.picker_input(None));
+        typed(&mut app, "loud");
+        app.picker_submit();
+        assert_golden("app_picker_form", &render(&app, &UiConfig::default(), 100, 20));
+    }
+}
+// :: ∎
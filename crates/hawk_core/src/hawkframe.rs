@@ -7,6 +7,19 @@ use time::OffsetDateTime;
 
 use crate::severity::Level;
 
+/// Comment line hawkd writes after replaying cached frames to a new client.
+pub const REPLAY_DONE_MARKER: &str = "# hawkd replay done";
+
+/// Frames with this kind carry acks instead of entity state.
+pub const ACK_KIND: &str = "ACK";
+
+/// Frames with this kind remove their scope:id entity.
+pub const RETIRE_KIND: &str = "RETIRE";
+
+/// Reserved kind of the synthetic frames fed to `needs_tick` threads.
+/// `$1` is the current time and `$7` carries `epoch=<unix seconds>`.
+pub const TICK_KIND: &str = "TICK";
+
 #[derive(Debug, Clone)]
 pub struct HawkFrame {
     pub ts: Option<OffsetDateTime>,
@@ -14,6 +27,9 @@ pub struct HawkFrame {
     pub scope: String,
     pub id: String,
     pub level: Level,
+    /// Producer's level text when it differs from `level.as_str()`
+    /// (`WARNING`, `3`, `emerg`); emitted back unchanged by `to_tsv_line`.
+    pub level_raw: Option<String>,
     pub msg: String,
     /// kv bag: key=value;key=value (no spaces required, but supported)
     pub kv: BTreeMap<String, String>,
@@ -23,9 +39,153 @@ pub struct HawkFrame {
 pub enum HawkFrameParseError {
     Empty,
     TooFewColumns { found: usize },
+    /// Strict mode only; lenient parsing drops the extras.
+    TooManyColumns { found: usize },
     BadTimestamp { raw: String },
 }
 
+impl std::fmt::Display for HawkFrameParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HawkFrameParseError::Empty => write!(f, "empty line"),
+            HawkFrameParseError::TooFewColumns { found } => {
+                write!(f, "too few columns: {} of 7", found)
+            }
+            HawkFrameParseError::TooManyColumns { found } => {
+                write!(f, "too many columns: {} of 7", found)
+            }
+            HawkFrameParseError::BadTimestamp { raw } => write!(f, "bad timestamp: {}", raw),
+        }
+    }
+}
+
+/// How forgiving `parse_tsv_line_with` is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Exactly 7 tab-separated columns, RFC3339 or empty timestamp.
+    Strict,
+    /// Strict, but columns past the 7th are dropped (and reported).
+    #[default]
+    Lenient,
+    /// Lenient, plus: 6 columns (missing kv), epoch seconds/millis
+    /// timestamps, and space-separated lines.
+    Repair,
+}
+
+impl ParseMode {
+    pub fn parse(s: &str) -> Result<ParseMode, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "strict" => Ok(ParseMode::Strict),
+            "lenient" => Ok(ParseMode::Lenient),
+            "repair" => Ok(ParseMode::Repair),
+            other => Err(format!("unknown parse mode {} (strict|lenient|repair)", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+    pub mode: ParseMode,
+}
+
+/// Something the parser had to fix or drop to accept a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Repair {
+    /// More than 7 columns; the extras were dropped.
+    ExtraColumns { found: usize },
+    /// 6 columns; kv taken as empty.
+    MissingKv,
+    EpochSeconds,
+    EpochMillis,
+    /// No tabs; columns split on whitespace.
+    SpaceDelimited,
+}
+
+impl std::fmt::Display for Repair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Repair::ExtraColumns { found } => write!(f, "dropped extra columns: {} of 7", found),
+            Repair::MissingKv => write!(f, "missing kv column"),
+            Repair::EpochSeconds => write!(f, "epoch seconds timestamp"),
+            Repair::EpochMillis => write!(f, "epoch millis timestamp"),
+            Repair::SpaceDelimited => write!(f, "space separated columns"),
+        }
+    }
+}
+
+/// A parsed frame plus the repairs it needed. Empty `repairs` means the
+/// line was well-formed.
+#[derive(Debug, Clone)]
+pub struct Parsed {
+    pub frame: HawkFrame,
+    pub repairs: Vec<Repair>,
+}
+
+/// Longest raw text kept on a `RejectedLine`.
+pub const REJECTED_RAW_CLIP: usize = 160;
+
+/// A line that failed to parse, with where it came from.
+#[derive(Debug, Clone)]
+pub struct RejectedLine {
+    pub source: String,
+    /// 1-based line number within the source.
+    pub line_no: u64,
+    /// Byte offset of the line start within the source.
+    pub offset: u64,
+    /// Offending text, clipped to `REJECTED_RAW_CLIP` chars.
+    pub raw: String,
+    pub error: HawkFrameParseError,
+}
+
+impl RejectedLine {
+    pub fn new(
+        source: &str,
+        line_no: u64,
+        offset: u64,
+        raw: &str,
+        error: HawkFrameParseError,
+    ) -> Self {
+        Self {
+            source: source.to_string(),
+            line_no,
+            offset,
+            raw: clip_raw(raw),
+            error,
+        }
+    }
+}
+
+/// A line accepted only after repairs (see `ParseMode::Repair`).
+#[derive(Debug, Clone)]
+pub struct RepairedLine {
+    pub source: String,
+    pub line_no: u64,
+    pub offset: u64,
+    /// Original text, clipped like `RejectedLine::raw`.
+    pub raw: String,
+    pub repairs: Vec<Repair>,
+}
+
+impl RepairedLine {
+    pub fn new(source: &str, line_no: u64, offset: u64, raw: &str, repairs: Vec<Repair>) -> Self {
+        Self {
+            source: source.to_string(),
+            line_no,
+            offset,
+            raw: clip_raw(raw),
+            repairs,
+        }
+    }
+}
+
+fn clip_raw(raw: &str) -> String {
+    let raw = raw.trim_end_matches(['\n', '\r']);
+    match raw.char_indices().nth(REJECTED_RAW_CLIP) {
+        Some((i, _)) => format!("{}...", &raw[..i]),
+        None => raw.to_string(),
+    }
+}
+
 impl HawkFrame {
     /// TSV columns (7):
     /// ts, kind, scope, id, level, msg, kv
@@ -33,6 +193,14 @@ impl HawkFrame {
     /// Example:
     /// 2026-02-16T12:34:56Z  RECEIPT_EVENT  service  tape  ok  receipt written  trace_id=abc;status=ok
     pub fn parse_tsv_line(line: &str) -> Result<Option<HawkFrame>, HawkFrameParseError> {
+        Self::parse_tsv_line_with(line, &ParseOptions::default()).map(|p| p.map(|p| p.frame))
+    }
+
+    /// Parse with an explicit mode, reporting any repairs made.
+    pub fn parse_tsv_line_with(
+        line: &str,
+        opts: &ParseOptions,
+    ) -> Result<Option<Parsed>, HawkFrameParseError> {
         let line = line.trim_end_matches(['\n', '\r']);
         if line.trim().is_empty() {
             return Ok(None);
@@ -41,10 +209,28 @@ impl HawkFrame {
             return Ok(None);
         }
 
-        let cols: Vec<&str> = line.split('\t').collect();
+        let repair = opts.mode == ParseMode::Repair;
+        let mut repairs = Vec::new();
+
+        let mut cols: Vec<&str> = line.split('\t').collect();
+        if repair && cols.len() == 1 {
+            cols = split_spaced(line);
+            repairs.push(Repair::SpaceDelimited);
+        }
+
+        if cols.len() == 6 && repair {
+            cols.push("");
+            repairs.push(Repair::MissingKv);
+        }
         if cols.len() < 7 {
             return Err(HawkFrameParseError::TooFewColumns { found: cols.len() });
         }
+        if cols.len() > 7 {
+            if opts.mode == ParseMode::Strict {
+                return Err(HawkFrameParseError::TooManyColumns { found: cols.len() });
+            }
+            repairs.push(Repair::ExtraColumns { found: cols.len() });
+        }
 
         let raw_ts = cols[0].trim();
         let ts = if raw_ts.is_empty() {
@@ -52,50 +238,43 @@ impl HawkFrame {
         } else {
             match OffsetDateTime::parse(raw_ts, &Rfc3339) {
                 Ok(v) => Some(v),
-                Err(_) => {
-                    return Err(HawkFrameParseError::BadTimestamp {
-                        raw: raw_ts.to_string(),
-                    })
-                }
+                Err(_) => match parse_epoch(raw_ts).filter(|_| repair) {
+                    Some((v, fix)) => {
+                        repairs.push(fix);
+                        Some(v)
+                    }
+                    None => {
+                        return Err(HawkFrameParseError::BadTimestamp {
+                            raw: raw_ts.to_string(),
+                        })
+                    }
+                },
             }
         };
 
         let kind = cols[1].trim().to_string();
         let scope = cols[2].trim().to_string();
         let id = cols[3].trim().to_string();
-        let level = Level::parse(cols[4]);
+        let level_txt = cols[4].trim();
+        let level = Level::parse(level_txt);
+        let level_raw = (level_txt != level.as_str()).then(|| level_txt.to_string());
 
         let msg = cols[5].trim().to_string();
         let kv_raw = cols[6].trim();
 
         let kv = parse_kv_bag(kv_raw);
 
-        Ok(Some(HawkFrame {
+        let frame = HawkFrame {
             ts,
             kind,
             scope,
             id,
             level,
+            level_raw,
             msg,
             kv,
-        }))
-    }
-
-    pub fn to_compact_line(&self) -> String {
-        // For log tail display: keep it human and short.
-        let ts = self
-            .ts
-            .map(|t| t.format(&Rfc3339).unwrap_or_default())
-            .unwrap_or_default();
-        format!(
-            "{}\t{}\t{}\t{}\t{}\t{}",
-            ts,
-            self.kind,
-            self.scope,
-            self.id,
-            self.level.as_str(),
-            self.msg
-        )
+        };
+        Ok(Some(Parsed { frame, repairs }))
     }
 
     /// Emit a full TSV line suitable for piping to hawk or awk.
@@ -110,11 +289,70 @@ impl HawkFrame {
             self.kind,
             self.scope,
             self.id,
-            self.level.as_str(),
+            self.level_str(),
             self.msg,
             kv
         )
     }
+
+    /// Level as the producer wrote it.
+    pub fn level_str(&self) -> &str {
+        self.level_raw.as_deref().unwrap_or(self.level.as_str())
+    }
+}
+
+/// Space-separated fallback: ts kind scope id level, then msg words, with a
+/// trailing `k=v;...` token taken as kv. Yields 6 columns when there is no kv.
+fn split_spaced(line: &str) -> Vec<&str> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if words.len() < 6 {
+        return words;
+    }
+
+    let mut cols = words[..5].to_vec();
+    let rest = &words[5..];
+    let (msg_words, kv) = match rest.split_last() {
+        Some((last, init)) if last.contains('=') => (init, Some(*last)),
+        _ => (rest, None),
+    };
+
+    // Keep msg as a slice of the original line so inner spacing survives.
+    let msg = match (msg_words.first(), msg_words.last()) {
+        (Some(first), Some(last)) => {
+            let start = first.as_ptr() as usize - line.as_ptr() as usize;
+            let end = last.as_ptr() as usize - line.as_ptr() as usize + last.len();
+            &line[start..end]
+        }
+        _ => "",
+    };
+    cols.push(msg);
+    if let Some(kv) = kv {
+        cols.push(kv);
+    }
+    cols
+}
+
+/// Unix epoch seconds (optionally fractional) or milliseconds.
+fn parse_epoch(raw: &str) -> Option<(OffsetDateTime, Repair)> {
+    if raw.is_empty() || !raw.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let (whole, frac) = raw.split_once('.').unwrap_or((raw, ""));
+    let n: i64 = whole.parse().ok()?;
+
+    // 1e11 seconds is year 5138; anything larger is milliseconds.
+    if n >= 100_000_000_000 && frac.is_empty() {
+        let t = OffsetDateTime::from_unix_timestamp_nanos(n as i128 * 1_000_000).ok()?;
+        return Some((t, Repair::EpochMillis));
+    }
+
+    let mut nanos = 0i128;
+    if !frac.is_empty() {
+        let digits: String = frac.chars().chain(std::iter::repeat('0')).take(9).collect();
+        nanos = digits.parse().ok()?;
+    }
+    let t = OffsetDateTime::from_unix_timestamp_nanos(n as i128 * 1_000_000_000 + nanos).ok()?;
+    Some((t, Repair::EpochSeconds))
 }
 
 fn parse_kv_bag(s: &str) -> BTreeMap<String, String> {
@@ -176,6 +414,66 @@ mod tests {
         assert!(f.is_none());
     }
 
+    #[test]
+    fn keeps_raw_level_for_round_trip() {
+        let line = "2026-02-16T12:34:56Z\tHEALTH\tservice\talpha\tWARNING\tslow\t";
+        let f = HawkFrame::parse_tsv_line(line).unwrap().unwrap();
+        assert_eq!(f.level, Level::Warn);
+        assert_eq!(f.level_raw.as_deref(), Some("WARNING"));
+        assert_eq!(f.to_tsv_line(OffsetDateTime::now_utc()), line);
+
+        let f = HawkFrame::parse_tsv_line(&line.replace("WARNING", "warn"))
+            .unwrap()
+            .unwrap();
+        assert!(f.level_raw.is_none());
+    }
+
+    #[test]
+    fn parse_modes() {
+        let repair = ParseOptions {
+            mode: ParseMode::Repair,
+        };
+        let strict = ParseOptions {
+            mode: ParseMode::Strict,
+        };
+
+        let six = "1771245296\tHEALTH\tservice\talpha\tok\talive";
+        assert!(HawkFrame::parse_tsv_line(six).is_err());
+        let p = HawkFrame::parse_tsv_line_with(six, &repair).unwrap().unwrap();
+        assert_eq!(p.repairs, vec![Repair::MissingKv, Repair::EpochSeconds]);
+        assert_eq!(p.frame.ts.unwrap().unix_timestamp(), 1771245296);
+
+        let ms = "1771245296123\tHEALTH\tservice\talpha\tok\talive\t";
+        let p = HawkFrame::parse_tsv_line_with(ms, &repair).unwrap().unwrap();
+        assert_eq!(p.repairs, vec![Repair::EpochMillis]);
+        assert_eq!(p.frame.ts.unwrap().millisecond(), 123);
+
+        let wide = "\tHEALTH\tservice\talpha\tok\talive\tpid=1\textra";
+        let p = HawkFrame::parse_tsv_line_with(wide, &ParseOptions::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(p.repairs, vec![Repair::ExtraColumns { found: 8 }]);
+        assert!(matches!(
+            HawkFrame::parse_tsv_line_with(wide, &strict),
+            Err(HawkFrameParseError::TooManyColumns { found: 8 })
+        ));
+
+        let spaced = "2026-02-16T12:34:56Z HEALTH grpc proto.alpha warn slow  reply pid=7";
+        let p = HawkFrame::parse_tsv_line_with(spaced, &repair).unwrap().unwrap();
+        assert_eq!(p.repairs, vec![Repair::SpaceDelimited]);
+        assert_eq!(p.frame.msg, "slow  reply");
+        assert_eq!(p.frame.kv.get("pid").unwrap(), "7");
+    }
+
+    #[test]
+    fn rejected_line_clips_raw() {
+        let raw = "x".repeat(REJECTED_RAW_CLIP + 10) + "\n";
+        let r = RejectedLine::new("stdin", 3, 40, &raw, HawkFrameParseError::Empty);
+        assert_eq!(r.raw.len(), REJECTED_RAW_CLIP + 3);
+        assert!(r.raw.ends_with("..."));
+        assert_eq!(r.error.to_string(), "empty line");
+    }
+
     #[test]
     fn emits_tsv_with_kv() {
         let line =
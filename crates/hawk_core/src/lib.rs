@@ -3,6 +3,9 @@
 pub mod hawkframe;
 pub mod severity;
 
-pub use hawkframe::{HawkFrame, HawkFrameParseError};
+pub use hawkframe::{
+    HawkFrame, HawkFrameParseError, ParseMode, ParseOptions, Parsed, RejectedLine, Repair,
+    RepairedLine, ACK_KIND, REPLAY_DONE_MARKER, RETIRE_KIND, TICK_KIND,
+};
 pub use severity::{Level, LevelRank};
 // :: ∎
@@ -2,33 +2,50 @@
 // @ctx ⫸ [level.parse.order]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Level {
+    Debug,
     Ok,
     Info,
+    Notice,
     Warn,
     Fail,
+    Critical,
     Unknown,
 }
 
 impl Level {
+    /// Names are case-insensitive. Digits 0-7 are syslog severities.
     pub fn parse(s: &str) -> Self {
         match s.trim().to_ascii_lowercase().as_str() {
+            "debug" | "trace" | "7" => Level::Debug,
             "ok" => Level::Ok,
-            "info" => Level::Info,
-            "warn" | "warning" => Level::Warn,
-            "fail" | "error" | "fatal" => Level::Fail,
+            "info" | "6" => Level::Info,
+            "notice" | "5" => Level::Notice,
+            "warn" | "warning" | "4" => Level::Warn,
+            "fail" | "error" | "err" | "fatal" | "3" => Level::Fail,
+            "critical" | "crit" | "alert" | "emerg" | "emergency" | "panic" | "2" | "1" | "0" => {
+                Level::Critical
+            }
             _ => Level::Unknown,
         }
     }
 
     pub fn as_str(&self) -> &'static str {
         match self {
+            Level::Debug => "debug",
             Level::Ok => "ok",
             Level::Info => "info",
+            Level::Notice => "notice",
             Level::Warn => "warn",
             Level::Fail => "fail",
+            Level::Critical => "critical",
             Level::Unknown => "unknown",
         }
     }
+
+    /// Healthy levels: nothing for an operator to look at.
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, Level::Debug | Level::Ok | Level::Info | Level::Notice)
+    }
 }
 
 /// Smaller is worse so sorting ascending puts failures first.
@@ -38,12 +55,40 @@ pub struct LevelRank(pub u8);
 impl From<Level> for LevelRank {
     fn from(l: Level) -> Self {
         match l {
-            Level::Fail => LevelRank(0),
-            Level::Warn => LevelRank(1),
-            Level::Info => LevelRank(2),
-            Level::Ok => LevelRank(3),
-            Level::Unknown => LevelRank(4),
+            Level::Critical => LevelRank(0),
+            Level::Fail => LevelRank(1),
+            Level::Warn => LevelRank(2),
+            // Unknown is not known to be fine, so it sits above the healthy levels.
+            Level::Unknown => LevelRank(3),
+            Level::Notice => LevelRank(4),
+            Level::Info => LevelRank(5),
+            Level::Ok => LevelRank(6),
+            Level::Debug => LevelRank(7),
         }
     }
 }
 // :: ∎
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names_and_syslog_numbers() {
+        assert_eq!(Level::parse("CRIT"), Level::Critical);
+        assert_eq!(Level::parse("2"), Level::Critical);
+        assert_eq!(Level::parse("3"), Level::Fail);
+        assert_eq!(Level::parse("notice"), Level::Notice);
+        assert_eq!(Level::parse("7"), Level::Debug);
+        assert_eq!(Level::parse("8"), Level::Unknown);
+    }
+
+    #[test]
+    fn critical_ranks_above_fail_and_unknown_above_ok() {
+        let rank = LevelRank::from;
+        assert!(rank(Level::Critical) < rank(Level::Fail));
+        assert!(rank(Level::Unknown) < rank(Level::Ok));
+        assert!(!Level::Unknown.is_healthy());
+    }
+}
+// :: ∎
@@ -60,7 +60,7 @@ pub fn parse_watch_spec(s: &str) -> Result<WatchSpec, String> {
     //   service:8443,My.Service,svc.my
     let parts: Vec<&str> = s.split(',').collect();
 
-    let endpoint = parts.get(0).map(|v| v.trim()).unwrap_or("");
+    let endpoint = parts.first().map(|v| v.trim()).unwrap_or("");
     if endpoint.is_empty() {
         return Err("watch spec missing endpoint".to_string());
     }
@@ -71,11 +71,11 @@ pub fn parse_watch_spec(s: &str) -> Result<WatchSpec, String> {
         Some(v) if !v.is_empty() => v.to_string(),
         _ => {
             let mut base = endpoint.replace("http://", "").replace("https://", "");
-            base = base.replace('/', "_").replace(':', "_");
+            base = base.replace(['/', ':'], "_");
             if service.is_empty() {
                 format!("grpc.{}", base)
             } else {
-                let svc = service.replace('/', "_").replace(':', "_");
+                let svc = service.replace(['/', ':'], "_");
                 format!("grpc.{}.{}", base, svc)
             }
         }
@@ -305,6 +305,7 @@ fn emit_status_frame(
         scope: "grpc".to_string(),
         id: spec.id.clone(),
         level,
+        level_raw: None,
         msg: msg.to_string(),
         kv,
     };
@@ -336,6 +337,7 @@ fn emit_error_frame(
         scope: "grpc".to_string(),
         id: spec.id.clone(),
         level: Level::Fail,
+        level_raw: None,
         msg: msg.to_string(),
         kv,
     };
@@ -1,13 +1,15 @@
 // ▛▞// HAWKD :: Spine (broadcast + ingest + grpc watch)
 // @ctx ⫸ [accept.broadcast.broadcastloop]
 use anyhow::Context;
-use clap::{Parser, ValueEnum};
+use clap::{ArgAction, Parser, ValueEnum};
 
+use std::collections::BTreeMap;
 use std::io::{self, BufRead, Write};
 use std::path::PathBuf;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
 use std::sync::{Arc, Mutex};
 
-use hawk_core::HawkFrame;
+use hawk_core::{HawkFrame, ACK_KIND, REPLAY_DONE_MARKER, RETIRE_KIND};
 use time::OffsetDateTime;
 
 #[cfg(unix)]
@@ -54,6 +56,11 @@ struct Cli {
     #[arg(long, default_value_t = true)]
     client_banner: bool,
 
+    /// Replay the latest frame per entity to new clients, then a
+    /// "# hawkd replay done" marker. `--replay false` turns it off.
+    #[arg(long, default_value_t = true, action = ArgAction::Set)]
+    replay: bool,
+
     /// gRPC health watch spec (repeatable).
     /// format: endpoint,service,id
     #[arg(long = "watch", value_parser = grpc_health::parse_watch_spec)]
@@ -108,6 +115,53 @@ enum Source {
 }
 // :: ∎
 
+// ▛▞// spine state :: hawkd.spine
+// @ctx ⫸ [clients.replay.cache]
+/// Frames a client may fall behind by before hawkd drops it.
+#[cfg(unix)]
+const CLIENT_QUEUE: usize = 4096;
+
+/// Connected UI clients plus the replay cache, under one lock so a new
+/// client never misses or double-sees a frame between replay and live.
+/// Each client has its own writer thread, so a slow socket never holds
+/// the lock.
+#[cfg(unix)]
+struct Spine {
+    clients: Vec<SyncSender<Vec<u8>>>,
+    /// Latest frame line per scope:id (ACK frames keyed separately).
+    latest: BTreeMap<String, Vec<u8>>,
+    replay: bool,
+}
+
+#[cfg(unix)]
+impl Spine {
+    fn record(&mut self, bytes: &[u8]) {
+        if !self.replay {
+            return;
+        }
+        let Ok(line) = std::str::from_utf8(bytes) else {
+            return;
+        };
+        let Ok(Some(frame)) = HawkFrame::parse_tsv_line(line) else {
+            return;
+        };
+
+        let key = format!("{}:{}", frame.scope, frame.id);
+        match frame.kind.as_str() {
+            RETIRE_KIND => {
+                self.latest.remove(&key);
+            }
+            ACK_KIND => {
+                self.latest.insert(format!("{}|{}", ACK_KIND, key), bytes.to_vec());
+            }
+            _ => {
+                self.latest.insert(key, bytes.to_vec());
+            }
+        }
+    }
+}
+// :: ∎
+
 // ▛▞// main :: hawkd.main
 // @ctx ⫸ [accept.broadcast.broadcastloop]
 fn main() -> anyhow::Result<()> {
@@ -127,16 +181,20 @@ fn main() -> anyhow::Result<()> {
         let listener = UnixListener::bind(&cli.socket_path)
             .with_context(|| format!("bind broadcast socket at {:?}", cli.socket_path))?;
 
-        let clients: Arc<Mutex<Vec<UnixStream>>> = Arc::new(Mutex::new(Vec::new()));
+        let spine: Arc<Mutex<Spine>> = Arc::new(Mutex::new(Spine {
+            clients: Vec::new(),
+            latest: BTreeMap::new(),
+            replay: cli.replay,
+        }));
 
         // Central spine channel: producers emit bytes, broadcaster writes to UI clients.
         let (spine_tx, spine_rx) = std::sync::mpsc::channel::<Vec<u8>>();
 
         // Broadcast accept loop.
         {
-            let clients = Arc::clone(&clients);
+            let spine = Arc::clone(&spine);
             let banner = cli.client_banner;
-            std::thread::spawn(move || accept_loop(listener, clients, banner));
+            std::thread::spawn(move || accept_loop(listener, spine, banner));
         }
 
         // Optional ingest socket.
@@ -184,7 +242,7 @@ fn main() -> anyhow::Result<()> {
 
         // Broadcast loop.
         for bytes in spine_rx {
-            broadcast(&clients, &bytes);
+            broadcast(&spine, &bytes);
         }
 
         Ok(())
@@ -194,25 +252,61 @@ fn main() -> anyhow::Result<()> {
 
 // ▛▞// accept_loop :: hawkd.sock.accept
 // ⫸ [sock.accept.push]
+/// Registers the client and snapshots the replay cache under the lock, so
+/// live frames queue behind the replay; the writes happen off the lock.
 #[cfg(unix)]
-fn accept_loop(listener: UnixListener, clients: Arc<Mutex<Vec<UnixStream>>>, banner: bool) {
+fn accept_loop(listener: UnixListener, spine: Arc<Mutex<Spine>>, banner: bool) {
     for incoming in listener.incoming() {
-        match incoming {
-            Ok(mut stream) => {
-                if banner {
-                    let _ = stream.write_all(b"# hawkd connected\n");
-                }
-                let _ = stream.flush();
+        let Ok(stream) = incoming else {
+            continue;
+        };
+
+        let (tx, rx) = mpsc::sync_channel::<Vec<u8>>(CLIENT_QUEUE);
+        let replay = {
+            let mut lock = match spine.lock() {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            lock.clients.push(tx);
+            lock.replay
+                .then(|| lock.latest.values().cloned().collect::<Vec<_>>())
+        };
+
+        std::thread::spawn(move || client_writer(stream, banner, replay, rx));
+    }
+}
 
-                let mut lock = match clients.lock() {
-                    Ok(v) => v,
-                    Err(_) => continue,
-                };
-                lock.push(stream);
+/// Banner, replay and marker, then live frames until the socket fails.
+/// Dropping `rx` on return unregisters the client at the next broadcast.
+#[cfg(unix)]
+fn client_writer(
+    mut stream: UnixStream,
+    banner: bool,
+    replay: Option<Vec<Vec<u8>>>,
+    rx: mpsc::Receiver<Vec<u8>>,
+) {
+    if banner && stream.write_all(b"# hawkd connected\n").is_err() {
+        return;
+    }
+    if let Some(cached) = replay {
+        for bytes in &cached {
+            if stream.write_all(bytes).is_err() {
+                return;
             }
-            Err(_) => continue,
+        }
+        let marker = format!("{}\n", REPLAY_DONE_MARKER);
+        if stream.write_all(marker.as_bytes()).is_err() {
+            return;
         }
     }
+    let _ = stream.flush();
+
+    for bytes in rx {
+        if stream.write_all(&bytes).is_err() {
+            return;
+        }
+        let _ = stream.flush();
+    }
 }
 // :: ∎
 
@@ -257,26 +351,17 @@ fn spawn_stdin_source(tx: std::sync::mpsc::Sender<Vec<u8>>, strict: bool) {
 // ▛▞// broadcast :: hawkd.sock.broadcast
 // ⫸ [sock.broadcast.dead]
 #[cfg(unix)]
-fn broadcast(clients: &Arc<Mutex<Vec<UnixStream>>>, bytes: &[u8]) {
-    let mut lock = match clients.lock() {
+fn broadcast(spine: &Arc<Mutex<Spine>>, bytes: &[u8]) {
+    let mut spine = match spine.lock() {
         Ok(v) => v,
         Err(_) => return,
     };
+    spine.record(bytes);
 
-    let mut i = 0usize;
-    while i < lock.len() {
-        let mut remove = false;
-        if lock[i].write_all(bytes).is_err() {
-            remove = true;
-        } else {
-            let _ = lock[i].flush();
-        }
-
-        if remove {
-            lock.remove(i);
-        } else {
-            i += 1;
-        }
-    }
+    // Gone or CLIENT_QUEUE frames behind: drop the client.
+    spine.clients.retain(|tx| match tx.try_send(bytes.to_vec()) {
+        Ok(()) => true,
+        Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => false,
+    });
 }
 // :: ∎
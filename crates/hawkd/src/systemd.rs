@@ -197,6 +197,7 @@ async fn emit_snapshot_frame(
         scope: "systemd".to_string(),
         id: spec.id.clone(),
         level,
+        level_raw: None,
         msg: format!("{}:{}", active, sub),
         kv,
     };
@@ -261,6 +262,7 @@ fn emit_error_frame(
         scope: "systemd".to_string(),
         id: spec.id.clone(),
         level: Level::Fail,
+        level_raw: None,
         msg: msg.to_string(),
         kv,
     };
@@ -1,7 +1,7 @@
 // ▛▞// HAWKD::INGEST :: Unix producer socket
 // @ctx ⫸ [unix.accept.emit]
 use std::io::{BufRead, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::Sender;
 
 use hawk_core::{HawkFrame, Level};
@@ -100,7 +100,7 @@ fn handle_ingest_client(
 
 // ▛▞// parse_error_frame :: hawkd.ingest.error
 // ⫸ [emit.hawkd.parseerror]
-fn parse_error_frame(now: OffsetDateTime, ingest_path: &PathBuf, raw: &str, err: String) -> HawkFrame {
+fn parse_error_frame(now: OffsetDateTime, ingest_path: &Path, raw: &str, err: String) -> HawkFrame {
     let mut kv = std::collections::BTreeMap::new();
     kv.insert(
         "ingest_path".to_string(),
@@ -122,6 +122,7 @@ fn parse_error_frame(now: OffsetDateTime, ingest_path: &PathBuf, raw: &str, err:
         scope: "hawkd".to_string(),
         id: "ingest".to_string(),
         level: Level::Warn,
+        level_raw: None,
         msg: "ingest parse error".to_string(),
         kv,
     }
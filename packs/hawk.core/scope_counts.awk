@@ -2,46 +2,55 @@
 # @ctx ⫸ [awk.aggregate.count]
 # @ctx ⫸ [awk.emit.hawkframe]
 # @ctx ⫸ [awk.arg.window_s]
+# @ctx ⫸ [awk.clock.tick]
 BEGIN {
   FS = "\t"; OFS = "\t";
   if (window_s == "") window_s = 10;
-  next_emit = systime() + window_s;
+  next_emit = 0;
 }
 
 function emit(now, scope, lvl, c) {
   # Emit a synthetic HawkFrame TSV line:
   # ts  kind   scope  id            level msg    kv
-  ts = $1;
-  if (ts == "") ts = now;
-
   kind = "RECEIPT_EVENT";
   out_scope = "awk";
-  id = "scope_counts";
+  # One id per bucket: the derived panel keys rows by scope:id.
+  id = scope "|" lvl;
 
   level = "info";
   msg = "count";
   kv = "src_scope=" scope ";src_level=" lvl ";count=" c ";window_s=" window_s;
 
-  print ts, kind, out_scope, id, level, msg, kv;
+  print now, kind, out_scope, id, level, msg, kv;
 }
 
-{
-  s = $3;
-  l = tolower($5);
-  key = s "|" l;
-  counts[key] += 1;
+# hawk feeds TICK frames; $7 carries epoch=<unix seconds>.
+$2 == "TICK" {
+  epoch = $7;
+  sub(/^.*epoch=/, "", epoch);
+  sub(/;.*$/, "", epoch);
+  epoch += 0;
 
-  if (systime() >= next_emit) {
+  if (next_emit == 0) next_emit = epoch + window_s;
+  if (epoch >= next_emit) {
     now = $1;
     if (now == "") now = "1970-01-01T00:00:00Z";
 
     for (k in counts) {
       split(k, parts, "|");
       emit(now, parts[1], parts[2], counts[k]);
-      counts[k] = 0;
     }
+    delete counts;
 
-    next_emit = systime() + window_s;
+    next_emit = epoch + window_s;
   }
+  next;
+}
+
+{
+  s = $3;
+  l = tolower($5);
+  key = s "|" l;
+  counts[key] += 1;
 }
 # :: ∎